@@ -0,0 +1,53 @@
+// Замер пропускной способности при одновременной записи в режимах журнала DELETE и WAL.
+// Соединения открываются через schema::connect с настройками по умолчанию, как у сервера,
+// а запись идет через SqliteTaskRepository.
+// Запуск: cargo bench --bench concurrent_writes
+use std::time::Instant;
+
+use clean_todo_demo::domain::entities::Task;
+use clean_todo_demo::domain::repositories::TaskRepository;
+use clean_todo_demo::infrastructure::schema::{self, ConnectionSettings, JournalMode, MIGRATOR};
+use clean_todo_demo::infrastructure::sqlite::SqliteTaskRepository;
+
+const WRITERS: i64 = 8;
+const WRITES: usize = 200;
+
+fn main() {
+    let runtime = tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap();
+    runtime.block_on(async {
+        for journal_mode in [JournalMode::Delete, JournalMode::Wal] {
+            let path = std::env::temp_dir().join(format!("bench-{:?}-{}.db", journal_mode, std::process::id()));
+            let settings = ConnectionSettings { journal_mode, ..Default::default() };
+            let pool = schema::connect(&format!("sqlite:{}", path.display()), &settings).await.unwrap();
+            MIGRATOR.run(&pool).await.unwrap();
+            let mut repo = SqliteTaskRepository::new(pool.clone()).await;
+            for id in 1..=WRITERS {
+                repo.create(Task { id, title: "Task".to_string(), description: "Desc".to_string(), ..Default::default() }).await.unwrap();
+            }
+
+            // Каждый писатель переключает свою задачу через собственный репозиторий поверх общего пула
+            let started = Instant::now();
+            let mut writers = tokio::task::JoinSet::new();
+            for id in 1..=WRITERS {
+                let pool = pool.clone();
+                writers.spawn(async move {
+                    let mut repo = SqliteTaskRepository::new(pool).await;
+                    for _ in 0..WRITES {
+                        repo.toggle(id).await.unwrap();
+                    }
+                });
+            }
+            while let Some(result) = writers.join_next().await {
+                result.unwrap();
+            }
+            let elapsed = started.elapsed();
+
+            let total = WRITERS as usize * WRITES;
+            println!("{:?}: {} writes in {:?}, {:.0} writes/s", journal_mode, total, elapsed, total as f64 / elapsed.as_secs_f64());
+            pool.close().await;
+            for suffix in ["", "-wal", "-shm"] {
+                let _ = std::fs::remove_file(format!("{}{}", path.display(), suffix));
+            }
+        }
+    });
+}
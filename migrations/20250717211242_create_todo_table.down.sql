@@ -0,0 +1,2 @@
+-- 20250717211242_create_todo_table.down.sql
+DROP TABLE tasks;
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- 20261018090000_add_task_tags.down.sql
+ALTER TABLE tasks DROP COLUMN tags;
\ No newline at end of file
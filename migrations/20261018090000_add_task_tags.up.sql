@@ -0,0 +1,3 @@
+-- 20261018090000_add_task_tags.sql
+-- Метки задачи хранятся JSON-массивом строк
+ALTER TABLE tasks ADD COLUMN tags TEXT NOT NULL DEFAULT '[]';
\ No newline at end of file
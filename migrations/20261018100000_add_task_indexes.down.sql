@@ -0,0 +1,3 @@
+-- 20261018100000_add_task_indexes.down.sql
+DROP INDEX idx_tasks_title;
+DROP INDEX idx_tasks_status;
\ No newline at end of file
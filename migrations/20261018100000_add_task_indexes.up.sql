@@ -0,0 +1,4 @@
+-- 20261018100000_add_task_indexes.up.sql
+-- Индексы под условия поиска задач: по статусу и по шаблону названия (GLOB использует индекс для префикса)
+CREATE INDEX idx_tasks_status ON tasks (status);
+CREATE INDEX idx_tasks_title ON tasks (title);
\ No newline at end of file
@@ -0,0 +1,7 @@
+-- 20261018110000_add_todo_txt_fields.down.sql
+ALTER TABLE tasks DROP COLUMN extensions;
+ALTER TABLE tasks DROP COLUMN projects;
+ALTER TABLE tasks DROP COLUMN due;
+ALTER TABLE tasks DROP COLUMN completed;
+ALTER TABLE tasks DROP COLUMN created;
+ALTER TABLE tasks DROP COLUMN priority;
\ No newline at end of file
@@ -0,0 +1,8 @@
+-- 20261018110000_add_todo_txt_fields.up.sql
+-- Поля формата todo.txt: приоритет, даты (YYYY-MM-DD), проекты (JSON-массив) и прочие расширения key:value (JSON-объект)
+ALTER TABLE tasks ADD COLUMN priority TEXT;
+ALTER TABLE tasks ADD COLUMN created TEXT;
+ALTER TABLE tasks ADD COLUMN completed TEXT;
+ALTER TABLE tasks ADD COLUMN due TEXT;
+ALTER TABLE tasks ADD COLUMN projects TEXT NOT NULL DEFAULT '[]';
+ALTER TABLE tasks ADD COLUMN extensions TEXT NOT NULL DEFAULT '{}';
\ No newline at end of file
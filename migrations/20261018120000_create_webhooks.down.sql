@@ -0,0 +1,3 @@
+-- 20261018120000_create_webhooks.down.sql
+DROP TABLE webhook_deliveries;
+DROP TABLE webhooks;
\ No newline at end of file
@@ -0,0 +1,18 @@
+-- 20261018130000_allow_lost_delivery_events.down.sql
+DELETE FROM webhook_deliveries WHERE event IS NULL;
+CREATE TABLE webhook_deliveries_old (
+    id INTEGER PRIMARY KEY,
+    webhook_id INTEGER NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    delivery TEXT NOT NULL,
+    event_id INTEGER NOT NULL,
+    event TEXT NOT NULL,
+    attempt INTEGER NOT NULL,
+    attempted_at TEXT NOT NULL,
+    status_code INTEGER,
+    error TEXT,
+    succeeded BOOLEAN NOT NULL
+);
+INSERT INTO webhook_deliveries_old SELECT id, webhook_id, delivery, event_id, event, attempt, attempted_at, status_code, error, succeeded FROM webhook_deliveries;
+DROP TABLE webhook_deliveries;
+ALTER TABLE webhook_deliveries_old RENAME TO webhook_deliveries;
+CREATE INDEX idx_webhook_deliveries_webhook ON webhook_deliveries (webhook_id, id);
\ No newline at end of file
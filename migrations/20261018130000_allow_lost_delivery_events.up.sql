@@ -0,0 +1,19 @@
+-- 20261018130000_allow_lost_delivery_events.up.sql
+-- Вид изменения в журнале доставки необязателен: события, потерянные до доставки, записываются без него.
+-- SQLite не умеет снимать NOT NULL со столбца, поэтому таблица пересоздается.
+CREATE TABLE webhook_deliveries_new (
+    id INTEGER PRIMARY KEY,
+    webhook_id INTEGER NOT NULL REFERENCES webhooks (id) ON DELETE CASCADE,
+    delivery TEXT NOT NULL,
+    event_id INTEGER NOT NULL,
+    event TEXT,
+    attempt INTEGER NOT NULL,
+    attempted_at TEXT NOT NULL,
+    status_code INTEGER,
+    error TEXT,
+    succeeded BOOLEAN NOT NULL
+);
+INSERT INTO webhook_deliveries_new SELECT id, webhook_id, delivery, event_id, event, attempt, attempted_at, status_code, error, succeeded FROM webhook_deliveries;
+DROP TABLE webhook_deliveries;
+ALTER TABLE webhook_deliveries_new RENAME TO webhook_deliveries;
+CREATE INDEX idx_webhook_deliveries_webhook ON webhook_deliveries (webhook_id, id);
\ No newline at end of file
@@ -1 +1,10 @@
-pub mod services;
\ No newline at end of file
+pub mod export;
+pub mod feed;
+pub mod history;
+pub mod icalendar;
+pub mod import;
+pub mod patch;
+pub mod services;
+pub mod todo_txt;
+pub mod validation;
+pub mod webhooks;
\ No newline at end of file
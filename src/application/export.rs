@@ -0,0 +1,153 @@
+use std::io::{self, Write};
+
+use crate::application::{icalendar, todo_txt};
+use crate::domain::entities::Task;
+
+// Формат выгрузки задач
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, clap::ValueEnum, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ExportFormat {
+    Csv, // Таблица: id, title, description, status, tags (метки через `;`)
+    Json, // Массив задач с отступами
+    Ndjson, // Одна задача в строке, удобно читать потоком
+    Markdown, // Список дел: `- [x] title`
+    TodoTxt, // Формат todo.txt; выгрузка выполненных задач дает done.txt
+    #[serde(alias = "ics")]
+    #[value(alias = "ics")]
+    Ical, // Календарь iCalendar из компонентов VTODO
+}
+
+impl ExportFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "text/csv; charset=utf-8",
+            ExportFormat::Json => "application/json",
+            ExportFormat::Ndjson => "application/x-ndjson",
+            ExportFormat::Markdown => "text/markdown; charset=utf-8",
+            ExportFormat::TodoTxt => "text/plain; charset=utf-8",
+            ExportFormat::Ical => "text/calendar; charset=utf-8",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Csv => "csv",
+            ExportFormat::Json => "json",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Markdown => "md",
+            ExportFormat::TodoTxt => "txt",
+            ExportFormat::Ical => "ics",
+        }
+    }
+}
+
+// Записывает задачи в выбранном формате
+pub fn export(tasks: &[Task], format: ExportFormat, mut writer: impl Write) -> io::Result<()> {
+    match format {
+        ExportFormat::Csv => {
+            let mut csv = csv::Writer::from_writer(writer);
+            csv.write_record(["id", "title", "description", "status", "tags"])?;
+            for task in tasks {
+                csv.write_record([
+                    task.id.to_string(),
+                    task.title.clone(),
+                    task.description.clone(),
+                    task.status.to_string(),
+                    task.tags.join(";"),
+                ])?;
+            }
+            csv.flush()
+        }
+        ExportFormat::Json => {
+            serde_json::to_writer_pretty(&mut writer, tasks)?;
+            writeln!(writer)?;
+            writer.flush()
+        }
+        ExportFormat::Ndjson => {
+            for task in tasks {
+                writer.write_all(&ndjson_line(task))?;
+            }
+            writer.flush()
+        }
+        ExportFormat::Markdown => {
+            for task in tasks {
+                let mark = if task.status { 'x' } else { ' ' };
+                writeln!(writer, "- [{}] {}", mark, task.title)?;
+            }
+            writer.flush()
+        }
+        ExportFormat::TodoTxt => {
+            for task in tasks {
+                writeln!(writer, "{}", todo_txt::format_line(task))?;
+            }
+            writer.flush()
+        }
+        ExportFormat::Ical => icalendar::write_calendar(tasks, chrono::Utc::now(), writer),
+    }
+}
+
+// Строка NDJSON для одной задачи; используется и для потоковой выгрузки по HTTP
+pub fn ndjson_line(task: &Task) -> Vec<u8> {
+    let mut line = serde_json::to_vec(task).unwrap();
+    line.push(b'\n');
+    line
+}
+
+// Проверяем каждый формат выгрузки на небольшом наборе задач
+#[cfg(test)]
+mod export_tests {
+    use crate::application::export::{export, ExportFormat};
+    use crate::domain::entities::Task;
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            Task { id: 1, title: "Buy milk".to_string(), description: "2 liters".to_string(), status: true, tags: vec!["home".to_string(), "shop".to_string()], ..Default::default() },
+            Task { id: 2, title: "Write \"report\", draft".to_string(), description: "Q3".to_string(), status: false, tags: vec![], ..Default::default() },
+        ]
+    }
+
+    fn render(format: ExportFormat) -> String {
+        let mut output = Vec::new();
+        export(&tasks(), format, &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn csv_escapes_fields() {
+        // Проверяем заголовок, склейку меток и экранирование запятых и кавычек
+        assert_eq!(
+            render(ExportFormat::Csv),
+            "id,title,description,status,tags\n1,Buy milk,2 liters,true,home;shop\n2,\"Write \"\"report\"\", draft\",Q3,false,\n"
+        );
+    }
+
+    #[test]
+    fn json_roundtrips() {
+        // Проверяем, что выгрузка в JSON читается обратно в те же задачи
+        let parsed: Vec<Task> = serde_json::from_str(&render(ExportFormat::Json)).unwrap();
+        assert_eq!(parsed, tasks());
+    }
+
+    #[test]
+    fn ndjson_has_one_task_per_line() {
+        // Проверяем, что каждая строка NDJSON - отдельная задача
+        let output = render(ExportFormat::Ndjson);
+        let parsed: Vec<Task> = output.lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(parsed, tasks());
+    }
+
+    #[test]
+    fn todo_txt_has_contexts_and_description() {
+        // Проверяем отметку выполнения, контексты из меток и описание, отличное от названия
+        assert_eq!(
+            render(ExportFormat::TodoTxt),
+            "x Buy milk @home @shop description:2%20liters id:1\nWrite \"report\", draft description:Q3 id:2\n"
+        );
+    }
+
+    #[test]
+    fn markdown_is_checklist() {
+        // Проверяем отметки выполненных и невыполненных задач
+        assert_eq!(render(ExportFormat::Markdown), "- [x] Buy milk\n- [ ] Write \"report\", draft\n");
+    }
+}
@@ -0,0 +1,251 @@
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+use tokio::sync::broadcast::{self, error::RecvError};
+
+use crate::application::history::Change;
+use crate::domain::entities::Task;
+use crate::domain::events::ChangeKind;
+
+// Сколько последних событий лента хранит для продолжения с Last-Event-ID
+pub const DEFAULT_FEED_BACKLOG: usize = 1000;
+
+// Вид изменения; None, если задача не изменилась
+fn kind_of(change: &Change) -> Option<ChangeKind> {
+    match (&change.before, &change.after) {
+        (None, Some(_)) => Some(ChangeKind::Created),
+        (Some(_), None) => Some(ChangeKind::Deleted),
+        (Some(before), Some(after)) if before == after => None,
+        // Переключение меняет только статус и, возможно, дату выполнения
+        (Some(before), Some(after)) if Task { status: before.status, completed: before.completed, ..after.clone() } == *before => {
+            Some(ChangeKind::Toggled)
+        }
+        (Some(_), Some(_)) => Some(ChangeKind::Updated),
+        (None, None) => None,
+    }
+}
+
+// Событие ленты изменений. Номера событий растут с запуска сервера.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FeedEvent {
+    pub id: u64, // Номер события
+    pub kind: ChangeKind,
+    pub task: Task, // Задача после изменения; для удаленной - последнее ее состояние
+    pub before: Option<Box<Task>>, // Задача до изменения; None для созданной
+}
+
+// Что получает подписчик
+#[derive(Clone, Debug, PartialEq)]
+pub enum FeedItem {
+    Event(FeedEvent),
+    // Часть событий потеряна: подписчик отстал или продолжает с события, которого
+    // лента уже не помнит. Клиенту нужно заново прочитать список задач.
+    Reset,
+}
+
+// Отбор событий подписчика. Пустые поля не участвуют в отборе.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FeedFilter {
+    pub tag: Option<String>, // Метка, которая должна быть у задачи
+    pub project: Option<String>, // Проект, к которому должна относиться задача
+}
+
+impl FeedFilter {
+    fn matches(&self, task: &Task) -> bool {
+        self.tag.as_ref().is_none_or(|tag| task.tags.contains(tag))
+            && self.project.as_ref().is_none_or(|project| task.projects.contains(project))
+    }
+
+    // Событие подходит, если задача подходила до изменения или подходит после него:
+    // так подписчик узнает и о задаче, которая вышла из отбора
+    pub fn accepts(&self, event: &FeedEvent) -> bool {
+        self.matches(&event.task) || event.before.as_ref().is_some_and(|before| self.matches(before))
+    }
+}
+
+struct FeedState {
+    last_id: u64, // Номер последнего опубликованного события
+    backlog: VecDeque<FeedEvent>, // Последние события, от старых к новым
+    capacity: usize,
+}
+
+// Лента изменений задач внутри процесса. TaskService публикует в нее каждое
+// изменение, а подписчики (SSE и WebSocket) получают события по мере появления.
+#[derive(Clone)]
+pub struct ChangeFeed {
+    state: Arc<Mutex<FeedState>>,
+    sender: broadcast::Sender<FeedEvent>,
+}
+
+impl ChangeFeed {
+    pub fn new(capacity: usize) -> ChangeFeed {
+        let (sender, _) = broadcast::channel(capacity.max(1));
+        ChangeFeed { state: Arc::new(Mutex::new(FeedState { last_id: 0, backlog: VecDeque::new(), capacity })), sender }
+    }
+
+    // Публикует изменения одной операции по порядку
+    pub fn publish(&self, changes: &[Change]) {
+        let mut state = self.state.lock().unwrap();
+        for change in changes {
+            let (Some(kind), Some(task)) = (kind_of(change), change.after.as_ref().or(change.before.as_ref())) else {
+                continue;
+            };
+            state.last_id += 1;
+            let event = FeedEvent { id: state.last_id, kind, task: task.clone(), before: change.before.clone().map(Box::new) };
+            if state.backlog.len() == state.capacity {
+                state.backlog.pop_front();
+            }
+            if state.capacity > 0 {
+                state.backlog.push_back(event.clone());
+            }
+            // Ошибка означает только, что подписчиков сейчас нет
+            let _ = self.sender.send(event);
+        }
+    }
+
+    // Подписка на события после события `after`; без него - только на новые события
+    pub fn subscribe(&self, after: Option<u64>, filter: FeedFilter) -> Subscription {
+        // Под блокировкой ни одно событие не попадет одновременно в пропущенные и в канал
+        let state = self.state.lock().unwrap();
+        let mut pending = VecDeque::new();
+        if let Some(after) = after {
+            let oldest = state.backlog.front().map(|event| event.id).unwrap_or(state.last_id + 1);
+            // Номер новее последнего события остался от предыдущего запуска сервера
+            if after > state.last_id || after + 1 < oldest {
+                pending.push_back(FeedItem::Reset);
+            }
+            pending.extend(
+                state.backlog.iter().filter(|event| event.id > after && filter.accepts(event)).cloned().map(FeedItem::Event),
+            );
+        }
+        Subscription { pending, receiver: self.sender.subscribe(), filter }
+    }
+}
+
+pub struct Subscription {
+    pending: VecDeque<FeedItem>, // Пропущенные события, которые отдаются раньше новых
+    receiver: broadcast::Receiver<FeedEvent>,
+    filter: FeedFilter,
+}
+
+impl Subscription {
+    // Следующее подходящее событие; None, когда лента закрыта
+    pub async fn next(&mut self) -> Option<FeedItem> {
+        if let Some(item) = self.pending.pop_front() {
+            return Some(item);
+        }
+        loop {
+            match self.receiver.recv().await {
+                Ok(event) if self.filter.accepts(&event) => return Some(FeedItem::Event(event)),
+                Ok(_) => continue,
+                Err(RecvError::Lagged(_)) => return Some(FeedItem::Reset),
+                Err(RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+// Проверяем публикацию изменений, продолжение с номера события и отбор
+#[cfg(test)]
+mod feed_tests {
+    use crate::application::feed::{ChangeFeed, FeedEvent, FeedFilter, FeedItem};
+    use crate::application::history::Change;
+    use crate::domain::entities::Task;
+    use crate::domain::events::ChangeKind;
+
+    fn task(id: i64, tags: &[&str]) -> Task {
+        Task { id, title: format!("Task {id}"), tags: tags.iter().map(|tag| tag.to_string()).collect(), ..Default::default() }
+    }
+
+    fn kinds(items: &[FeedItem]) -> Vec<Option<(u64, ChangeKind)>> {
+        items
+            .iter()
+            .map(|item| match item {
+                FeedItem::Event(event) => Some((event.id, event.kind)),
+                FeedItem::Reset => None,
+            })
+            .collect()
+    }
+
+    #[tokio::test]
+    async fn changes_are_classified_and_delivered() {
+        // Проверяем вид каждого изменения и доставку подписчику в порядке публикации
+        let feed = ChangeFeed::new(10);
+        let mut subscription = feed.subscribe(None, FeedFilter::default());
+        let created = task(1, &[]);
+        let toggled = Task { status: true, ..created.clone() };
+        let updated = Task { title: "Renamed".to_string(), ..toggled.clone() };
+        feed.publish(&[
+            Change { before: None, after: Some(created.clone()) },
+            Change { before: Some(created.clone()), after: Some(toggled.clone()) },
+            Change { before: Some(toggled.clone()), after: Some(toggled.clone()) },
+            Change { before: Some(toggled), after: Some(updated.clone()) },
+            Change { before: Some(updated.clone()), after: None },
+        ]);
+
+        let mut items = Vec::new();
+        for _ in 0..4 {
+            items.push(subscription.next().await.unwrap());
+        }
+        assert_eq!(
+            kinds(&items),
+            vec![Some((1, ChangeKind::Created)), Some((2, ChangeKind::Toggled)), Some((3, ChangeKind::Updated)), Some((4, ChangeKind::Deleted))]
+        );
+        // Удаленная задача приходит в последнем состоянии
+        assert_eq!(items[3], FeedItem::Event(FeedEvent { id: 4, kind: ChangeKind::Deleted, task: updated.clone(), before: Some(Box::new(updated)) }));
+    }
+
+    #[tokio::test]
+    async fn resumes_after_event_or_resets() {
+        // Проверяем продолжение с номера события и сброс, если лента его уже не помнит
+        let feed = ChangeFeed::new(2);
+        for id in 1..=3 {
+            feed.publish(&[Change { before: None, after: Some(task(id, &[])) }]);
+        }
+
+        let mut subscription = feed.subscribe(Some(2), FeedFilter::default());
+        assert_eq!(kinds(&[subscription.next().await.unwrap()]), vec![Some((3, ChangeKind::Created))]);
+        let mut subscription = feed.subscribe(Some(0), FeedFilter::default());
+        let items = vec![subscription.next().await.unwrap(), subscription.next().await.unwrap(), subscription.next().await.unwrap()];
+        assert_eq!(kinds(&items), vec![None, Some((2, ChangeKind::Created)), Some((3, ChangeKind::Created))]);
+        let mut subscription = feed.subscribe(Some(7), FeedFilter::default());
+        assert_eq!(subscription.next().await, Some(FeedItem::Reset));
+    }
+
+    #[tokio::test]
+    async fn filter_by_tag_and_project() {
+        // Проверяем, что подписчик получает только задачи с нужной меткой и проектом
+        let feed = ChangeFeed::new(10);
+        let filter = FeedFilter { tag: Some("work".to_string()), project: Some("site".to_string()) };
+        let mut subscription = feed.subscribe(None, filter);
+        let mut matching = task(3, &["work"]);
+        matching.projects = vec!["site".to_string()];
+        feed.publish(&[
+            Change { before: None, after: Some(task(1, &["work"])) },
+            Change { before: None, after: Some(Task { projects: vec!["site".to_string()], ..task(2, &[]) }) },
+            Change { before: None, after: Some(matching) },
+        ]);
+        assert_eq!(kinds(&[subscription.next().await.unwrap()]), vec![Some((3, ChangeKind::Created))]);
+    }
+
+    #[tokio::test]
+    async fn task_leaving_filter_is_delivered() {
+        // Проверяем, что подписчик узнает о снятии метки, но не о дальнейших изменениях задачи
+        let feed = ChangeFeed::new(10);
+        let filter = FeedFilter { tag: Some("work".to_string()), ..Default::default() };
+        let mut subscription = feed.subscribe(None, filter.clone());
+        let tagged = task(1, &["work"]);
+        let untagged = task(1, &[]);
+        feed.publish(&[
+            Change { before: Some(tagged.clone()), after: Some(untagged.clone()) },
+            Change { before: Some(untagged.clone()), after: Some(Task { title: "Renamed".to_string(), ..untagged.clone() }) },
+            Change { before: Some(untagged.clone()), after: Some(tagged.clone()) },
+        ]);
+        let items = vec![subscription.next().await.unwrap(), subscription.next().await.unwrap()];
+        assert_eq!(kinds(&items), vec![Some((1, ChangeKind::Updated)), Some((3, ChangeKind::Updated))]);
+        assert_eq!(items[0], FeedItem::Event(FeedEvent { id: 1, kind: ChangeKind::Updated, task: untagged, before: Some(Box::new(tagged)) }));
+        // Продолжение с номера события отбирает так же
+        let mut subscription = feed.subscribe(Some(0), filter);
+        assert_eq!(kinds(&[subscription.next().await.unwrap(), subscription.next().await.unwrap()]), kinds(&items));
+    }
+}
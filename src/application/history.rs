@@ -0,0 +1,174 @@
+use std::collections::VecDeque;
+use std::num::NonZeroUsize;
+
+use lru::LruCache;
+
+use crate::domain::entities::Task;
+
+// Глубина истории по умолчанию
+pub const DEFAULT_HISTORY_DEPTH: usize = 20;
+// Сколько сессий хранится одновременно. Идентификатор сессии задает клиент, поэтому
+// история самой давно не использованной сессии вытесняется, а не копится без предела.
+pub const DEFAULT_HISTORY_SESSIONS: NonZeroUsize = NonZeroUsize::new(1000).unwrap();
+
+// Изменение задачи: состояние до и после операции.
+// None означает, что задачи не существовало (до создания или после удаления).
+#[derive(Clone, Debug, PartialEq)]
+pub struct Change {
+    pub before: Option<Task>,
+    pub after: Option<Task>,
+}
+
+// Операция в истории - набор изменений, которые отменяются и повторяются вместе
+pub type Operation = Vec<Change>;
+
+// Сводит изменения одной операции к одному изменению на задачу: состояние до первого
+// и после последнего. Задачи, созданные и удаленные внутри операции, выпадают.
+pub fn squash(changes: Vec<Change>) -> Operation {
+    let mut squashed: Vec<Change> = Vec::with_capacity(changes.len());
+    for change in changes {
+        let id = change.before.as_ref().or(change.after.as_ref()).map(|task| task.id);
+        let same_task = |existing: &&mut Change| existing.before.as_ref().or(existing.after.as_ref()).map(|task| task.id) == id;
+        match squashed.iter_mut().find(same_task) {
+            Some(existing) => existing.after = change.after,
+            None => squashed.push(change),
+        }
+    }
+    squashed.retain(|change| change.before.is_some() || change.after.is_some());
+    squashed
+}
+
+// Стеки отмены и повтора одной сессии
+#[derive(Default)]
+struct SessionHistory {
+    undo: VecDeque<Operation>,
+    redo: Vec<Operation>,
+}
+
+// История изменений, которую TaskService ведет отдельно для каждой сессии клиента
+pub struct History {
+    depth: usize, // Максимальное количество операций, которые можно отменить
+    sessions: LruCache<String, SessionHistory>, // Истории сессий, давно не использованные вытесняются
+}
+
+impl History {
+    pub fn new(depth: usize) -> History {
+        History::with_sessions(depth, DEFAULT_HISTORY_SESSIONS)
+    }
+
+    pub fn with_sessions(depth: usize, sessions: NonZeroUsize) -> History {
+        History { depth, sessions: LruCache::new(sessions) }
+    }
+
+    // Запоминает новую операцию. Ветка повтора при этом теряет смысл и очищается.
+    pub fn record(&mut self, session: &str, operation: Operation) {
+        if self.depth == 0 || operation.is_empty() {
+            return;
+        }
+        let history = self.sessions.get_or_insert_mut(session.to_string(), SessionHistory::default);
+        history.redo.clear();
+        Self::push_bounded(&mut history.undo, operation, self.depth);
+    }
+
+    // Достает последнюю операцию для отмены
+    pub fn pop_undo(&mut self, session: &str) -> Option<Operation> {
+        self.sessions.get_mut(session)?.undo.pop_back()
+    }
+
+    // Достает последнюю отмененную операцию для повтора
+    pub fn pop_redo(&mut self, session: &str) -> Option<Operation> {
+        self.sessions.get_mut(session)?.redo.pop()
+    }
+
+    // Запоминает успешно отмененную операцию, чтобы ее можно было повторить
+    pub fn push_redo(&mut self, session: &str, operation: Operation) {
+        let history = self.sessions.get_or_insert_mut(session.to_string(), SessionHistory::default);
+        history.redo.push(operation);
+    }
+
+    // Возвращает повторенную операцию в стек отмены, не трогая ветку повтора
+    pub fn push_undo(&mut self, session: &str, operation: Operation) {
+        let history = self.sessions.get_or_insert_mut(session.to_string(), SessionHistory::default);
+        Self::push_bounded(&mut history.undo, operation, self.depth);
+    }
+
+    fn push_bounded(stack: &mut VecDeque<Operation>, operation: Operation, depth: usize) {
+        stack.push_back(operation);
+        while stack.len() > depth {
+            stack.pop_front();
+        }
+    }
+}
+
+// Проверяем ограничение глубины и разделение историй по сессиям
+#[cfg(test)]
+mod history_tests {
+    use std::num::NonZeroUsize;
+
+    use crate::application::history::{self, Change, History};
+    use crate::domain::entities::Task;
+
+    fn created(id: i64) -> Vec<Change> {
+        let task = Task { id, title: "T".to_string(), description: "D".to_string(), status: false, ..Default::default() };
+        vec![Change { before: None, after: Some(task) }]
+    }
+
+    #[test]
+    fn depth_is_bounded() {
+        // Проверяем, что самые старые операции вытесняются при превышении глубины
+        let mut history = History::new(2);
+        history.record("a", created(1));
+        history.record("a", created(2));
+        history.record("a", created(3));
+
+        assert_eq!(history.pop_undo("a"), Some(created(3)));
+        assert_eq!(history.pop_undo("a"), Some(created(2)));
+        assert_eq!(history.pop_undo("a"), None);
+    }
+
+    #[test]
+    fn sessions_are_independent() {
+        // Проверяем, что сессии не видят операции друг друга
+        let mut history = History::new(10);
+        history.record("a", created(1));
+        assert_eq!(history.pop_undo("b"), None);
+        assert_eq!(history.pop_undo("a"), Some(created(1)));
+    }
+
+    #[test]
+    fn idle_sessions_are_evicted() {
+        // Проверяем, что при превышении числа сессий вытесняется давно не использованная
+        let mut history = History::with_sessions(10, NonZeroUsize::new(2).unwrap());
+        history.record("a", created(1));
+        history.record("b", created(2));
+        history.record("a", created(3));
+        history.record("c", created(4));
+
+        assert_eq!(history.pop_undo("b"), None);
+        assert_eq!(history.pop_undo("a"), Some(created(3)));
+        assert_eq!(history.pop_undo("c"), Some(created(4)));
+    }
+
+    #[test]
+    fn squash_keeps_one_change_per_task() {
+        // Проверяем, что несколько изменений задачи сводятся к первому и последнему состоянию
+        let first = created(1).remove(0).after;
+        let done = first.clone().map(|task| Task { status: true, ..task });
+        let changes = vec![
+            Change { before: None, after: first.clone() },
+            Change { before: first.clone(), after: done.clone() },
+            Change { before: None, after: created(2).remove(0).after },
+            Change { before: created(2).remove(0).after, after: None },
+        ];
+        assert_eq!(history::squash(changes), vec![Change { before: None, after: done }]);
+    }
+
+    #[test]
+    fn new_operation_clears_redo() {
+        // Проверяем, что новая операция делает повтор отмененных невозможным
+        let mut history = History::new(10);
+        history.push_redo("a", created(1));
+        history.record("a", created(2));
+        assert_eq!(history.pop_redo("a"), None);
+    }
+}
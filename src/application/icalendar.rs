@@ -0,0 +1,356 @@
+use std::io::{self, Write};
+
+use chrono::{DateTime, NaiveDate, Utc};
+
+use crate::application::import::{ImportRecord, ParsedRecord, RecordError};
+use crate::domain::entities::Task;
+
+// Преобразование задач в компоненты VTODO календаря iCalendar (RFC 5545) и обратно.
+//
+// SUMMARY, DESCRIPTION и CATEGORIES соответствуют названию, описанию и меткам задачи,
+// STATUS - отметке выполнения, CREATED, DUE и COMPLETED - датам, PRIORITY - приоритету
+// (A..I записываются как 1..9, более низкие приоритеты - как 9).
+// UID задачи имеет вид `task-<id>@clean-todo-demo`; UID из чужих календарей при загрузке
+// сохраняется в расширении `uid` и записывается обратно при выгрузке.
+// Проектам и прочим расширениям todo.txt в VTODO соответствия нет, в календарь они не попадают.
+
+const UID_SUFFIX: &str = "@clean-todo-demo";
+const LINE_LIMIT: usize = 75; // Максимальная длина строки в октетах без CRLF
+
+// Записывает задачи календарем из компонентов VTODO с отметкой времени DTSTAMP `stamp`
+pub fn write_calendar(tasks: &[Task], stamp: DateTime<Utc>, mut writer: impl Write) -> io::Result<()> {
+    let stamp = stamp.format("%Y%m%dT%H%M%SZ").to_string();
+    write_line(&mut writer, "BEGIN:VCALENDAR")?;
+    write_line(&mut writer, "VERSION:2.0")?;
+    write_line(&mut writer, "PRODID:-//clean-todo-demo//Tasks//EN")?;
+    write_line(&mut writer, "X-WR-CALNAME:Tasks")?;
+    for task in tasks {
+        let uid = match task.extensions.get("uid") {
+            Some(uid) => uid.clone(),
+            None => format!("task-{}{}", task.id, UID_SUFFIX),
+        };
+        write_line(&mut writer, "BEGIN:VTODO")?;
+        write_line(&mut writer, &format!("UID:{}", escape(&uid)))?;
+        write_line(&mut writer, &format!("DTSTAMP:{}", stamp))?;
+        write_line(&mut writer, &format!("SUMMARY:{}", escape(&task.title)))?;
+        write_line(&mut writer, &format!("DESCRIPTION:{}", escape(&task.description)))?;
+        write_line(&mut writer, if task.status { "STATUS:COMPLETED" } else { "STATUS:NEEDS-ACTION" })?;
+        if !task.tags.is_empty() {
+            let categories: Vec<String> = task.tags.iter().map(|tag| escape(tag)).collect();
+            write_line(&mut writer, &format!("CATEGORIES:{}", categories.join(",")))?;
+        }
+        // Приоритеты после I сводятся к низшему уровню 9, прочие символы в iCalendar не переносятся
+        if let Some(priority @ 'A'..='Z') = task.priority {
+            let level = (priority as u8 - b'A').min(8) + 1;
+            write_line(&mut writer, &format!("PRIORITY:{}", level))?;
+        }
+        // CREATED и COMPLETED по стандарту - дата и время в UTC, DUE может быть датой
+        if let Some(created) = task.created {
+            write_line(&mut writer, &format!("CREATED:{}T000000Z", created.format("%Y%m%d")))?;
+        }
+        if let Some(due) = task.due {
+            write_line(&mut writer, &format!("DUE;VALUE=DATE:{}", due.format("%Y%m%d")))?;
+        }
+        if let Some(completed) = task.completed {
+            write_line(&mut writer, &format!("COMPLETED:{}T000000Z", completed.format("%Y%m%d")))?;
+        }
+        write_line(&mut writer, "END:VTODO")?;
+    }
+    write_line(&mut writer, "END:VCALENDAR")?;
+    writer.flush()
+}
+
+// Разбирает календарь на записи по одной на каждый VTODO; остальные компоненты пропускаются.
+// Ошибка в отдельном VTODO не мешает разбору остальных.
+pub fn parse_calendar(input: &str) -> Result<Vec<ParsedRecord>, String> {
+    let lines = unfold(input);
+    if !lines.first().is_some_and(|line| line.eq_ignore_ascii_case("BEGIN:VCALENDAR")) {
+        return Err("not an iCalendar file: expected BEGIN:VCALENDAR".to_string());
+    }
+
+    let mut records = Vec::new();
+    let mut todo: Option<Vec<&str>> = None; // Свойства текущего VTODO
+    let mut depth = 0; // Вложенность компонентов внутри VTODO (например, VALARM)
+    for line in &lines {
+        let line = line.as_str();
+        match todo.as_mut() {
+            None => {
+                if line.eq_ignore_ascii_case("BEGIN:VTODO") {
+                    todo = Some(Vec::new());
+                }
+            }
+            Some(properties) => {
+                let upper = line.to_ascii_uppercase();
+                if upper.starts_with("BEGIN:") {
+                    depth += 1;
+                } else if depth > 0 && upper.starts_with("END:") {
+                    depth -= 1;
+                } else if upper == "END:VTODO" {
+                    records.push(parse_todo(properties));
+                    todo = None;
+                } else if depth == 0 {
+                    properties.push(line);
+                }
+            }
+        }
+    }
+    if todo.is_some() {
+        records.push(Err(RecordError::Malformed("VTODO is not terminated with END:VTODO".to_string())));
+    }
+    Ok(records)
+}
+
+fn parse_todo(lines: &[&str]) -> ParsedRecord {
+    let mut record = ImportRecord::default();
+    let mut description = None;
+    for line in lines {
+        let (name, value) = split_property(line).ok_or_else(|| format!("malformed content line: {}", line))?;
+        match name.as_str() {
+            "UID" => {
+                let uid = unescape(value);
+                let own = uid.strip_prefix("task-").and_then(|rest| rest.strip_suffix(UID_SUFFIX)).and_then(|id| id.parse().ok());
+                match own {
+                    Some(id) => record.id = Some(id),
+                    None => {
+                        record.extensions.insert("uid".to_string(), uid);
+                    }
+                }
+            }
+            "SUMMARY" => record.title = unescape(value),
+            "DESCRIPTION" => description = Some(unescape(value)),
+            "STATUS" => record.status = value.eq_ignore_ascii_case("COMPLETED"),
+            "CATEGORIES" => record.tags.extend(split_list(value)),
+            "PRIORITY" => {
+                let level: u8 = value.trim().parse().map_err(|_| format!("invalid PRIORITY: {}", value))?;
+                record.priority = match level {
+                    0 => None, // 0 - приоритет не задан
+                    1..=9 => Some((b'A' + level - 1) as char),
+                    _ => return Err(RecordError::Malformed(format!("invalid PRIORITY: {}", value))),
+                };
+            }
+            "CREATED" => record.created = Some(parse_date(value)?),
+            "DUE" => record.due = Some(parse_date(value)?),
+            "COMPLETED" => record.completed = Some(parse_date(value)?),
+            _ => {}
+        }
+    }
+    // В календарях описание часто не заполняют; без него задача не прошла бы проверку
+    record.description = description.unwrap_or_else(|| record.title.clone());
+    Ok(record)
+}
+
+// Записывает строку, перенося ее по 75 октетов: продолжение начинается с пробела
+fn write_line(writer: &mut impl Write, line: &str) -> io::Result<()> {
+    let mut rest = line;
+    let mut limit = LINE_LIMIT;
+    while rest.len() > limit {
+        let mut split = limit;
+        while !rest.is_char_boundary(split) {
+            split -= 1;
+        }
+        writer.write_all(&rest.as_bytes()[..split])?;
+        writer.write_all(b"\r\n ")?;
+        rest = &rest[split..];
+        limit = LINE_LIMIT - 1;
+    }
+    writer.write_all(rest.as_bytes())?;
+    writer.write_all(b"\r\n")
+}
+
+// Склеивает перенесенные строки; допускаются и CRLF, и LF
+fn unfold(input: &str) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for line in input.split('\n') {
+        let line = line.strip_suffix('\r').unwrap_or(line);
+        match (line.strip_prefix([' ', '\t']), lines.last_mut()) {
+            (Some(continuation), Some(last)) => last.push_str(continuation),
+            _ if line.is_empty() => {}
+            _ => lines.push(line.to_string()),
+        }
+    }
+    lines
+}
+
+// Имя свойства в верхнем регистре и значение. Параметры (`;VALUE=DATE`) отбрасываются;
+// двоеточие внутри параметра в кавычках не считается началом значения.
+fn split_property(line: &str) -> Option<(String, &str)> {
+    let mut quoted = false;
+    let colon = line.char_indices().find_map(|(index, c)| match c {
+        '"' => {
+            quoted = !quoted;
+            None
+        }
+        ':' if !quoted => Some(index),
+        _ => None,
+    })?;
+    let name = line[..colon].split(';').next()?;
+    if name.is_empty() {
+        return None;
+    }
+    Some((name.to_ascii_uppercase(), &line[colon + 1..]))
+}
+
+fn parse_date(value: &str) -> Result<NaiveDate, String> {
+    value
+        .get(..8)
+        .and_then(|date| NaiveDate::parse_from_str(date, "%Y%m%d").ok())
+        .ok_or_else(|| format!("invalid date: {}", value))
+}
+
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    let mut chars = value.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            ';' => escaped.push_str("\\;"),
+            ',' => escaped.push_str("\\,"),
+            '\n' => escaped.push_str("\\n"),
+            // Переводы строк Windows и старых Mac становятся одним \n
+            '\r' => {
+                chars.next_if_eq(&'\n');
+                escaped.push_str("\\n");
+            }
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn unescape(value: &str) -> String {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n' | 'N') => unescaped.push('\n'),
+            Some(c) => unescaped.push(c),
+            None => unescaped.push('\\'),
+        }
+    }
+    unescaped
+}
+
+// Значения списка, разделенные неэкранированными запятыми
+fn split_list(value: &str) -> Vec<String> {
+    let mut items = Vec::new();
+    let mut start = 0;
+    let mut escaped = false;
+    for (index, c) in value.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            ',' => {
+                items.push(unescape(&value[start..index]));
+                start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(unescape(&value[start..]));
+    items.retain(|item| !item.is_empty());
+    items
+}
+
+// Проверяем запись и разбор VTODO, в том числе календарей из сторонних программ
+#[cfg(test)]
+mod icalendar_tests {
+    use chrono::NaiveDate;
+
+    use crate::application::icalendar::{parse_calendar, write_calendar};
+    use crate::domain::entities::Task;
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            Task {
+                id: 1,
+                title: "Buy milk, bread; eggs".to_string(),
+                description: "From the shop\non the corner \\ near home".to_string(),
+                status: true,
+                tags: vec!["home".to_string(), "a,b".to_string()],
+                priority: Some('B'),
+                created: NaiveDate::from_ymd_opt(2026, 10, 1),
+                completed: NaiveDate::from_ymd_opt(2026, 10, 2),
+                ..Default::default()
+            },
+            Task {
+                id: 2,
+                title: "Очень длинное название задачи, которое не помещается в одну строку календаря".to_string(),
+                description: "Desc".to_string(),
+                due: NaiveDate::from_ymd_opt(2026, 10, 5),
+                ..Default::default()
+            },
+        ]
+    }
+
+    fn render(tasks: &[Task]) -> String {
+        let mut output = Vec::new();
+        write_calendar(tasks, chrono::Utc::now(), &mut output).unwrap();
+        String::from_utf8(output).unwrap()
+    }
+
+    #[test]
+    fn calendar_roundtrips() {
+        // Проверяем, что выгруженный календарь читается обратно в те же задачи
+        let parsed: Vec<Task> = parse_calendar(&render(&tasks()))
+            .unwrap()
+            .into_iter()
+            .map(|record| {
+                let record = record.unwrap();
+                let id = record.id.unwrap();
+                record.into_task(id)
+            })
+            .collect();
+        assert_eq!(parsed, tasks());
+    }
+
+    #[test]
+    fn lines_are_folded_and_escaped() {
+        // Проверяем перенос длинных строк, CRLF и экранирование спецсимволов
+        let output = render(&tasks());
+        assert!(output.split_terminator('\n').all(|line| line.ends_with('\r') && line.len() <= 76));
+        assert!(output.contains("\r\nSUMMARY:Buy milk\\, bread\\; eggs\r\n"));
+        assert!(output.contains("\r\nCATEGORIES:home,a\\,b\r\n"));
+        assert!(output.contains("\r\nUID:task-2@clean-todo-demo\r\n"));
+        assert!(output.contains("\r\nDUE;VALUE=DATE:20261005\r\n"));
+        assert!(output.contains("\r\nPRIORITY:2\r\n"));
+    }
+
+    #[test]
+    fn line_breaks_and_priorities_are_normalized() {
+        // Проверяем, что CR и CRLF в тексте становятся \n, а приоритет не из A-Z не выгружается
+        let task = |description: &str, priority| Task { id: 1, title: "T".to_string(), description: description.to_string(), priority, ..Default::default() };
+        let output = render(&[task("one\r\ntwo\rthree", Some('Z')), task("D", Some('Я'))]);
+        assert!(output.contains("\r\nDESCRIPTION:one\\ntwo\\nthree\r\n"));
+        assert!(output.contains("\r\nPRIORITY:9\r\n"));
+        assert_eq!(output.matches("PRIORITY").count(), 1);
+    }
+
+    #[test]
+    fn foreign_calendar_is_imported() {
+        // Проверяем календарь сторонней программы: чужой UID, вложенный VALARM, события и перенос строк
+        let input = "BEGIN:VCALENDAR\nVERSION:2.0\nBEGIN:VEVENT\nUID:event\nSUMMARY:Meeting\nEND:VEVENT\n\
+            BEGIN:VTODO\nUID:abc-123@example.com\nSUMMARY:Submit\n  taxes\nDUE;TZID=\"Europe/Moscow\":20261015T180000\n\
+            BEGIN:VALARM\nACTION:DISPLAY\nDESCRIPTION:Reminder\nEND:VALARM\nEND:VTODO\n\
+            BEGIN:VTODO\nSUMMARY:Broken\nDUE:tomorrow\nEND:VTODO\nEND:VCALENDAR\n";
+        let records = parse_calendar(input).unwrap();
+        assert_eq!(records.len(), 2);
+
+        let task = records[0].clone().unwrap().into_task(7);
+        assert_eq!(task.title, "Submit taxes");
+        assert_eq!(task.description, "Submit taxes");
+        assert_eq!(task.due, NaiveDate::from_ymd_opt(2026, 10, 15));
+        assert_eq!(task.extensions["uid"], "abc-123@example.com");
+        assert!(render(&[task]).contains("\r\nUID:abc-123@example.com\r\n"));
+        assert!(records[1].is_err());
+    }
+
+    #[test]
+    fn non_calendar_is_rejected() {
+        // Проверяем, что файл без VCALENDAR отклоняется целиком
+        assert!(parse_calendar("SUMMARY:Task\n").is_err());
+    }
+}
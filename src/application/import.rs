@@ -0,0 +1,314 @@
+pub mod github;
+pub mod todoist;
+pub mod trello;
+
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use serde_json::Value;
+
+use crate::application::services::TaskServiceError;
+use crate::application::{icalendar, todo_txt};
+use crate::domain::entities::{Task, TaskId};
+
+// Формат загружаемого файла; совпадает с соответствующими форматами выгрузки
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, clap::ValueEnum, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ImportFormat {
+    Csv, // Таблица: id, title, description, status, tags (метки через `;`)
+    Json, // Массив задач
+    Ndjson, // Одна задача в строке
+    TodoTxt, // Одна задача в строке в формате todo.txt (подходит и для done.txt)
+    #[serde(alias = "ics")]
+    #[value(alias = "ics")]
+    Ical, // Календарь iCalendar; задачами становятся компоненты VTODO
+    Trello, // Выгрузка доски Trello в JSON
+    Todoist, // Резервная копия Todoist в JSON (формат Sync API)
+    Github, // Список задач GitHub Issues в JSON (REST API или `gh issue list --json`)
+}
+
+// Что делать с записью, ID которой уже занят
+#[derive(Clone, Copy, Debug, Default, PartialEq, serde::Deserialize, clap::ValueEnum, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ConflictMode {
+    #[default]
+    Skip, // Оставить существующую задачу
+    Abort, // Ничего не загружать
+    Upsert, // Заменить существующую задачу
+}
+
+// Запись загружаемого файла. Отсутствующие поля считаются пустыми,
+// чтобы такие записи отклонялись теми же проверками, что и при создании задачи.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize)]
+pub struct ImportRecord {
+    #[serde(default)]
+    pub id: Option<TaskId>, // ID задачи; без него задача получает новый ID
+    #[serde(default)]
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub status: bool,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    #[serde(default)]
+    pub priority: Option<char>,
+    #[serde(default)]
+    pub created: Option<NaiveDate>,
+    #[serde(default)]
+    pub completed: Option<NaiveDate>,
+    #[serde(default)]
+    pub due: Option<NaiveDate>,
+    #[serde(default)]
+    pub projects: Vec<String>,
+    #[serde(default)]
+    pub extensions: BTreeMap<String, String>,
+    #[serde(skip)]
+    pub unmapped: Vec<String>, // Заполненные поля исходной записи, которым нет места в задаче
+}
+
+impl ImportRecord {
+    // Задача с указанным ID и полями записи
+    pub fn into_task(self, id: TaskId) -> Task {
+        Task {
+            id,
+            title: self.title,
+            description: self.description,
+            status: self.status,
+            tags: self.tags,
+            priority: self.priority,
+            created: self.created,
+            completed: self.completed,
+            due: self.due,
+            projects: self.projects,
+            extensions: self.extensions,
+        }
+    }
+}
+
+// Строка CSV: метки записаны одной ячейкой через `;`
+#[derive(serde::Deserialize)]
+struct CsvRecord {
+    id: Option<TaskId>,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: String,
+    status: Option<bool>,
+    tags: Option<String>,
+}
+
+impl From<CsvRecord> for ImportRecord {
+    fn from(record: CsvRecord) -> Self {
+        ImportRecord {
+            id: record.id,
+            title: record.title,
+            description: record.description,
+            status: record.status.unwrap_or(false),
+            tags: record
+                .tags
+                .unwrap_or_default()
+                .split(';')
+                .filter(|tag| !tag.is_empty())
+                .map(str::to_string)
+                .collect(),
+            ..Default::default()
+        }
+    }
+}
+
+// Почему из записи файла не получилась задача
+#[derive(Clone, Debug, PartialEq)]
+pub enum RecordError {
+    Malformed(String), // Запись не удалось разобрать
+    Skipped(String), // Запись не описывает задачу, например pull request среди задач GitHub
+}
+
+impl From<String> for RecordError {
+    fn from(message: String) -> Self {
+        RecordError::Malformed(message)
+    }
+}
+
+// Разобранная запись или описание того, почему ее не удалось разобрать
+pub type ParsedRecord = Result<ImportRecord, RecordError>;
+
+// Разбирает файл на записи. Ошибка в отдельной записи не мешает разбору остальных;
+// ошибка возвращается, только если не удалось разобрать файл целиком.
+pub fn parse(format: ImportFormat, input: &[u8]) -> Result<Vec<ParsedRecord>, String> {
+    match format {
+        ImportFormat::Csv => {
+            let mut reader = csv::Reader::from_reader(input);
+            Ok(reader
+                .deserialize::<CsvRecord>()
+                .map(|record| record.map(ImportRecord::from).map_err(|e| RecordError::Malformed(e.to_string())))
+                .collect())
+        }
+        ImportFormat::Json => {
+            let values: Vec<serde_json::Value> = serde_json::from_slice(input).map_err(|e| e.to_string())?;
+            Ok(values.into_iter().map(|value| serde_json::from_value(value).map_err(|e| RecordError::Malformed(e.to_string()))).collect())
+        }
+        ImportFormat::Ndjson => {
+            let input = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+            Ok(input
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| serde_json::from_str(line).map_err(|e| RecordError::Malformed(e.to_string())))
+                .collect())
+        }
+        ImportFormat::TodoTxt => {
+            let input = std::str::from_utf8(input).map_err(|e| e.to_string())?;
+            Ok(input
+                .lines()
+                .filter(|line| !line.trim().is_empty())
+                .map(|line| todo_txt::parse_line(line).map_err(RecordError::Malformed))
+                .collect())
+        }
+        ImportFormat::Ical => icalendar::parse_calendar(std::str::from_utf8(input).map_err(|e| e.to_string())?),
+        ImportFormat::Trello => trello::parse(input),
+        ImportFormat::Todoist => todoist::parse(input),
+        ImportFormat::Github => github::parse(input),
+    }
+}
+
+// Заполненные поля объекта, которых нет среди известных импортеру.
+// Пустыми считаются null, false, 0, пустые строки, массивы и объекты.
+fn unmapped_fields(object: &Value, known: &[&str]) -> Vec<String> {
+    let Some(object) = object.as_object() else {
+        return Vec::new();
+    };
+    object
+        .iter()
+        .filter(|(key, value)| {
+            let empty = match value {
+                Value::Null => true,
+                Value::Bool(value) => !value,
+                Value::Number(value) => value.as_f64() == Some(0.0),
+                Value::String(value) => value.is_empty(),
+                Value::Array(value) => value.is_empty(),
+                Value::Object(value) => value.is_empty(),
+            };
+            !empty && !known.contains(&key.as_str())
+        })
+        .map(|(key, _)| key.clone())
+        .collect()
+}
+
+// Дата из начала строки ISO 8601: `2026-10-18` или `2026-10-18T09:00:00Z`
+fn date_prefix(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+// Пункт списка дел в описании задачи, как в Markdown: `- [x] text`
+fn checklist_item(done: bool, text: &str, depth: usize) -> String {
+    format!("{}- [{}] {}", "  ".repeat(depth), if done { 'x' } else { ' ' }, text)
+}
+
+// Итог обработки одной записи
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum RowStatus {
+    Created, // Задача создана
+    Updated, // Существующая задача заменена
+    Skipped, // Задача уже существует и оставлена как есть, или запись не описывает задачу
+    Rejected, // Запись отклонена
+    Aborted, // Запись корректна, но загрузка отменена из-за конфликта в другой записи
+}
+
+// Отчет по одной записи
+#[derive(Debug)]
+pub struct RowReport {
+    pub row: usize, // Номер записи в файле, начиная с 1
+    pub status: RowStatus,
+    pub id: Option<TaskId>, // ID задачи, которую создала или затронула запись
+    pub error: Option<TaskServiceError>, // Причина отклонения
+    pub reason: Option<String>, // Почему пропущена запись, которая не описывает задачу
+    pub unmapped: Vec<String>, // Поля записи, которые не перенесены в задачу
+}
+
+// Отчет о загрузке
+#[derive(Debug)]
+pub struct ImportReport {
+    pub dry_run: bool, // Был ли это пробный запуск
+    pub aborted: bool, // Загрузка отменена из-за конфликта в режиме Abort
+    pub rows: Vec<RowReport>,
+}
+
+impl ImportReport {
+    pub fn count(&self, status: RowStatus) -> usize {
+        self.rows.iter().filter(|row| row.status == status).count()
+    }
+
+    // Сколько записей содержало каждое не перенесенное в задачи поле
+    pub fn unmapped(&self) -> BTreeMap<String, usize> {
+        let mut fields = BTreeMap::new();
+        for field in self.rows.iter().flat_map(|row| &row.unmapped) {
+            *fields.entry(field.clone()).or_insert(0) += 1;
+        }
+        fields
+    }
+}
+
+// Проверяем разбор каждого формата и устойчивость к ошибкам в отдельных записях
+#[cfg(test)]
+mod import_tests {
+    use crate::application::export::{export, ExportFormat};
+    use crate::application::import::{parse, ImportFormat, ImportRecord};
+    use crate::domain::entities::Task;
+
+    fn tasks() -> Vec<Task> {
+        vec![
+            Task { id: 1, title: "Buy milk".to_string(), description: "2 liters".to_string(), status: true, tags: vec!["home".to_string(), "shop".to_string()], ..Default::default() },
+            Task { id: 2, title: "Write \"report\", draft".to_string(), description: "Q3".to_string(), status: false, tags: vec![], ..Default::default() },
+        ]
+    }
+
+    fn records() -> Vec<ImportRecord> {
+        tasks()
+            .into_iter()
+            .map(|task| ImportRecord { id: Some(task.id), title: task.title, description: task.description, status: task.status, tags: task.tags, ..Default::default() })
+            .collect()
+    }
+
+    fn roundtrip(export_format: ExportFormat, import_format: ImportFormat) {
+        let mut output = Vec::new();
+        export(&tasks(), export_format, &mut output).unwrap();
+        let parsed: Vec<ImportRecord> = parse(import_format, &output).unwrap().into_iter().map(Result::unwrap).collect();
+        assert_eq!(parsed, records());
+    }
+
+    #[test]
+    fn exported_files_are_importable() {
+        // Проверяем, что выгрузка в CSV, JSON и NDJSON читается обратно без потерь
+        roundtrip(ExportFormat::Csv, ImportFormat::Csv);
+        roundtrip(ExportFormat::Json, ImportFormat::Json);
+        roundtrip(ExportFormat::Ndjson, ImportFormat::Ndjson);
+    }
+
+    #[test]
+    fn csv_without_optional_columns() {
+        // Проверяем, что в CSV достаточно названия и описания
+        let parsed = parse(ImportFormat::Csv, b"title,description\nTask,Desc\n").unwrap();
+        assert_eq!(parsed, vec![Ok(ImportRecord { title: "Task".to_string(), description: "Desc".to_string(), ..Default::default() })]);
+    }
+
+    #[test]
+    fn malformed_record_does_not_stop_parsing() {
+        // Проверяем, что ошибка в одной записи не мешает разбору остальных
+        let parsed = parse(ImportFormat::Ndjson, b"{\"title\":\"A\",\"description\":\"B\"}\n{\"title\":1}\n{\"title\":\"C\"}\n").unwrap();
+        assert!(parsed[0].is_ok());
+        assert!(parsed[1].is_err());
+        assert_eq!(parsed[2].as_ref().unwrap().description, "");
+
+        let parsed = parse(ImportFormat::Csv, b"id,title,description,status\nx,A,B,true\n2,C,D,maybe\n3,E,F,false\n").unwrap();
+        assert!(parsed[0].is_err());
+        assert!(parsed[1].is_err());
+        assert!(parsed[2].is_ok());
+    }
+
+    #[test]
+    fn broken_json_file_is_rejected() {
+        // Проверяем, что файл, который нельзя разобрать целиком, отклоняется
+        assert!(parse(ImportFormat::Json, b"[{\"title\":").is_err());
+    }
+}
@@ -0,0 +1,123 @@
+use serde_json::Value;
+
+use crate::application::import::{ImportRecord, ParsedRecord, RecordError, date_prefix, unmapped_fields};
+
+// Массив задач GitHub Issues: ответ REST API (/repos/{owner}/{repo}/issues) или вывод
+// `gh issue list --json ...`. Метки становятся метками, веха - проектом, номер задачи
+// сохраняется в расширении `github-issue`. Pull request'ы из ответа REST API пропускаются
+// с пояснением в отчете.
+
+// Поля задачи GitHub, которые переносятся в задачу (в обоих вариантах написания)
+const MAPPED: &[&str] = &[
+    "number", "title", "body", "state", "labels", "milestone", "created_at", "createdAt", "closed_at", "closedAt",
+];
+// Служебные и производные поля без пользовательских данных
+const IGNORED: &[&str] = &[
+    "id", "node_id", "url", "repository_url", "labels_url", "comments_url", "events_url", "html_url", "timeline_url",
+    "updated_at", "updatedAt", "author_association", "state_reason", "stateReason", "performed_via_github_app",
+    "reactions", "reactionGroups", "locked", "active_lock_reason", "closed", "draft", "sub_issues_summary",
+];
+
+#[derive(serde::Deserialize)]
+struct Issue {
+    number: Option<u64>,
+    title: String,
+    body: Option<String>,
+    state: String, // open или closed; gh пишет OPEN и CLOSED
+    #[serde(default)]
+    labels: Vec<Label>,
+    milestone: Option<Milestone>,
+    #[serde(alias = "createdAt")]
+    created_at: Option<String>,
+    #[serde(alias = "closedAt")]
+    closed_at: Option<String>,
+}
+
+// REST API отдает метки объектами, старые выгрузки - строками
+#[derive(serde::Deserialize)]
+#[serde(untagged)]
+enum Label {
+    Object { name: String },
+    Name(String),
+}
+
+#[derive(serde::Deserialize)]
+struct Milestone {
+    title: String,
+}
+
+pub fn parse(input: &[u8]) -> Result<Vec<ParsedRecord>, String> {
+    let issues: Vec<Value> = serde_json::from_slice(input).map_err(|e| e.to_string())?;
+    Ok(issues.iter().map(record).collect())
+}
+
+fn record(value: &Value) -> ParsedRecord {
+    if value.get("pull_request").is_some() {
+        return Err(RecordError::Skipped("pull requests are not imported".to_string()));
+    }
+    let issue: Issue = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+    Ok(ImportRecord {
+        description: issue.body.filter(|body| !body.is_empty()).unwrap_or_else(|| issue.title.clone()),
+        title: issue.title,
+        status: issue.state.eq_ignore_ascii_case("closed"),
+        tags: issue
+            .labels
+            .into_iter()
+            .map(|label| match label {
+                Label::Object { name } | Label::Name(name) => name,
+            })
+            .collect(),
+        created: issue.created_at.as_deref().and_then(date_prefix),
+        completed: issue.closed_at.as_deref().and_then(date_prefix),
+        projects: issue.milestone.map(|milestone| milestone.title).into_iter().collect(),
+        extensions: issue.number.map(|number| ("github-issue".to_string(), number.to_string())).into_iter().collect(),
+        unmapped: unmapped_fields(value, &[MAPPED, IGNORED].concat()),
+        ..Default::default()
+    })
+}
+
+// Проверяем перенос задач GitHub Issues
+#[cfg(test)]
+mod github_tests {
+    use chrono::NaiveDate;
+
+    use crate::application::import::RecordError;
+    use crate::application::import::github::parse;
+
+    #[test]
+    fn rest_api_issues() {
+        // Проверяем метки, веху, номер и пропуск pull request'ов
+        let input = r#"[
+            {"id": 10, "number": 42, "title": "Crash on start", "body": null, "state": "closed", "locked": false,
+             "labels": [{"id": 1, "name": "bug"}], "milestone": {"title": "v1.0"}, "comments": 3,
+             "user": {"login": "octocat"}, "assignees": [], "created_at": "2026-10-01T10:00:00Z", "closed_at": "2026-10-03T12:00:00Z"},
+            {"number": 43, "title": "Fix crash", "state": "open", "pull_request": {"url": "https://example.com"}}
+        ]"#;
+        let records = parse(input.as_bytes()).unwrap();
+        let record = records[0].as_ref().unwrap();
+        assert_eq!(record.title, "Crash on start");
+        assert_eq!(record.description, "Crash on start");
+        assert!(record.status);
+        assert_eq!(record.tags, vec!["bug"]);
+        assert_eq!(record.projects, vec!["v1.0"]);
+        assert_eq!(record.created, NaiveDate::from_ymd_opt(2026, 10, 1));
+        assert_eq!(record.completed, NaiveDate::from_ymd_opt(2026, 10, 3));
+        assert_eq!(record.extensions.get("github-issue").map(String::as_str), Some("42"));
+        assert_eq!(record.unmapped, vec!["comments", "user"]);
+        assert_eq!(records[1], Err(RecordError::Skipped("pull requests are not imported".to_string())));
+    }
+
+    #[test]
+    fn gh_cli_issues() {
+        // Проверяем вывод `gh issue list --json` с полями в camelCase
+        let input = r#"[{"number": 7, "title": "Docs", "body": "Write docs", "state": "OPEN", "labels": [{"name": "docs", "color": "fff"}],
+            "createdAt": "2026-10-02T09:00:00Z", "author": {"login": "octocat"}}]"#;
+        let records = parse(input.as_bytes()).unwrap();
+        let record = records[0].as_ref().unwrap();
+        assert_eq!(record.description, "Write docs");
+        assert!(!record.status);
+        assert_eq!(record.tags, vec!["docs"]);
+        assert_eq!(record.created, NaiveDate::from_ymd_opt(2026, 10, 2));
+        assert_eq!(record.unmapped, vec!["author"]);
+    }
+}
@@ -0,0 +1,264 @@
+use std::collections::{BTreeSet, HashMap};
+
+use serde_json::Value;
+
+use crate::application::import::{ImportRecord, ParsedRecord, RecordError, checklist_item, date_prefix, unmapped_fields};
+
+// Резервная копия Todoist в формате Sync API: объект с массивами projects, sections, items и notes.
+// Задача верхнего уровня становится задачей: проект и раздел - проектами, метки - метками,
+// приоритет p1..p3 - приоритетом A..C. Подзадачи дописываются к описанию родителя списком дел;
+// их поля, которым нет места в пункте списка, попадают в отчет родителя как `subtasks.<поле>`.
+
+// Поля задачи Todoist, которые переносятся в задачу или связывают ее с остальной копией
+const MAPPED: &[&str] = &[
+    "id", "content", "description", "checked", "priority", "due", "labels", "project_id", "section_id", "parent_id",
+    "child_order", "added_at", "date_added", "completed_at", "date_completed", "is_deleted",
+];
+// Служебные и производные поля без пользовательских данных
+const IGNORED: &[&str] = &[
+    "user_id", "added_by_uid", "assigned_by_uid", "sync_id", "day_order", "collapsed", "updated_at", "note_count",
+    "v2_id", "v2_project_id", "v2_section_id", "v2_parent_id",
+];
+
+#[derive(serde::Deserialize)]
+struct Backup {
+    #[serde(default)]
+    projects: Vec<Container>,
+    #[serde(default)]
+    sections: Vec<Container>,
+    items: Vec<Value>,
+    #[serde(default)]
+    notes: Vec<Note>,
+}
+
+// Проект или раздел
+#[derive(serde::Deserialize)]
+struct Container {
+    id: Value, // Строка в новых версиях API, число в старых
+    name: String,
+}
+
+#[derive(serde::Deserialize)]
+struct Note {
+    item_id: Value,
+}
+
+#[derive(serde::Deserialize)]
+struct Item {
+    id: Value,
+    content: String,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    checked: Flag,
+    #[serde(default = "lowest_priority")]
+    priority: u8, // 4 - p1 (высший), 1 - p4 (без приоритета)
+    due: Option<Due>,
+    #[serde(default)]
+    labels: Vec<Value>, // Названия меток; в старых версиях API - их ID
+    project_id: Option<Value>,
+    section_id: Option<Value>,
+    parent_id: Option<Value>,
+    #[serde(default)]
+    child_order: i64,
+    #[serde(alias = "date_added")]
+    added_at: Option<String>,
+    #[serde(alias = "date_completed")]
+    completed_at: Option<String>,
+    #[serde(default)]
+    is_deleted: Flag,
+}
+
+#[derive(serde::Deserialize)]
+struct Due {
+    date: String,
+}
+
+// Логический флаг: старые версии API пишут 0 и 1 вместо false и true
+#[derive(Default, serde::Deserialize)]
+#[serde(from = "Value")]
+struct Flag(bool);
+
+impl From<Value> for Flag {
+    fn from(value: Value) -> Self {
+        Flag(value.as_bool().unwrap_or_else(|| value.as_i64() == Some(1)))
+    }
+}
+
+fn lowest_priority() -> u8 {
+    1
+}
+
+// ID в виде строки независимо от того, записан он строкой или числом
+fn key(id: &Value) -> String {
+    match id {
+        Value::String(id) => id.clone(),
+        id => id.to_string(),
+    }
+}
+
+pub fn parse(input: &[u8]) -> Result<Vec<ParsedRecord>, String> {
+    let backup: Backup = serde_json::from_slice(input).map_err(|e| e.to_string())?;
+    let projects: HashMap<String, &str> = backup.projects.iter().map(|project| (key(&project.id), project.name.as_str())).collect();
+    let sections: HashMap<String, &str> = backup.sections.iter().map(|section| (key(&section.id), section.name.as_str())).collect();
+    let mut notes: HashMap<String, usize> = HashMap::new();
+    for note in &backup.notes {
+        *notes.entry(key(&note.item_id)).or_insert(0) += 1;
+    }
+
+    let mut items = Vec::new();
+    let mut records = Vec::new();
+    for value in &backup.items {
+        match serde_json::from_value::<Item>(value.clone()) {
+            Ok(item) if item.is_deleted.0 => {}
+            Ok(item) => items.push((item, value)),
+            Err(e) => records.push(Err(RecordError::Malformed(e.to_string()))),
+        }
+    }
+    let ids: Vec<String> = items.iter().map(|(item, _)| key(&item.id)).collect();
+    let mut children: HashMap<String, Vec<&(Item, &Value)>> = HashMap::new();
+    for entry in &items {
+        if let Some(parent) = entry.0.parent_id.as_ref().map(key).filter(|parent| ids.contains(parent)) {
+            children.entry(parent).or_default().push(entry);
+        }
+    }
+    for siblings in children.values_mut() {
+        siblings.sort_by_key(|(item, _)| item.child_order);
+    }
+
+    // Подзадачи, чей родитель есть в копии, отдельными задачами не загружаются
+    for (item, value) in &items {
+        if item.parent_id.as_ref().map(key).is_some_and(|parent| ids.contains(&parent)) {
+            continue;
+        }
+        let mut checklist = Vec::new();
+        let mut dropped = BTreeSet::new();
+        subtasks(item, &children, &notes, 0, &mut checklist, &mut dropped);
+        let mut description = item.description.clone();
+        if !checklist.is_empty() {
+            if !description.is_empty() {
+                description.push_str("\n\n");
+            }
+            description.push_str(&checklist.join("\n"));
+        }
+        if description.is_empty() {
+            description = item.content.clone();
+        }
+
+        let mut unmapped = unmapped_fields(value, &[MAPPED, IGNORED].concat());
+        if notes.contains_key(&key(&item.id)) {
+            unmapped.push("notes".to_string());
+        }
+        unmapped.extend(dropped.into_iter().map(|field| format!("subtasks.{}", field)));
+        let projects = [
+            item.project_id.as_ref().and_then(|id| projects.get(&key(id))),
+            item.section_id.as_ref().and_then(|id| sections.get(&key(id))),
+        ];
+        records.push(Ok(ImportRecord {
+            title: item.content.clone(),
+            description,
+            status: item.checked.0,
+            tags: item.labels.iter().map(key).collect(),
+            priority: match item.priority {
+                4 => Some('A'),
+                3 => Some('B'),
+                2 => Some('C'),
+                _ => None,
+            },
+            created: item.added_at.as_deref().and_then(date_prefix),
+            completed: item.completed_at.as_deref().and_then(date_prefix),
+            due: item.due.as_ref().and_then(|due| date_prefix(&due.date)),
+            projects: projects.into_iter().flatten().map(|name| name.to_string()).collect(),
+            unmapped,
+            ..Default::default()
+        }));
+    }
+    Ok(records)
+}
+
+// Дописывает подзадачи `item` пунктами списка дел с отступом по глубине вложенности и
+// собирает в `dropped` заполненные поля подзадач, которые в пункт не попадают
+fn subtasks(
+    item: &Item,
+    children: &HashMap<String, Vec<&(Item, &Value)>>,
+    notes: &HashMap<String, usize>,
+    depth: usize,
+    lines: &mut Vec<String>,
+    dropped: &mut BTreeSet<String>,
+) {
+    for (child, value) in children.get(&key(&item.id)).into_iter().flatten() {
+        lines.push(checklist_item(child.checked.0, &child.content, depth));
+        let fields = [
+            ("description", !child.description.is_empty()),
+            ("labels", !child.labels.is_empty()),
+            ("due", child.due.is_some()),
+            ("priority", child.priority > 1),
+            ("notes", notes.contains_key(&key(&child.id))),
+        ];
+        dropped.extend(fields.into_iter().filter(|(_, filled)| *filled).map(|(field, _)| field.to_string()));
+        dropped.extend(unmapped_fields(value, &[MAPPED, IGNORED].concat()));
+        subtasks(child, children, notes, depth + 1, lines, dropped);
+    }
+}
+
+// Проверяем перенос задач из резервной копии Todoist
+#[cfg(test)]
+mod todoist_tests {
+    use chrono::NaiveDate;
+
+    use crate::application::import::todoist::parse;
+
+    const BACKUP: &str = r#"{
+        "projects": [{"id": "p1", "name": "Work"}],
+        "sections": [{"id": "s1", "name": "Review"}],
+        "items": [
+            {"id": "1", "content": "Release", "description": "", "checked": false, "priority": 4, "project_id": "p1",
+             "section_id": "s1", "labels": ["office"], "due": {"date": "2026-10-25", "is_recurring": false},
+             "added_at": "2026-10-01T08:00:00Z", "user_id": "u1", "responsible_uid": "u2", "duration": {"amount": 30}},
+            {"id": "2", "content": "Changelog", "checked": true, "parent_id": "1", "child_order": 2},
+            {"id": "3", "content": "Tag", "checked": false, "parent_id": "1", "child_order": 1, "labels": ["git"],
+             "due": {"date": "2026-10-24"}},
+            {"id": "4", "content": "Sign", "checked": 0, "parent_id": "3", "description": "With the release key", "priority": 3,
+             "responsible_uid": "u2"},
+            {"id": 5, "content": "Old", "checked": 1, "priority": 1, "project_id": "p1", "date_completed": "2026-09-30T10:00:00Z"},
+            {"id": "6", "content": "Deleted", "is_deleted": true}
+        ],
+        "notes": [{"id": "n1", "item_id": 5, "content": "Done at last"}, {"id": "n2", "item_id": "2", "content": "Use the template"}]
+    }"#;
+
+    #[test]
+    fn subtasks_become_checklist() {
+        // Проверяем, что подзадачи дописываются к описанию корневой задачи с отступом по вложенности,
+        // а их не перенесенные поля попадают в отчет корневой задачи
+        let records = parse(BACKUP.as_bytes()).unwrap();
+        assert_eq!(records.len(), 2);
+        let record = records[0].as_ref().unwrap();
+        assert_eq!(record.title, "Release");
+        assert_eq!(record.description, "- [ ] Tag\n  - [ ] Sign\n- [x] Changelog");
+        assert_eq!(record.priority, Some('A'));
+        assert_eq!(record.projects, vec!["Work", "Review"]);
+        assert_eq!(record.tags, vec!["office"]);
+        assert_eq!(record.due, NaiveDate::from_ymd_opt(2026, 10, 25));
+        assert_eq!(record.created, NaiveDate::from_ymd_opt(2026, 10, 1));
+        assert_eq!(
+            record.unmapped,
+            vec![
+                "duration", "responsible_uid", "subtasks.description", "subtasks.due", "subtasks.labels", "subtasks.notes",
+                "subtasks.priority", "subtasks.responsible_uid",
+            ]
+        );
+    }
+
+    #[test]
+    fn old_api_fields_and_notes() {
+        // Проверяем числовые ID и флаги старого API и отчет о комментариях
+        let records = parse(BACKUP.as_bytes()).unwrap();
+        let record = records[1].as_ref().unwrap();
+        assert_eq!(record.title, "Old");
+        assert_eq!(record.description, "Old");
+        assert!(record.status);
+        assert_eq!(record.priority, None);
+        assert_eq!(record.completed, NaiveDate::from_ymd_opt(2026, 9, 30));
+        assert_eq!(record.unmapped, vec!["notes"]);
+    }
+}
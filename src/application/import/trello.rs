@@ -0,0 +1,184 @@
+use std::collections::HashMap;
+
+use chrono::DateTime;
+use serde_json::Value;
+
+use crate::application::import::{ImportRecord, ParsedRecord, checklist_item, date_prefix, unmapped_fields};
+
+// Выгрузка доски Trello (Menu > Print, export and share > Export as JSON).
+// Карточка становится задачей: список доски - проектом, метки - метками,
+// чек-листы дописываются к описанию списком дел. Архивные карточки получают метку `archived`.
+
+// Поля карточки, которые переносятся в задачу или связывают ее с остальной выгрузкой
+const MAPPED: &[&str] = &["id", "name", "desc", "closed", "idList", "labels", "idLabels", "idChecklists", "due", "dueComplete"];
+// Служебные и производные поля без пользовательских данных
+const IGNORED: &[&str] = &[
+    "idBoard", "pos", "url", "shortUrl", "shortLink", "idShort", "dateLastActivity", "badges", "subscribed", "descData",
+    "checkItemStates", "manualCoverAttachment", "idAttachmentCover", "limits", "creationMethod", "nodeId", "isTemplate",
+    "cardRole", "idMembersVoted", "pinned", "email",
+];
+
+#[derive(serde::Deserialize)]
+struct Board {
+    #[serde(default)]
+    lists: Vec<List>,
+    cards: Vec<Value>,
+    #[serde(default)]
+    checklists: Vec<Checklist>,
+}
+
+#[derive(serde::Deserialize)]
+struct List {
+    id: String,
+    name: String,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Checklist {
+    id_card: String,
+    name: String,
+    #[serde(default)]
+    pos: f64,
+    #[serde(default)]
+    check_items: Vec<CheckItem>,
+}
+
+#[derive(serde::Deserialize)]
+struct CheckItem {
+    name: String,
+    state: String, // complete или incomplete
+    #[serde(default)]
+    pos: f64,
+}
+
+#[derive(serde::Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Card {
+    id: String,
+    name: String,
+    #[serde(default)]
+    desc: String,
+    #[serde(default)]
+    closed: bool,
+    id_list: Option<String>,
+    #[serde(default)]
+    labels: Vec<Label>,
+    due: Option<String>,
+    #[serde(default)]
+    due_complete: bool,
+}
+
+#[derive(serde::Deserialize)]
+struct Label {
+    #[serde(default)]
+    name: String,
+    color: Option<String>, // Метка без названия различается только цветом
+}
+
+pub fn parse(input: &[u8]) -> Result<Vec<ParsedRecord>, String> {
+    let board: Board = serde_json::from_slice(input).map_err(|e| e.to_string())?;
+    let lists: HashMap<&str, &str> = board.lists.iter().map(|list| (list.id.as_str(), list.name.as_str())).collect();
+    let mut checklists: HashMap<&str, Vec<&Checklist>> = HashMap::new();
+    for checklist in &board.checklists {
+        checklists.entry(checklist.id_card.as_str()).or_default().push(checklist);
+    }
+    for card_checklists in checklists.values_mut() {
+        card_checklists.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+    }
+    Ok(board.cards.iter().map(|card| record(card, &lists, &checklists)).collect())
+}
+
+fn record(value: &Value, lists: &HashMap<&str, &str>, checklists: &HashMap<&str, Vec<&Checklist>>) -> ParsedRecord {
+    let card: Card = serde_json::from_value(value.clone()).map_err(|e| e.to_string())?;
+
+    let mut sections = Vec::new();
+    if !card.desc.is_empty() {
+        sections.push(card.desc);
+    }
+    for checklist in checklists.get(card.id.as_str()).into_iter().flatten() {
+        let mut items: Vec<&CheckItem> = checklist.check_items.iter().collect();
+        items.sort_by(|a, b| a.pos.total_cmp(&b.pos));
+        let mut lines = vec![checklist.name.clone()];
+        lines.extend(items.iter().map(|item| checklist_item(item.state == "complete", &item.name, 0)));
+        sections.push(lines.join("\n"));
+    }
+
+    let mut tags: Vec<String> = card
+        .labels
+        .into_iter()
+        .filter_map(|label| if label.name.is_empty() { label.color } else { Some(label.name) })
+        .collect();
+    if card.closed {
+        tags.push("archived".to_string());
+    }
+
+    Ok(ImportRecord {
+        description: if sections.is_empty() { card.name.clone() } else { sections.join("\n\n") },
+        title: card.name,
+        status: card.due_complete,
+        tags,
+        // Первые 8 шестнадцатеричных цифр ID объекта Trello - время его создания
+        created: card
+            .id
+            .get(..8)
+            .and_then(|seconds| i64::from_str_radix(seconds, 16).ok())
+            .and_then(|seconds| DateTime::from_timestamp(seconds, 0))
+            .map(|created| created.date_naive()),
+        due: card.due.as_deref().and_then(date_prefix),
+        projects: card.id_list.as_deref().and_then(|id| lists.get(id)).map(|name| name.to_string()).into_iter().collect(),
+        unmapped: unmapped_fields(value, &[MAPPED, IGNORED].concat()),
+        ..Default::default()
+    })
+}
+
+// Проверяем перенос карточек Trello в задачи
+#[cfg(test)]
+mod trello_tests {
+    use chrono::NaiveDate;
+
+    use crate::application::import::trello::parse;
+
+    const BOARD: &str = r#"{
+        "name": "Home",
+        "lists": [{"id": "l1", "name": "Doing"}],
+        "cards": [
+            {"id": "66f3a000aaaaaaaaaaaaaaaa", "name": "Paint fence", "desc": "White paint", "closed": true, "idList": "l1",
+             "labels": [{"name": "garden", "color": "green"}, {"name": "", "color": "red"}],
+             "due": "2026-10-20T09:00:00.000Z", "dueComplete": true, "pos": 1024, "badges": {"votes": 0},
+             "idMembers": ["m1"], "cover": {"color": "blue"}},
+            {"id": "66f3a000bbbbbbbbbbbbbbbb", "name": "Wash car", "desc": "", "idList": "unknown", "start": null}
+        ],
+        "checklists": [
+            {"id": "c2", "idCard": "66f3a000aaaaaaaaaaaaaaaa", "name": "After", "pos": 2,
+             "checkItems": [{"name": "Clean brushes", "state": "incomplete", "pos": 1}]},
+            {"id": "c1", "idCard": "66f3a000aaaaaaaaaaaaaaaa", "name": "Before", "pos": 1,
+             "checkItems": [{"name": "Sand", "state": "incomplete", "pos": 2}, {"name": "Buy paint", "state": "complete", "pos": 1}]}
+        ]
+    }"#;
+
+    #[test]
+    fn cards_become_tasks() {
+        // Проверяем список, метки, чек-листы, архив, срок и дату создания из ID карточки
+        let records = parse(BOARD.as_bytes()).unwrap();
+        let record = records[0].as_ref().unwrap();
+        assert_eq!(record.title, "Paint fence");
+        assert_eq!(record.description, "White paint\n\nBefore\n- [x] Buy paint\n- [ ] Sand\n\nAfter\n- [ ] Clean brushes");
+        assert!(record.status);
+        assert_eq!(record.tags, vec!["garden", "red", "archived"]);
+        assert_eq!(record.projects, vec!["Doing"]);
+        assert_eq!(record.due, NaiveDate::from_ymd_opt(2026, 10, 20));
+        assert_eq!(record.created, NaiveDate::from_ymd_opt(2024, 9, 25));
+    }
+
+    #[test]
+    fn reports_unmapped_fields() {
+        // Проверяем, что в отчет попадают только заполненные и не служебные поля
+        let records = parse(BOARD.as_bytes()).unwrap();
+        assert_eq!(records[0].as_ref().unwrap().unmapped, vec!["cover", "idMembers"]);
+        let record = records[1].as_ref().unwrap();
+        assert!(record.unmapped.is_empty());
+        assert_eq!(record.description, "Wash car");
+        assert!(record.projects.is_empty());
+    }
+}
@@ -0,0 +1,359 @@
+use std::collections::BTreeMap;
+
+use serde::de::DeserializeOwned;
+use serde_json::{Map, Value, json};
+
+use crate::domain::entities::Task;
+
+// Частичное изменение задачи. Применяется к JSON-представлению задачи, в котором есть
+// все поля (пустые - как null, [] или {}), поэтому операции JSON Patch могут адресовать
+// любое поле, даже незаполненное.
+
+// Поля JSON-представления задачи
+const FIELDS: &[&str] = &[
+    "id", "title", "description", "status", "tags", "priority", "created", "completed", "due", "projects", "extensions",
+];
+// Поля, которые изменением задачи не меняются
+const IMMUTABLE: &[&str] = &["id", "created"];
+
+#[derive(Clone, Debug)]
+pub enum TaskPatch {
+    Json(Vec<PatchOperation>), // JSON Patch (RFC 6902)
+    Merge(Map<String, Value>), // JSON Merge Patch (RFC 7396)
+}
+
+// Операция JSON Patch; пути - JSON Pointer (RFC 6901)
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, utoipa::ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum PatchOperation {
+    Add { path: String, value: Value },
+    Remove { path: String },
+    Replace { path: String, value: Value },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: Value },
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum PatchError {
+    UnknownField(String), // Такого поля у задачи нет
+    ImmutableField(String), // Поле нельзя изменить
+    InvalidPath(String), // Путь не существует или не может быть изменен
+    TestFailed(String), // Значение по пути не совпало с ожидаемым в операции test
+    InvalidValue { field: String, reason: String }, // Значение не подходит полю
+}
+
+// Применяет изменение к задаче и возвращает задачу после него. Изменение применяется
+// целиком или не применяется вовсе; название и описание не проверяются - это делает TaskService.
+pub fn apply(task: &Task, patch: &TaskPatch) -> Result<Task, PatchError> {
+    let mut document = document(task);
+    match patch {
+        TaskPatch::Json(operations) => {
+            for operation in operations {
+                apply_operation(&mut document, operation)?;
+            }
+        }
+        TaskPatch::Merge(patch) => {
+            for field in patch.keys() {
+                check_field(field, true)?;
+            }
+            merge(&mut document, &Value::Object(patch.clone()));
+        }
+    }
+    let Value::Object(mut fields) = document else {
+        return Err(PatchError::InvalidPath(String::new()));
+    };
+    let priority: Option<char> = field(&mut fields, "priority")?;
+    if priority.is_some_and(|priority| !priority.is_ascii_uppercase()) {
+        let reason = "priority must be a letter from A to Z".to_string();
+        return Err(PatchError::InvalidValue { field: "priority".to_string(), reason });
+    }
+    Ok(Task {
+        id: task.id,
+        title: field(&mut fields, "title")?,
+        description: field(&mut fields, "description")?,
+        status: field(&mut fields, "status")?,
+        tags: field::<Option<Vec<String>>>(&mut fields, "tags")?.unwrap_or_default(),
+        priority,
+        created: task.created,
+        completed: field(&mut fields, "completed")?,
+        due: field(&mut fields, "due")?,
+        projects: field::<Option<Vec<String>>>(&mut fields, "projects")?.unwrap_or_default(),
+        extensions: field::<Option<BTreeMap<String, String>>>(&mut fields, "extensions")?.unwrap_or_default(),
+    })
+}
+
+fn document(task: &Task) -> Value {
+    json!({
+        "id": task.id,
+        "title": task.title,
+        "description": task.description,
+        "status": task.status,
+        "tags": task.tags,
+        "priority": task.priority,
+        "created": task.created,
+        "completed": task.completed,
+        "due": task.due,
+        "projects": task.projects,
+        "extensions": task.extensions,
+    })
+}
+
+// Значение поля после изменения; удаленное поле считается null
+fn field<T: DeserializeOwned>(fields: &mut Map<String, Value>, name: &str) -> Result<T, PatchError> {
+    serde_json::from_value(fields.remove(name).unwrap_or(Value::Null))
+        .map_err(|e| PatchError::InvalidValue { field: name.to_string(), reason: e.to_string() })
+}
+
+// Проверяет поле, которое затрагивает операция: оно должно существовать, а если
+// операция его меняет - быть изменяемым
+fn check_field(field: &str, changes: bool) -> Result<(), PatchError> {
+    if !FIELDS.contains(&field) {
+        return Err(PatchError::UnknownField(field.to_string()));
+    }
+    if changes && IMMUTABLE.contains(&field) {
+        return Err(PatchError::ImmutableField(field.to_string()));
+    }
+    Ok(())
+}
+
+// Проверяет поле, на которое указывает путь операции
+fn check_path(path: &str, changes: bool) -> Result<(), PatchError> {
+    match tokens(path)?.first() {
+        Some(field) => check_field(field, changes),
+        // Пустой путь указывает на задачу целиком
+        None if changes => Err(PatchError::InvalidPath(path.to_string())),
+        None => Ok(()),
+    }
+}
+
+fn apply_operation(document: &mut Value, operation: &PatchOperation) -> Result<(), PatchError> {
+    match operation {
+        PatchOperation::Add { path, value } => {
+            check_path(path, true)?;
+            add(document, path, value.clone())
+        }
+        PatchOperation::Remove { path } => {
+            check_path(path, true)?;
+            remove(document, path).map(|_| ())
+        }
+        PatchOperation::Replace { path, value } => {
+            check_path(path, true)?;
+            let target = document.pointer_mut(path).ok_or_else(|| PatchError::InvalidPath(path.clone()))?;
+            *target = value.clone();
+            Ok(())
+        }
+        PatchOperation::Move { from, path } => {
+            check_path(from, true)?;
+            check_path(path, true)?;
+            if path.starts_with(&format!("{}/", from)) {
+                return Err(PatchError::InvalidPath(path.clone()));
+            }
+            let value = remove(document, from)?;
+            add(document, path, value)
+        }
+        PatchOperation::Copy { from, path } => {
+            check_path(from, false)?;
+            check_path(path, true)?;
+            let value = document.pointer(from).cloned().ok_or_else(|| PatchError::InvalidPath(from.clone()))?;
+            add(document, path, value)
+        }
+        PatchOperation::Test { path, value } => {
+            check_path(path, false)?;
+            match document.pointer(path) {
+                Some(actual) if actual == value => Ok(()),
+                _ => Err(PatchError::TestFailed(path.clone())),
+            }
+        }
+    }
+}
+
+// Разбирает JSON Pointer на ключи, раскрывая ~1 и ~0
+fn tokens(path: &str) -> Result<Vec<String>, PatchError> {
+    if path.is_empty() {
+        return Ok(Vec::new());
+    }
+    let Some(path) = path.strip_prefix('/') else {
+        return Err(PatchError::InvalidPath(path.to_string()));
+    };
+    Ok(path.split('/').map(|token| token.replace("~1", "/").replace("~0", "~")).collect())
+}
+
+// Родитель значения по пути и последний ключ пути
+fn parent<'a>(document: &'a mut Value, path: &str) -> Result<(&'a mut Value, String), PatchError> {
+    let (parent, _) = path.rsplit_once('/').ok_or_else(|| PatchError::InvalidPath(path.to_string()))?;
+    let key = tokens(path)?.pop().unwrap_or_default();
+    let parent = document.pointer_mut(parent).ok_or_else(|| PatchError::InvalidPath(path.to_string()))?;
+    Ok((parent, key))
+}
+
+// Индекс элемента массива; `-` означает позицию за последним элементом
+fn index(key: &str, len: usize, path: &str) -> Result<usize, PatchError> {
+    if key == "-" {
+        return Ok(len);
+    }
+    // Ведущие нули и знаки RFC 6901 не допускает
+    if key.is_empty() || (key.len() > 1 && key.starts_with('0')) || !key.bytes().all(|byte| byte.is_ascii_digit()) {
+        return Err(PatchError::InvalidPath(path.to_string()));
+    }
+    key.parse().map_err(|_| PatchError::InvalidPath(path.to_string()))
+}
+
+fn add(document: &mut Value, path: &str, value: Value) -> Result<(), PatchError> {
+    match parent(document, path)? {
+        (Value::Object(object), key) => {
+            object.insert(key, value);
+            Ok(())
+        }
+        (Value::Array(array), key) => {
+            let index = index(&key, array.len(), path)?;
+            if index > array.len() {
+                return Err(PatchError::InvalidPath(path.to_string()));
+            }
+            array.insert(index, value);
+            Ok(())
+        }
+        _ => Err(PatchError::InvalidPath(path.to_string())),
+    }
+}
+
+fn remove(document: &mut Value, path: &str) -> Result<Value, PatchError> {
+    match parent(document, path)? {
+        (Value::Object(object), key) => object.remove(&key).ok_or_else(|| PatchError::InvalidPath(path.to_string())),
+        (Value::Array(array), key) => {
+            let index = index(&key, array.len(), path)?;
+            if index >= array.len() {
+                return Err(PatchError::InvalidPath(path.to_string()));
+            }
+            Ok(array.remove(index))
+        }
+        _ => Err(PatchError::InvalidPath(path.to_string())),
+    }
+}
+
+// Алгоритм MergePatch из RFC 7396: null удаляет ключ, объекты сливаются рекурсивно,
+// остальные значения заменяют прежние целиком
+fn merge(target: &mut Value, patch: &Value) {
+    let Value::Object(patch) = patch else {
+        *target = patch.clone();
+        return;
+    };
+    if !target.is_object() {
+        *target = Value::Object(Map::new());
+    }
+    if let Value::Object(target) = target {
+        for (key, value) in patch {
+            if value.is_null() {
+                target.remove(key);
+            } else {
+                merge(target.entry(key.clone()).or_insert(Value::Null), value);
+            }
+        }
+    }
+}
+
+// Проверяем применение JSON Patch и JSON Merge Patch к задаче
+#[cfg(test)]
+mod patch_tests {
+    use chrono::NaiveDate;
+    use serde_json::{Value, json};
+
+    use crate::application::patch::{PatchError, PatchOperation, TaskPatch, apply};
+    use crate::domain::entities::Task;
+
+    fn task() -> Task {
+        Task {
+            id: 7,
+            title: "Title".to_string(),
+            description: "Description".to_string(),
+            tags: vec!["home".to_string()],
+            due: NaiveDate::from_ymd_opt(2026, 10, 25),
+            ..Default::default()
+        }
+    }
+
+    fn json_patch(operations: Value) -> TaskPatch {
+        TaskPatch::Json(serde_json::from_value::<Vec<PatchOperation>>(operations).unwrap())
+    }
+
+    fn merge_patch(patch: Value) -> TaskPatch {
+        TaskPatch::Merge(serde_json::from_value(patch).unwrap())
+    }
+
+    #[test]
+    fn json_patch_operations() {
+        // Проверяем операции add, remove, replace, move, copy и test, в том числе на незаполненных полях
+        let patch = json_patch(json!([
+            { "op": "test", "path": "/title", "value": "Title" },
+            { "op": "replace", "path": "/title", "value": "Changed" },
+            { "op": "add", "path": "/tags/-", "value": "work" },
+            { "op": "add", "path": "/tags/0", "value": "urgent" },
+            { "op": "remove", "path": "/tags/1" },
+            { "op": "replace", "path": "/priority", "value": "B" },
+            { "op": "add", "path": "/extensions/a~1b", "value": "c" },
+            { "op": "copy", "from": "/tags", "path": "/projects" },
+            { "op": "move", "from": "/due", "path": "/completed" },
+        ]));
+        let patched = apply(&task(), &patch).unwrap();
+        assert_eq!(patched.title, "Changed");
+        assert_eq!(patched.tags, vec!["urgent", "work"]);
+        assert_eq!(patched.projects, vec!["urgent", "work"]);
+        assert_eq!(patched.priority, Some('B'));
+        assert_eq!(patched.extensions.get("a/b").map(String::as_str), Some("c"));
+        assert_eq!(patched.due, None);
+        assert_eq!(patched.completed, NaiveDate::from_ymd_opt(2026, 10, 25));
+    }
+
+    #[test]
+    fn json_patch_rejects_unknown_and_immutable_fields() {
+        // Проверяем, что операции над неизвестными и неизменяемыми полями отклоняются, а test над id допускается
+        let cases = [
+            (json!([{ "op": "add", "path": "/owner", "value": "me" }]), PatchError::UnknownField("owner".to_string())),
+            (json!([{ "op": "replace", "path": "/id", "value": 8 }]), PatchError::ImmutableField("id".to_string())),
+            (json!([{ "op": "move", "from": "/created", "path": "/due" }]), PatchError::ImmutableField("created".to_string())),
+            (json!([{ "op": "remove", "path": "/tags/5" }]), PatchError::InvalidPath("/tags/5".to_string())),
+            (json!([{ "op": "replace", "path": "", "value": {} }]), PatchError::InvalidPath(String::new())),
+            (json!([{ "op": "test", "path": "/id", "value": 8 }]), PatchError::TestFailed("/id".to_string())),
+        ];
+        for (operations, error) in cases {
+            assert_eq!(apply(&task(), &json_patch(operations)), Err(error));
+        }
+        assert!(apply(&task(), &json_patch(json!([{ "op": "test", "path": "/id", "value": 7 }]))).is_ok());
+    }
+
+    #[test]
+    fn json_patch_is_atomic_and_typed() {
+        // Проверяем, что неверный тип значения отклоняется с указанием поля
+        let patch = json_patch(json!([
+            { "op": "replace", "path": "/title", "value": "Changed" },
+            { "op": "replace", "path": "/status", "value": "yes" },
+        ]));
+        assert!(matches!(apply(&task(), &patch), Err(PatchError::InvalidValue { ref field, .. }) if field == "status"));
+        let patch = json_patch(json!([{ "op": "replace", "path": "/priority", "value": "b" }]));
+        assert!(matches!(apply(&task(), &patch), Err(PatchError::InvalidValue { ref field, .. }) if field == "priority"));
+    }
+
+    #[test]
+    fn merge_patch_sets_and_removes_fields() {
+        // Проверяем, что null очищает поле, а вложенные объекты сливаются
+        let mut original = task();
+        original.extensions.insert("keep".to_string(), "1".to_string());
+        original.extensions.insert("drop".to_string(), "2".to_string());
+        let patch = merge_patch(json!({ "status": true, "due": null, "tags": null, "extensions": { "drop": null, "new": "3" } }));
+        let patched = apply(&original, &patch).unwrap();
+        assert!(patched.status);
+        assert_eq!(patched.title, "Title");
+        assert_eq!(patched.due, None);
+        assert!(patched.tags.is_empty());
+        assert_eq!(patched.extensions.keys().collect::<Vec<_>>(), vec!["keep", "new"]);
+    }
+
+    #[test]
+    fn merge_patch_rejects_unknown_and_immutable_fields() {
+        // Проверяем ошибки для неизвестного поля, неизменяемого поля и удаления обязательного поля
+        assert_eq!(apply(&task(), &merge_patch(json!({ "owner": "me" }))), Err(PatchError::UnknownField("owner".to_string())));
+        assert_eq!(apply(&task(), &merge_patch(json!({ "id": 8 }))), Err(PatchError::ImmutableField("id".to_string())));
+        let result = apply(&task(), &merge_patch(json!({ "title": null })));
+        assert!(matches!(result, Err(PatchError::InvalidValue { ref field, .. }) if field == "title"));
+    }
+}
@@ -1,87 +1,518 @@
-use crate::domain::{entities::{Task, TaskId}, repositories::{RepositoryError,  TaskRepository}};
+use std::collections::HashMap;
+
+use chrono::{DateTime, Utc};
+
+use crate::application::feed::{ChangeFeed, DEFAULT_FEED_BACKLOG};
+use crate::application::history::{self, Change, History, DEFAULT_HISTORY_DEPTH};
+use crate::application::import::{ConflictMode, ImportReport, ParsedRecord, RecordError, RowReport, RowStatus};
+use crate::application::patch::{self, PatchError, TaskPatch};
+use crate::application::validation::{CheckedFields, ValidationError, ValidationPolicy};
+use crate::domain::{
+    entities::{Task, TaskId},
+    queries::{TaskFilter, TaskMutation},
+    repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository},
+};
 
 pub struct TaskService {
-    task_repository: Box<dyn TaskRepository>, // Динамический тип репозитория
+    task_repository: Box<dyn TaskRepository>, // Динамический тип репозитория
+    history: History, // История изменений для отмены и повтора, по сессиям
+    policy: ValidationPolicy, // Правила проверки полей задачи
+    feed: ChangeFeed, // Лента изменений для подписчиков
 }
 
 impl TaskService {
     // Конструктор
     pub fn new(task_repository: Box<dyn TaskRepository>) -> TaskService {
-        TaskService { task_repository }
+        TaskService::with_history_depth(task_repository, DEFAULT_HISTORY_DEPTH)
+    }
+    // Конструктор с заданной глубиной истории отмены
+    pub fn with_history_depth(task_repository: Box<dyn TaskRepository>, depth: usize) -> TaskService {
+        TaskService { task_repository, history: History::new(depth), policy: ValidationPolicy::default(), feed: ChangeFeed::new(DEFAULT_FEED_BACKLOG) }
+    }
+    // Заменяет правила проверки полей задачи
+    pub fn with_validation_policy(mut self, policy: ValidationPolicy) -> TaskService {
+        self.policy = policy;
+        self
+    }
+    // Лента изменений задач; подписаться на нее можно, не блокируя сервис
+    pub fn feed(&self) -> ChangeFeed {
+        self.feed.clone()
     }
     // Методы
     // Возвращает все задачи
-    pub async fn get_all(&self) -> Vec<Task> {
-        self.task_repository.get_all().await
+    pub async fn get_all(&self) -> Result<Vec<Task>, TaskServiceError> {
+        self.task_repository.get_all().await.map_err(|e|
+            match e {
+                RepositoryError::Timeout => TaskServiceError::Timeout,
+                _ => TaskServiceError::UnexpectedError
+            })
     }
     // Возвращает задачу по ID
     pub async fn get_by_id(&self, id: TaskId) -> Result<Task, TaskServiceError> {
         self.task_repository.get_by_id(id).await.map_err(|e| 
             match e {
                 RepositoryError::TaskNotFound => TaskServiceError::TaskNotFound,
+                RepositoryError::Timeout => TaskServiceError::Timeout,
                 _ => TaskServiceError::UnexpectedError
             })
     }
-    // Создает новую задачу
-    pub async fn create(&mut self, title: String, description: String) -> Result<(), TaskServiceError> {
-        if title.is_empty() {
-            return Err(TaskServiceError::MissingTitle);
-        }
-        if description.is_empty() {
-            return Err(TaskServiceError::MissingDescription);
+    // Возвращает состояние всех задач на указанный момент времени
+    pub async fn get_all_as_of(&self, at: DateTime<Utc>) -> Result<Vec<Task>, TaskServiceError> {
+        self.task_repository.get_all_as_of(at).await.map_err(|e|
+            match e {
+                RepositoryError::Unsupported => TaskServiceError::Unsupported,
+                RepositoryError::Timeout => TaskServiceError::Timeout,
+                _ => TaskServiceError::UnexpectedError
+            })
+    }
+    // Возвращает задачи, подходящие под условие; пустое условие подходит всем задачам
+    pub async fn find(&self, filter: &TaskFilter) -> Result<Vec<Task>, TaskServiceError> {
+        if filter.is_empty() {
+            return self.get_all().await;
         }
+        self.task_repository.find(filter).await.map_err(|e|
+            match e {
+                RepositoryError::Timeout => TaskServiceError::Timeout,
+                _ => TaskServiceError::UnexpectedError
+            })
+    }
+    // Создает новую задачу
+    pub async fn create(&mut self, session: &str, draft: TaskDraft) -> Result<Task, TaskServiceError> {
+        let CheckedFields { title, description, due } = self.validate(&draft)?;
         let id = self.task_repository.next_id().await;
-        let task = Task { id, title, description, status: false };
-        self.task_repository.create(task).await.map_err(|e|
+        let task = Task { id, title, description, status: false, due, ..Default::default() };
+        self.task_repository.create(task.clone()).await.map_err(|e|
             match e {
                 RepositoryError::TaskAlreadyExists => TaskServiceError::TaskAlreadyExists,
+                RepositoryError::Timeout => TaskServiceError::Timeout,
                 _ => TaskServiceError::UnexpectedError
-            })
+            })?;
+        self.record(session, vec![Change { before: None, after: Some(task.clone()) }]);
+        Ok(task)
+    }
+    // Изменяет название, описание и, если он указан, срок задачи
+    pub async fn update(&mut self, session: &str, id: TaskId, draft: TaskDraft) -> Result<Task, TaskServiceError> {
+        let CheckedFields { title, description, due } = self.validate(&draft)?;
+        let before = self.get_by_id(id).await?;
+        let after = Task { title, description, due: due.or(before.due), ..before.clone() };
+        self.task_repository.update(after.clone()).await.map_err(|e|
+            match e {
+                RepositoryError::TaskNotFound => TaskServiceError::TaskNotFound,
+                RepositoryError::Timeout => TaskServiceError::Timeout,
+                _ => TaskServiceError::UnexpectedError
+            })?;
+        self.record(session, vec![Change { before: Some(before), after: Some(after.clone()) }]);
+        Ok(after)
+    }
+    // Применяет к задаче JSON Patch или JSON Merge Patch. Название и описание после
+    // изменения проверяются теми же правилами, что и при создании задачи.
+    pub async fn patch(&mut self, session: &str, id: TaskId, patch: &TaskPatch) -> Result<Task, TaskServiceError> {
+        let before = self.get_by_id(id).await?;
+        let mut after = patch::apply(&before, patch).map_err(TaskServiceError::InvalidPatch)?;
+        let checked = self.policy.check(&after.title, &after.description, None).map_err(TaskServiceError::Validation)?;
+        (after.title, after.description) = (checked.title, checked.description);
+        self.task_repository.update(after.clone()).await.map_err(|e|
+            match e {
+                RepositoryError::TaskNotFound => TaskServiceError::TaskNotFound,
+                RepositoryError::Timeout => TaskServiceError::Timeout,
+                _ => TaskServiceError::UnexpectedError
+            })?;
+        self.record(session, vec![Change { before: Some(before), after: Some(after.clone()) }]);
+        Ok(after)
     }
 
-    pub async fn delete(&mut self, id: TaskId) -> Result<(), TaskServiceError> {
+    pub async fn delete(&mut self, session: &str, id: TaskId) -> Result<(), TaskServiceError> {
+        let before = self.get_by_id(id).await?;
         self.task_repository.delete(id).await.map_err(|e| 
             match e {
                 RepositoryError::TaskNotFound => TaskServiceError::TaskNotFound,
+                RepositoryError::Timeout => TaskServiceError::Timeout,
                 _ => TaskServiceError::UnexpectedError
-            })
+            })?;
+        self.record(session, vec![Change { before: Some(before), after: None }]);
+        Ok(())
     }
 
-    pub async fn toggle(&mut self, id: TaskId) -> Result<(), TaskServiceError> {
+    pub async fn toggle(&mut self, session: &str, id: TaskId) -> Result<Task, TaskServiceError> {
         self.task_repository.toggle(id).await.map_err(|e| 
             match e {
                 RepositoryError::TaskNotFound => TaskServiceError::TaskNotFound,
+                RepositoryError::Timeout => TaskServiceError::Timeout,
                 _ => TaskServiceError::UnexpectedError
             }
-        )
+        )?;
+        let after = self.get_by_id(id).await?;
+        let before = Task { status: !after.status, ..after.clone() };
+        self.record(session, vec![Change { before: Some(before), after: Some(after.clone()) }]);
+        Ok(after)
+    }
+    // Выполняет пакет операций. Для каждой операции возвращается идентификатор
+    // затронутой задачи или ошибка, в том же порядке, что и операции.
+    pub async fn execute_batch(&mut self, session: &str, operations: Vec<BatchOperation>, mode: BatchMode) -> Vec<Result<TaskId, TaskServiceError>> {
+        // Состояние задач ведется локально, чтобы операции пакета видели результат предыдущих
+        let mut known = HashMap::new();
+        let mut prepared = Vec::with_capacity(operations.len());
+        for operation in operations {
+            prepared.push(self.prepare(operation, &mut known).await);
+        }
+
+        // Если хоть одна операция не прошла проверку, в режиме AllOrNothing хранилище не трогаем
+        if mode == BatchMode::AllOrNothing && prepared.iter().any(Result::is_err) {
+            return prepared
+                .into_iter()
+                .map(|item| Err(item.err().unwrap_or(TaskServiceError::Aborted)))
+                .collect();
+        }
+
+        let operations = prepared.iter().flatten().map(|(operation, _)| operation.clone()).collect();
+        let mut executed = self.task_repository.execute_batch(operations, mode).await.into_iter();
+        let mut results = Vec::with_capacity(prepared.len());
+        let mut changes = Vec::new();
+        for item in prepared {
+            let result = item.and_then(|(_, change)| {
+                let executed = executed.next().unwrap_or(Err(RepositoryError::InternalError));
+                executed.map_err(|e|
+                    match e {
+                        RepositoryError::TaskNotFound => TaskServiceError::TaskNotFound,
+                        RepositoryError::TaskAlreadyExists => TaskServiceError::TaskAlreadyExists,
+                        RepositoryError::Aborted => TaskServiceError::Aborted,
+                        RepositoryError::Timeout => TaskServiceError::Timeout,
+                        _ => TaskServiceError::UnexpectedError
+                    })?;
+                let id = change.after.as_ref().or(change.before.as_ref()).map(|task| task.id).unwrap_or_default();
+                changes.push(change);
+                Ok(id)
+            });
+            results.push(result);
+        }
+        // Подписчики получают каждое изменение, а в историю пакет попадает одной операцией
+        self.feed.publish(&changes);
+        self.history.record(session, history::squash(changes));
+        results
+    }
+    // Проверяет операцию пакета и вычисляет состояние задачи до и после нее
+    async fn prepare(&mut self, operation: BatchOperation, known: &mut HashMap<TaskId, Option<Task>>) -> Result<(TaskOperation, Change), TaskServiceError> {
+        let (operation, change) = match operation {
+            BatchOperation::Create { draft } => {
+                let CheckedFields { title, description, due } = self.validate(&draft)?;
+                let id = self.task_repository.next_id().await;
+                let task = Task { id, title, description, status: false, due, ..Default::default() };
+                (TaskOperation::Create(task.clone()), Change { before: None, after: Some(task) })
+            }
+            BatchOperation::Update { id, draft } => {
+                let CheckedFields { title, description, due } = self.validate(&draft)?;
+                let before = self.current(id, known).await?;
+                let after = Task { title, description, due: due.or(before.due), ..before.clone() };
+                (TaskOperation::Update(after.clone()), Change { before: Some(before), after: Some(after) })
+            }
+            BatchOperation::Toggle { id } => {
+                let before = self.current(id, known).await?;
+                let after = Task { status: !before.status, ..before.clone() };
+                (TaskOperation::Toggle(id), Change { before: Some(before), after: Some(after) })
+            }
+            BatchOperation::Delete { id } => {
+                let before = self.current(id, known).await?;
+                (TaskOperation::Delete(id), Change { before: Some(before), after: None })
+            }
+        };
+        if let Some(id) = change.after.as_ref().or(change.before.as_ref()).map(|task| task.id) {
+            known.insert(id, change.after.clone());
+        }
+        Ok((operation, change))
+    }
+    // Запоминает операцию в истории сессии и публикует ее изменения в ленту
+    fn record(&mut self, session: &str, changes: Vec<Change>) {
+        self.feed.publish(&changes);
+        self.history.record(session, changes);
+    }
+    // Проверяет поля задачи по правилам сервиса
+    fn validate(&self, draft: &TaskDraft) -> Result<CheckedFields, TaskServiceError> {
+        self.policy.check(&draft.title, &draft.description, draft.due.as_deref()).map_err(TaskServiceError::Validation)
+    }
+    // Текущее состояние задачи с учетом уже подготовленных операций пакета
+    async fn current(&self, id: TaskId, known: &HashMap<TaskId, Option<Task>>) -> Result<Task, TaskServiceError> {
+        match known.get(&id) {
+            Some(Some(task)) => Ok(task.clone()),
+            Some(None) => Err(TaskServiceError::TaskNotFound),
+            None => self.get_by_id(id).await,
+        }
+    }
+    // Применяет изменение ко всем задачам, подходящим под фильтр, и возвращает их идентификаторы.
+    // В режиме dry_run ничего не меняет, а только сообщает, какие задачи были бы изменены.
+    // Изменение целиком попадает в историю как одна операция.
+    pub async fn bulk_update(&mut self, session: &str, filter: TaskFilter, mutation: TaskMutation, dry_run: bool) -> Result<Vec<TaskId>, TaskServiceError> {
+        if filter.is_empty() {
+            return Err(TaskServiceError::MissingFilter);
+        }
+        let matching = self.task_repository.find(&filter).await.map_err(|e|
+            match e {
+                RepositoryError::Timeout => TaskServiceError::Timeout,
+                _ => TaskServiceError::UnexpectedError
+            })?;
+        if dry_run {
+            return Ok(matching.into_iter().map(|task| task.id).collect());
+        }
+
+        let ids = self.task_repository.update_matching(&filter, &mutation).await.map_err(|e|
+            match e {
+                RepositoryError::Timeout => TaskServiceError::Timeout,
+                _ => TaskServiceError::UnexpectedError
+            })?;
+        let changes = matching
+            .into_iter()
+            .filter(|task| ids.contains(&task.id))
+            .map(|before| {
+                let mut after = before.clone();
+                mutation.apply(&mut after);
+                Change { before: Some(before), after: Some(after) }
+            })
+            .collect();
+        self.record(session, changes);
+        Ok(ids)
+    }
+    // Загружает задачи из разобранных записей файла.
+    // Записи проверяются так же, как при создании задачи; некорректные отклоняются,
+    // а остальные загружаются одним пакетом и попадают в историю как одна операция.
+    // В режиме dry_run ничего не меняет и новые ID не выдает.
+    pub async fn import(&mut self, session: &str, records: Vec<ParsedRecord>, conflict: ConflictMode, dry_run: bool) -> Result<ImportReport, TaskServiceError> {
+        let mut known: HashMap<TaskId, Option<Task>> = HashMap::new();
+        let mut rows = Vec::with_capacity(records.len());
+        let mut planned = Vec::new(); // Индекс строки отчета, состояние до загрузки и запись
+        let mut conflicting = false;
+
+        for (index, record) in records.into_iter().enumerate() {
+            let row = index + 1;
+            let mut record = match record {
+                Ok(record) => record,
+                Err(RecordError::Malformed(message)) => {
+                    let error = Some(TaskServiceError::Malformed(message));
+                    rows.push(RowReport { row, status: RowStatus::Rejected, id: None, error, reason: None, unmapped: Vec::new() });
+                    continue;
+                }
+                Err(RecordError::Skipped(reason)) => {
+                    rows.push(RowReport { row, status: RowStatus::Skipped, id: None, error: None, reason: Some(reason), unmapped: Vec::new() });
+                    continue;
+                }
+            };
+            match self.policy.check(&record.title, &record.description, None) {
+                Ok(checked) => (record.title, record.description) = (checked.title, checked.description),
+                Err(errors) => {
+                    let error = Some(TaskServiceError::Validation(errors));
+                    rows.push(RowReport { row, status: RowStatus::Rejected, id: record.id, error, reason: None, unmapped: record.unmapped });
+                    continue;
+                }
+            }
+
+            let before = match record.id {
+                Some(id) => match known.get(&id) {
+                    Some(state) => state.clone(),
+                    None => match self.task_repository.get_by_id(id).await {
+                        Ok(task) => Some(task),
+                        Err(RepositoryError::TaskNotFound) => None,
+                        Err(RepositoryError::Timeout) => return Err(TaskServiceError::Timeout),
+                        Err(_) => return Err(TaskServiceError::UnexpectedError),
+                    },
+                },
+                None => None,
+            };
+            let status = match (&before, conflict) {
+                (None, _) => RowStatus::Created,
+                (Some(_), ConflictMode::Upsert) => RowStatus::Updated,
+                (Some(_), ConflictMode::Skip) => {
+                    rows.push(RowReport { row, status: RowStatus::Skipped, id: record.id, error: None, reason: None, unmapped: record.unmapped });
+                    continue;
+                }
+                (Some(_), ConflictMode::Abort) => {
+                    conflicting = true;
+                    rows.push(RowReport { row, status: RowStatus::Rejected, id: record.id, error: Some(TaskServiceError::TaskAlreadyExists), reason: None, unmapped: record.unmapped });
+                    continue;
+                }
+            };
+            // Следующие записи с тем же ID конфликтуют уже с этой
+            if let Some(id) = record.id {
+                let after = record.clone().into_task(id);
+                known.insert(id, Some(after));
+            }
+            let unmapped = record.unmapped.clone();
+            planned.push((rows.len(), before, record));
+            rows.push(RowReport { row, status, id: None, error: None, reason: None, unmapped });
+        }
+
+        if conflicting {
+            for (index, _, _) in &planned {
+                rows[*index].status = RowStatus::Aborted;
+            }
+            return Ok(ImportReport { dry_run, aborted: true, rows });
+        }
+        if dry_run {
+            for (index, _, record) in &planned {
+                rows[*index].id = record.id;
+            }
+            return Ok(ImportReport { dry_run, aborted: false, rows });
+        }
+
+        let mut operations = Vec::with_capacity(planned.len());
+        let mut changes = Vec::with_capacity(planned.len());
+        // ID из файла уже известны все, поэтому новые ID выдаются в обход них и занятых в хранилище
+        for (index, before, record) in planned {
+            let id = match record.id {
+                Some(id) => id,
+                None => self.free_id(&known).await?,
+            };
+            rows[index].id = Some(id);
+            let after = record.into_task(id);
+            operations.push(match before {
+                Some(_) => TaskOperation::Update(after.clone()),
+                None => TaskOperation::Create(after.clone()),
+            });
+            changes.push(Change { before, after: Some(after) });
+        }
+        for result in self.task_repository.execute_batch(operations, BatchMode::AllOrNothing).await {
+            result.map_err(|e|
+                match e {
+                    RepositoryError::TaskAlreadyExists => TaskServiceError::TaskAlreadyExists,
+                    RepositoryError::TaskNotFound => TaskServiceError::TaskNotFound,
+                    RepositoryError::Timeout => TaskServiceError::Timeout,
+                    _ => TaskServiceError::UnexpectedError
+                })?;
+        }
+        self.record(session, changes);
+        Ok(ImportReport { dry_run, aborted: false, rows })
+    }
+    // Следующий ID, не занятый ни в хранилище, ни записями загружаемого файла
+    async fn free_id(&mut self, reserved: &HashMap<TaskId, Option<Task>>) -> Result<TaskId, TaskServiceError> {
+        loop {
+            let id = self.task_repository.next_id().await;
+            if reserved.contains_key(&id) {
+                continue;
+            }
+            match self.task_repository.get_by_id(id).await {
+                Err(RepositoryError::TaskNotFound) => return Ok(id),
+                Ok(_) => continue,
+                Err(RepositoryError::Timeout) => return Err(TaskServiceError::Timeout),
+                Err(_) => return Err(TaskServiceError::UnexpectedError),
+            }
+        }
+    }
+    // Отменяет последнюю операцию сессии.
+    // Если задачу с тех пор изменил кто-то другой, операция выбрасывается из истории
+    // и возвращается UndoConflict, чтобы не затереть чужие изменения.
+    pub async fn undo(&mut self, session: &str) -> Result<(), TaskServiceError> {
+        let changes = self.history.pop_undo(session).ok_or(TaskServiceError::NothingToUndo)?;
+        self.restore(changes.iter().rev().map(|change| (&change.after, &change.before)).collect()).await?;
+        self.history.push_redo(session, changes);
+        Ok(())
+    }
+    // Повторяет последнюю отмененную операцию сессии
+    pub async fn redo(&mut self, session: &str) -> Result<(), TaskServiceError> {
+        let changes = self.history.pop_redo(session).ok_or(TaskServiceError::NothingToRedo)?;
+        self.restore(changes.iter().map(|change| (&change.before, &change.after)).collect()).await?;
+        self.history.push_undo(session, changes);
+        Ok(())
+    }
+    // Переводит задачи из состояний `expected` в состояния `target` одним пакетом,
+    // предварительно убедившись, что текущие состояния совпадают с ожидаемыми
+    async fn restore(&mut self, transitions: Vec<(&Option<Task>, &Option<Task>)>) -> Result<(), TaskServiceError> {
+        let changes: Vec<Change> = transitions.iter().map(|(expected, target)| Change { before: (*expected).clone(), after: (*target).clone() }).collect();
+        let mut operations = Vec::with_capacity(transitions.len());
+        for (expected, target) in transitions {
+            let id = expected.as_ref().or(target.as_ref()).map(|task| task.id).ok_or(TaskServiceError::UnexpectedError)?;
+            let current = match self.task_repository.get_by_id(id).await {
+                Ok(task) => Some(task),
+                Err(RepositoryError::TaskNotFound) => None,
+                Err(RepositoryError::Timeout) => return Err(TaskServiceError::Timeout),
+                Err(_) => return Err(TaskServiceError::UnexpectedError),
+            };
+            if &current != expected {
+                return Err(TaskServiceError::UndoConflict);
+            }
+            operations.push(match target {
+                None => TaskOperation::Delete(id),
+                Some(task) if current.is_none() => TaskOperation::Create(task.clone()),
+                Some(task) => TaskOperation::Update(task.clone()),
+            });
+        }
+        for result in self.task_repository.execute_batch(operations, BatchMode::AllOrNothing).await {
+            result.map_err(|e|
+                match e {
+                    RepositoryError::TaskNotFound | RepositoryError::TaskAlreadyExists => TaskServiceError::UndoConflict,
+                    RepositoryError::Timeout => TaskServiceError::Timeout,
+                    _ => TaskServiceError::UnexpectedError
+                })?;
+        }
+        self.feed.publish(&changes);
+        Ok(())
     }
 }
 
+// Поля новой или изменяемой задачи в том виде, в каком их прислал клиент
+#[derive(Clone, Debug, Default)]
+pub struct TaskDraft {
+    pub title: String, // Название
+    pub description: String, // Описание
+    pub due: Option<String>, // Срок в формате ГГГГ-ММ-ДД
+}
+
+// Операция пакетной обработки в том виде, в каком ее присылает клиент
+#[derive(Clone, Debug)]
+pub enum BatchOperation {
+    Create { draft: TaskDraft }, // Создать задачу
+    Update { id: TaskId, draft: TaskDraft }, // Изменить название, описание и срок
+    Toggle { id: TaskId }, // Переключить статус
+    Delete { id: TaskId }, // Удалить задачу
+}
+
 #[derive(Debug)]
 pub enum TaskServiceError {
-    MissingTitle, // Отсутствует заголовок
-    MissingDescription, // Отсутствует описание
-    TaskNotFound, // Задача не найдена
+    Validation(Vec<ValidationError>), // Поля задачи не прошли проверку
+    InvalidPatch(PatchError), // Частичное изменение задачи не применимо
+    TaskNotFound, // Задача не найдена
     TaskAlreadyExists, // Задача уже существует
+    NothingToUndo, // Нет операций для отмены
+    NothingToRedo, // Нет операций для повтора
+    UndoConflict, // Задача изменилась после операции, отмена или повтор невозможны
+    Aborted, // Операция пакета отменена из-за ошибки в другой операции
+    MissingFilter, // Массовое изменение без условия отбора
+    Unsupported, // Операция не поддерживается выбранным хранилищем
+    Timeout, // Хранилище не ответило вовремя
+    Malformed(String), // Загружаемые данные не удалось разобрать
     UnexpectedError // Непредвиденная ошибка
 }
 
 // Проверяем работу сервиса, используя mockall для имитации поведения TaskRepository
 #[cfg(test)]
 mod task_service_tests {
-    use crate::application::services::{TaskService, TaskServiceError};
+    use crate::application::feed::{FeedFilter, FeedItem};
+    use crate::application::import::{ConflictMode, ImportRecord, ParsedRecord, RecordError, RowStatus};
+    use crate::application::patch::{PatchError, TaskPatch};
+    use crate::application::services::{BatchOperation, TaskDraft, TaskService, TaskServiceError};
+    use crate::application::validation::{DEFAULT_MAX_TITLE_LENGTH, TaskField, ValidationError, ValidationPolicy};
     use crate::domain::entities::Task;
-    use crate::domain::repositories::{MockTaskRepository, RepositoryError};
+    use crate::domain::events::ChangeKind;
+    use crate::domain::queries::{TaskFilter, TaskMutation};
+    use crate::domain::repositories::{BatchMode, MockTaskRepository, RepositoryError};
+    use crate::infrastructure::in_memory::InMemoryTaskRepository;
     use mockall::predicate::*;
+    use serde_json::json;
+
+    const SESSION: &str = "test-session";
+
+    fn task(id: i64, status: bool) -> Task {
+        Task { id, title: "Title".to_string(), description: "Description".to_string(), status, ..Default::default() }
+    }
+
+    fn draft(title: &str, description: &str) -> TaskDraft {
+        TaskDraft { title: title.to_string(), description: description.to_string(), due: None }
+    }
 
     #[tokio::test]
     async fn get_all_tasks_returns_empty_vec_if_no_tasks() {
         // Проверяем, что get_all возвращает пустой вектор, если задач нет
         let mut mock_repo = MockTaskRepository::new();
-        mock_repo.expect_get_all().times(1).returning(|| vec![]);
+        mock_repo.expect_get_all().times(1).returning(|| Ok(Vec::new()));
 
         let service = TaskService::new(Box::new(mock_repo));
-        let tasks = service.get_all().await;
+        let tasks = service.get_all().await.unwrap();
         assert!(tasks.is_empty());
     }
 
@@ -89,12 +520,12 @@ mod task_service_tests {
     async fn get_all_tasks_returns_all_tasks() {
         // Проверяем, что get_all возвращает все задачи
         let mut mock_repo = MockTaskRepository::new();
-        let task1 = Task { id: 1, title: "T1".to_string(), description: "D1".to_string(), status: false };
-        let task2 = Task { id: 2, title: "T2".to_string(), description: "D2".to_string(), status: true };
-        mock_repo.expect_get_all().times(1).returning(move || vec![task1.clone(), task2.clone()]);
+        let task1 = Task { id: 1, title: "T1".to_string(), description: "D1".to_string(), status: false, ..Default::default() };
+        let task2 = Task { id: 2, title: "T2".to_string(), description: "D2".to_string(), status: true, ..Default::default() };
+        mock_repo.expect_get_all().times(1).returning(move || Ok(vec![task1.clone(), task2.clone()]));
 
         let service = TaskService::new(Box::new(mock_repo));
-        let tasks = service.get_all().await;
+        let tasks = service.get_all().await.unwrap();
         assert_eq!(tasks.len(), 2);
         assert_eq!(tasks[0].id, 1);
         assert_eq!(tasks[1].id, 2);
@@ -107,7 +538,7 @@ mod task_service_tests {
         mock_repo.expect_get_by_id()
             .with(eq(1))
             .times(1)
-            .returning(move |id| Ok(Task { id, title: "Test".to_string(), description: "Desc".to_string(), status: false }));
+            .returning(move |id| Ok(Task { id, title: "Test".to_string(), description: "Desc".to_string(), status: false, ..Default::default() }));
 
         let service = TaskService::new(Box::new(mock_repo));
         let result = service.get_by_id(1).await;
@@ -129,6 +560,19 @@ mod task_service_tests {
         assert!(matches!(result, Err(TaskServiceError::TaskNotFound)));
     }
 
+    #[tokio::test]
+    async fn get_all_as_of_unsupported_by_repository() {
+        // Проверяем, что отсутствие истории в хранилище превращается в Unsupported
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_get_all_as_of()
+            .times(1)
+            .returning(|_| Err(RepositoryError::Unsupported));
+
+        let service = TaskService::new(Box::new(mock_repo));
+        let result = service.get_all_as_of(chrono::Utc::now()).await;
+        assert!(matches!(result, Err(TaskServiceError::Unsupported)));
+    }
+
     #[tokio::test]
     async fn create_task_success() {
         // Проверяем успешное создание задачи
@@ -140,8 +584,8 @@ mod task_service_tests {
             .returning(|_| Ok(()));
 
         let mut service = TaskService::new(Box::new(mock_repo));
-        let result = service.create("New Task".to_string(), "New Description".to_string()).await;
-        assert!(result.is_ok());
+        let result = service.create(SESSION, draft("New Task", "New Description")).await;
+        assert_eq!(result.unwrap().id, 1);
     }
 
     #[tokio::test]
@@ -149,8 +593,34 @@ mod task_service_tests {
         // Проверяем создание задачи с отсутствующим заголовком
         let mock_repo = MockTaskRepository::new(); // Mock не будет использоваться, но нужен для создания сервиса
         let mut service = TaskService::new(Box::new(mock_repo));
-        let result = service.create("".to_string(), "Description".to_string()).await;
-        assert!(matches!(result, Err(TaskServiceError::MissingTitle)));
+        let result = service.create(SESSION, draft("", "Description")).await;
+        assert!(matches!(result, Err(TaskServiceError::Validation(ref errors)) if errors[..] == [ValidationError::MissingTitle]));
+    }
+
+    #[tokio::test]
+    async fn create_task_reports_all_field_errors() {
+        // Проверяем, что проверка не останавливается на первой ошибке
+        let mut service = TaskService::new(Box::new(MockTaskRepository::new()));
+        let long = "x".repeat(DEFAULT_MAX_TITLE_LENGTH + 1);
+        let draft = TaskDraft { title: long, description: "".to_string(), due: Some("2026-02-30".to_string()) };
+        let result = service.create(SESSION, draft).await;
+        let Err(TaskServiceError::Validation(errors)) = result else { panic!("expected validation errors") };
+        assert_eq!(errors, vec![
+            ValidationError::TitleTooLong { max: DEFAULT_MAX_TITLE_LENGTH },
+            ValidationError::MissingDescription,
+            ValidationError::InvalidDue("2026-02-30".to_string()),
+        ]);
+    }
+
+    #[tokio::test]
+    async fn create_task_with_due_date() {
+        // Проверяем, что срок из запроса разбирается и сохраняется
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        let dated = TaskDraft { due: Some("2026-10-25".to_string()), ..draft("Title", "Description") };
+        let task = service.create(SESSION, dated).await.unwrap();
+        assert_eq!(task.due, chrono::NaiveDate::from_ymd_opt(2026, 10, 25));
+        let task = service.update(SESSION, task.id, draft("Changed", "Description")).await.unwrap();
+        assert_eq!(task.due, chrono::NaiveDate::from_ymd_opt(2026, 10, 25));
     }
 
     #[tokio::test]
@@ -158,8 +628,8 @@ mod task_service_tests {
         // Проверяем создание задачи с отсутствующим описанием
         let mock_repo = MockTaskRepository::new(); // Mock не будет использоваться
         let mut service = TaskService::new(Box::new(mock_repo));
-        let result = service.create("Title".to_string(), "".to_string()).await;
-        assert!(matches!(result, Err(TaskServiceError::MissingDescription)));
+        let result = service.create(SESSION, draft("Title", "")).await;
+        assert!(matches!(result, Err(TaskServiceError::Validation(ref errors)) if errors[..] == [ValidationError::MissingDescription]));
     }
 
     #[tokio::test]
@@ -172,7 +642,7 @@ mod task_service_tests {
             .returning(|_| Err(RepositoryError::TaskAlreadyExists));
 
         let mut service = TaskService::new(Box::new(mock_repo));
-        let result = service.create("Existing Task".to_string(), "Description".to_string()).await;
+        let result = service.create(SESSION, draft("Existing Task", "Description")).await;
         assert!(matches!(result, Err(TaskServiceError::TaskAlreadyExists)));
     }
 
@@ -180,13 +650,17 @@ mod task_service_tests {
     async fn delete_task_success() {
         // Проверяем успешное удаление задачи
         let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_get_by_id()
+            .with(eq(1))
+            .times(1)
+            .returning(|id| Ok(task(id, false)));
         mock_repo.expect_delete()
             .with(eq(1))
             .times(1)
             .returning(|_| Ok(()));
 
         let mut service = TaskService::new(Box::new(mock_repo));
-        let result = service.delete(1).await;
+        let result = service.delete(SESSION, 1).await;
         assert!(result.is_ok());
     }
 
@@ -194,13 +668,14 @@ mod task_service_tests {
     async fn delete_task_not_found() {
         // Проверяем удаление несуществующей задачи
         let mut mock_repo = MockTaskRepository::new();
-        mock_repo.expect_delete()
+        mock_repo.expect_get_by_id()
             .with(eq(99))
             .times(1)
             .returning(|_| Err(RepositoryError::TaskNotFound));
+        mock_repo.expect_delete().never();
 
         let mut service = TaskService::new(Box::new(mock_repo));
-        let result = service.delete(99).await;
+        let result = service.delete(SESSION, 99).await;
         assert!(matches!(result, Err(TaskServiceError::TaskNotFound)));
     }
 
@@ -212,10 +687,14 @@ mod task_service_tests {
             .with(eq(1))
             .times(1)
             .returning(|_| Ok(()));
+        mock_repo.expect_get_by_id()
+            .with(eq(1))
+            .times(1)
+            .returning(|id| Ok(task(id, true)));
 
         let mut service = TaskService::new(Box::new(mock_repo));
-        let result = service.toggle(1).await;
-        assert!(result.is_ok());
+        let result = service.toggle(SESSION, 1).await;
+        assert!(result.unwrap().status);
     }
 
     #[tokio::test]
@@ -228,7 +707,373 @@ mod task_service_tests {
             .returning(|_| Err(RepositoryError::TaskNotFound));
 
         let mut service = TaskService::new(Box::new(mock_repo));
-        let result = service.toggle(99).await;
+        let result = service.toggle(SESSION, 99).await;
         assert!(matches!(result, Err(TaskServiceError::TaskNotFound)));
     }
+
+    #[tokio::test]
+    async fn update_task_success() {
+        // Проверяем изменение названия и описания задачи с сохранением статуса
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_get_by_id()
+            .with(eq(1))
+            .times(1)
+            .returning(|id| Ok(task(id, true)));
+        mock_repo.expect_update()
+            .with(function(|task: &Task| task.id == 1 && task.title == "New" && task.description == "Desc" && task.status))
+            .times(1)
+            .returning(|_| Ok(()));
+
+        let mut service = TaskService::new(Box::new(mock_repo));
+        let result = service.update(SESSION, 1, draft("New", "Desc")).await;
+        assert_eq!(result.unwrap().title, "New");
+    }
+
+    #[tokio::test]
+    async fn update_task_missing_title() {
+        // Проверяем, что изменение проходит ту же проверку, что и создание
+        let mock_repo = MockTaskRepository::new();
+        let mut service = TaskService::new(Box::new(mock_repo));
+        let result = service.update(SESSION, 1, draft("", "Desc")).await;
+        assert!(matches!(result, Err(TaskServiceError::Validation(ref errors)) if errors[..] == [ValidationError::MissingTitle]));
+    }
+
+    #[tokio::test]
+    async fn undo_with_empty_history() {
+        // Проверяем отмену, когда отменять нечего
+        let mock_repo = MockTaskRepository::new();
+        let mut service = TaskService::new(Box::new(mock_repo));
+        assert!(matches!(service.undo(SESSION).await, Err(TaskServiceError::NothingToUndo)));
+        assert!(matches!(service.redo(SESSION).await, Err(TaskServiceError::NothingToRedo)));
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_toggle() {
+        // Проверяем, что отмена переключения возвращает прежний статус, а повтор снова его меняет
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        service.create(SESSION, draft("Title", "Description")).await.unwrap();
+        service.toggle(SESSION, 1).await.unwrap();
+
+        service.undo(SESSION).await.unwrap();
+        assert!(!service.get_by_id(1).await.unwrap().status);
+
+        service.redo(SESSION).await.unwrap();
+        assert!(service.get_by_id(1).await.unwrap().status);
+    }
+
+    #[tokio::test]
+    async fn undo_create_update_and_delete() {
+        // Проверяем отмену удаления, изменения и создания по очереди
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        service.create(SESSION, draft("Title", "Description")).await.unwrap();
+        service.update(SESSION, 1, draft("Changed", "Description")).await.unwrap();
+        service.delete(SESSION, 1).await.unwrap();
+
+        service.undo(SESSION).await.unwrap();
+        assert_eq!(service.get_by_id(1).await.unwrap().title, "Changed");
+        service.undo(SESSION).await.unwrap();
+        assert_eq!(service.get_by_id(1).await.unwrap().title, "Title");
+        service.undo(SESSION).await.unwrap();
+        assert!(matches!(service.get_by_id(1).await, Err(TaskServiceError::TaskNotFound)));
+    }
+
+    #[tokio::test]
+    async fn undo_is_per_session() {
+        // Проверяем, что сессия не может отменить чужую операцию
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        service.create("alice", draft("Title", "Description")).await.unwrap();
+        assert!(matches!(service.undo("bob").await, Err(TaskServiceError::NothingToUndo)));
+        service.undo("alice").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn undo_conflicts_with_foreign_modification() {
+        // Проверяем, что отмена не затирает изменения, сделанные другой сессией
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        service.create("alice", draft("Title", "Description")).await.unwrap();
+        service.toggle("alice", 1).await.unwrap();
+        service.update("bob", 1, draft("Bob's title", "Description")).await.unwrap();
+
+        assert!(matches!(service.undo("alice").await, Err(TaskServiceError::UndoConflict)));
+        let task = service.get_by_id(1).await.unwrap();
+        assert_eq!(task.title, "Bob's title");
+        assert!(task.status);
+    }
+
+    #[tokio::test]
+    async fn undo_depth_is_bounded() {
+        // Проверяем, что отменить можно не больше операций, чем задано глубиной истории
+        let mut service = TaskService::with_history_depth(Box::new(InMemoryTaskRepository::new()), 1);
+        service.create(SESSION, draft("Title", "Description")).await.unwrap();
+        service.toggle(SESSION, 1).await.unwrap();
+
+        service.undo(SESSION).await.unwrap();
+        assert!(matches!(service.undo(SESSION).await, Err(TaskServiceError::NothingToUndo)));
+        assert!(service.get_by_id(1).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn batch_all_or_nothing_skips_repository_on_validation_error() {
+        // Проверяем, что при ошибке проверки в режиме AllOrNothing хранилище не вызывается
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_next_id().times(1).returning(|| 1);
+        mock_repo.expect_execute_batch().never();
+
+        let mut service = TaskService::new(Box::new(mock_repo));
+        let results = service.execute_batch(SESSION, vec![
+            BatchOperation::Create { draft: draft("Title", "Description") },
+            BatchOperation::Create { draft: draft("", "Description") },
+        ], BatchMode::AllOrNothing).await;
+
+        assert!(matches!(results[0], Err(TaskServiceError::Aborted)));
+        assert!(matches!(results[1], Err(TaskServiceError::Validation(ref errors)) if errors[..] == [ValidationError::MissingTitle]));
+    }
+
+    #[tokio::test]
+    async fn batch_best_effort_reports_each_operation() {
+        // Проверяем результаты по каждой операции в режиме BestEffort
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        let results = service.execute_batch(SESSION, vec![
+            BatchOperation::Create { draft: draft("Title", "Description") },
+            BatchOperation::Toggle { id: 1 },
+            BatchOperation::Delete { id: 99 },
+            BatchOperation::Update { id: 1, draft: draft("", "Description") },
+        ], BatchMode::BestEffort).await;
+
+        assert!(matches!(results[0], Ok(1)));
+        assert!(matches!(results[1], Ok(1)));
+        assert!(matches!(results[2], Err(TaskServiceError::TaskNotFound)));
+        assert!(matches!(results[3], Err(TaskServiceError::Validation(ref errors)) if errors[..] == [ValidationError::MissingTitle]));
+        assert!(service.get_by_id(1).await.unwrap().status);
+    }
+
+    #[tokio::test]
+    async fn batch_operations_can_be_undone() {
+        // Проверяем, что пакет отменяется и повторяется целиком одной операцией
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        service.execute_batch(SESSION, vec![
+            BatchOperation::Create { draft: draft("Title", "Description") },
+            BatchOperation::Toggle { id: 1 },
+        ], BatchMode::AllOrNothing).await;
+
+        service.undo(SESSION).await.unwrap();
+        assert!(service.get_all().await.unwrap().is_empty());
+        assert!(matches!(service.undo(SESSION).await, Err(TaskServiceError::NothingToUndo)));
+
+        service.redo(SESSION).await.unwrap();
+        assert!(service.get_by_id(1).await.unwrap().status);
+    }
+
+    #[tokio::test]
+    async fn bulk_update_requires_filter() {
+        // Проверяем, что массовое изменение без условия отбора запрещено
+        let mock_repo = MockTaskRepository::new();
+        let mut service = TaskService::new(Box::new(mock_repo));
+        let result = service.bulk_update(SESSION, TaskFilter::default(), TaskMutation::SetStatus(true), false).await;
+        assert!(matches!(result, Err(TaskServiceError::MissingFilter)));
+    }
+
+    #[tokio::test]
+    async fn bulk_update_dry_run_changes_nothing() {
+        // Проверяем, что пробный запуск только возвращает идентификаторы
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_find().times(1).returning(|_| Ok(vec![task(1, false), task(2, false)]));
+        mock_repo.expect_update_matching().never();
+
+        let mut service = TaskService::new(Box::new(mock_repo));
+        let filter = TaskFilter { status: Some(false), ..Default::default() };
+        let result = service.bulk_update(SESSION, filter, TaskMutation::SetStatus(true), true).await;
+        assert_eq!(result.unwrap(), vec![1, 2]);
+    }
+
+    #[tokio::test]
+    async fn bulk_update_is_undone_as_one_operation() {
+        // Проверяем, что массовое изменение отменяется одной операцией
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        service.create(SESSION, draft("Release notes", "Description")).await.unwrap();
+        service.create(SESSION, draft("Release build", "Description")).await.unwrap();
+        service.create(SESSION, draft("Other", "Description")).await.unwrap();
+
+        let filter = TaskFilter { title: Some("Release*".to_string()), ..Default::default() };
+        let ids = service.bulk_update(SESSION, filter, TaskMutation::AddTag("release-1.2".to_string()), false).await.unwrap();
+        assert_eq!(ids, vec![1, 2]);
+
+        let filter = TaskFilter { tag: Some("release-1.2".to_string()), ..Default::default() };
+        service.bulk_update(SESSION, filter, TaskMutation::SetStatus(true), false).await.unwrap();
+        assert_eq!(service.get_all().await.unwrap().iter().filter(|task| task.status).count(), 2);
+
+        service.undo(SESSION).await.unwrap();
+        assert!(service.get_all().await.unwrap().iter().all(|task| !task.status));
+        service.undo(SESSION).await.unwrap();
+        assert!(service.get_all().await.unwrap().iter().all(|task| task.tags.is_empty()));
+    }
+
+    fn record(id: Option<i64>, title: &str) -> ParsedRecord {
+        Ok(ImportRecord { id, title: title.to_string(), description: "Description".to_string(), ..Default::default() })
+    }
+
+    #[tokio::test]
+    async fn import_reports_every_row() {
+        // Проверяем, что корректные записи загружаются, некорректные отклоняются с причиной,
+        // а записи, которые не описывают задачу, пропускаются с пояснением
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        let records = vec![
+            record(None, "First"),
+            record(None, ""),
+            Err(RecordError::Malformed("bad row".to_string())),
+            record(Some(10), "Tenth"),
+            Err(RecordError::Skipped("pull requests are not imported".to_string())),
+        ];
+
+        let report = service.import(SESSION, records, ConflictMode::Skip, false).await.unwrap();
+        let statuses: Vec<_> = report.rows.iter().map(|row| row.status).collect();
+        assert_eq!(statuses, vec![RowStatus::Created, RowStatus::Rejected, RowStatus::Rejected, RowStatus::Created, RowStatus::Skipped]);
+        assert!(matches!(report.rows[1].error, Some(TaskServiceError::Validation(ref errors)) if errors[..] == [ValidationError::MissingTitle]));
+        assert!(matches!(report.rows[2].error, Some(TaskServiceError::Malformed(_))));
+        assert_eq!((report.rows[4].error.is_none(), report.rows[4].reason.as_deref()), (true, Some("pull requests are not imported")));
+        assert_eq!(report.rows[0].id, Some(1));
+        assert_eq!(service.get_by_id(10).await.unwrap().title, "Tenth");
+
+        // Новые задачи не занимают ID, пришедшие из файла
+        service.create(SESSION, draft("Next", "Description")).await.unwrap();
+        assert_eq!(service.get_by_id(11).await.unwrap().title, "Next");
+    }
+
+    #[tokio::test]
+    async fn import_mixes_rows_with_and_without_id() {
+        // Проверяем, что записи без ID не получают ID, указанные дальше в том же файле
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        let records = vec![record(None, "a"), record(Some(1), "b"), record(None, "c"), record(Some(2), "d")];
+        let report = service.import(SESSION, records, ConflictMode::Skip, false).await.unwrap();
+        assert!(report.rows.iter().all(|row| row.status == RowStatus::Created));
+        let ids: Vec<_> = report.rows.iter().map(|row| row.id.unwrap()).collect();
+        assert_eq!(ids, vec![3, 1, 4, 2]);
+        let mut titles: Vec<_> = service.get_all().await.unwrap().into_iter().map(|task| (task.id, task.title)).collect();
+        titles.sort();
+        let expected = [(1, "b"), (2, "d"), (3, "a"), (4, "c")];
+        assert_eq!(titles, expected.map(|(id, title)| (id, title.to_string())));
+    }
+
+    #[tokio::test]
+    async fn import_conflict_modes() {
+        // Проверяем пропуск, замену и отмену загрузки при совпадении ID
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        service.create(SESSION, draft("Existing", "Description")).await.unwrap();
+
+        let report = service.import(SESSION, vec![record(Some(1), "Imported")], ConflictMode::Skip, false).await.unwrap();
+        assert_eq!(report.rows[0].status, RowStatus::Skipped);
+        assert_eq!(service.get_by_id(1).await.unwrap().title, "Existing");
+
+        let report = service.import(SESSION, vec![record(None, "New"), record(Some(1), "Imported")], ConflictMode::Abort, false).await.unwrap();
+        assert!(report.aborted);
+        assert_eq!(report.rows[0].status, RowStatus::Aborted);
+        assert!(matches!(report.rows[1].error, Some(TaskServiceError::TaskAlreadyExists)));
+        assert_eq!(service.get_all().await.unwrap().len(), 1);
+
+        let report = service.import(SESSION, vec![record(Some(1), "Imported")], ConflictMode::Upsert, false).await.unwrap();
+        assert_eq!(report.rows[0].status, RowStatus::Updated);
+        assert_eq!(service.get_by_id(1).await.unwrap().title, "Imported");
+    }
+
+    #[tokio::test]
+    async fn import_dry_run_changes_nothing() {
+        // Проверяем, что пробная загрузка только составляет отчет
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        let report = service.import(SESSION, vec![record(None, "First"), record(Some(5), "Fifth")], ConflictMode::Skip, true).await.unwrap();
+
+        assert!(report.dry_run);
+        assert_eq!(report.count(RowStatus::Created), 2);
+        assert_eq!(report.rows[0].id, None);
+        assert!(service.get_all().await.unwrap().is_empty());
+        assert!(matches!(service.undo(SESSION).await, Err(TaskServiceError::NothingToUndo)));
+    }
+
+    #[tokio::test]
+    async fn import_is_undone_as_one_operation() {
+        // Проверяем, что вся загрузка отменяется одним действием
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        service.create(SESSION, draft("Existing", "Description")).await.unwrap();
+        let records = vec![record(None, "New"), record(Some(1), "Imported")];
+        service.import(SESSION, records, ConflictMode::Upsert, false).await.unwrap();
+
+        service.undo(SESSION).await.unwrap();
+        let tasks = service.get_all().await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Existing");
+    }
+
+    #[tokio::test]
+    async fn patch_is_validated_and_undone() {
+        // Проверяем, что изменение проверяется правилами сервиса, не трогает задачу при ошибке и отменяется
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        let task = service.create(SESSION, draft("Title", "Description")).await.unwrap();
+
+        let patch = TaskPatch::Merge(serde_json::from_value(json!({ "description": "   " })).unwrap());
+        let result = service.patch(SESSION, task.id, &patch).await;
+        assert!(matches!(result, Err(TaskServiceError::Validation(ref errors)) if errors[..] == [ValidationError::MissingDescription]));
+        let patch = TaskPatch::Merge(serde_json::from_value(json!({ "id": 2 })).unwrap());
+        let result = service.patch(SESSION, task.id, &patch).await;
+        assert!(matches!(result, Err(TaskServiceError::InvalidPatch(PatchError::ImmutableField(ref field))) if field == "id"));
+
+        let patch = TaskPatch::Merge(serde_json::from_value(json!({ "status": true, "tags": ["work"] })).unwrap());
+        let patched = service.patch(SESSION, task.id, &patch).await.unwrap();
+        assert!(patched.status);
+        assert_eq!(patched.tags, vec!["work"]);
+        service.undo(SESSION).await.unwrap();
+        assert_eq!(service.get_by_id(task.id).await.unwrap(), task);
+    }
+
+    #[tokio::test]
+    async fn changes_are_published_to_feed() {
+        // Проверяем, что в ленту попадает каждое изменение, в том числе из пакета и при отмене
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        let mut subscription = service.feed().subscribe(None, FeedFilter::default());
+        let task = service.create(SESSION, draft("Title", "Description")).await.unwrap();
+        service.toggle(SESSION, task.id).await.unwrap();
+        service.update(SESSION, task.id, draft("Renamed", "Description")).await.unwrap();
+        let operations = vec![BatchOperation::Create { draft: draft("Second", "Description") }, BatchOperation::Delete { id: task.id }];
+        service.execute_batch(SESSION, operations, BatchMode::BestEffort).await;
+        service.undo(SESSION).await.unwrap();
+
+        let mut events = Vec::new();
+        for _ in 0..6 {
+            match subscription.next().await.unwrap() {
+                FeedItem::Event(event) => events.push((event.kind, event.task.id)),
+                FeedItem::Reset => panic!("no events should be lost"),
+            }
+        }
+        assert_eq!(
+            events,
+            vec![
+                (ChangeKind::Created, 1),
+                (ChangeKind::Toggled, 1),
+                (ChangeKind::Updated, 1),
+                (ChangeKind::Created, 2),
+                (ChangeKind::Deleted, 1),
+                (ChangeKind::Created, 1),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn validation_policy_applies_to_every_update_path() {
+        // Проверяем, что правила сервиса действуют при создании, изменении, в пакете и при загрузке
+        let policy = ValidationPolicy { forbidden_characters: vec!['<'], require_description: false, ..Default::default() };
+        let mut service = TaskService::new(Box::new(InMemoryTaskRepository::new())).with_validation_policy(policy);
+
+        let task = service.create(SESSION, draft("  Title  ", "")).await.unwrap();
+        assert_eq!((task.title.as_str(), task.description.as_str()), ("Title", ""));
+
+        let result = service.update(SESSION, task.id, draft("<b>", "Description")).await;
+        let forbidden = ValidationError::ForbiddenCharacter { field: TaskField::Title, character: '<' };
+        assert!(matches!(result, Err(TaskServiceError::Validation(ref errors)) if errors[..] == [forbidden]));
+
+        let operations = vec![BatchOperation::Update { id: task.id, draft: draft(" ", "Description") }];
+        let results = service.execute_batch(SESSION, operations, BatchMode::BestEffort).await;
+        assert!(matches!(results[0], Err(TaskServiceError::Validation(ref errors)) if errors[..] == [ValidationError::MissingTitle]));
+
+        let report = service.import(SESSION, vec![record(Some(task.id), " Imported\n")], ConflictMode::Upsert, false).await.unwrap();
+        assert_eq!(report.count(RowStatus::Updated), 1);
+        assert_eq!(service.get_by_id(task.id).await.unwrap().title, "Imported");
+    }
 }
\ No newline at end of file
@@ -0,0 +1,338 @@
+use chrono::NaiveDate;
+
+use crate::application::import::ImportRecord;
+use crate::domain::entities::Task;
+
+// Формат строки todo.txt (https://github.com/todotxt/todo.txt):
+//
+//     x (A) 2026-10-02 2026-10-01 Позвонить маме +Family @phone due:2026-10-05
+//
+// `x` - выполненная задача, `(A)` - приоритет, затем даты выполнения и создания,
+// а в тексте - проекты `+project`, контексты `@context` и расширения `key:value`.
+// Контексты соответствуют меткам задачи, остальной текст - названию.
+//
+// Не у всех полей задачи есть место в строке todo.txt, поэтому они записываются расширениями:
+// ID задачи (`id:`), описание (`description:`, если отличается от названия), приоритет
+// выполненной задачи (`pri:`, как принято в инструментах todo.txt) и даты, которые нельзя
+// записать на их позиции. Название, которое при разборе превратилось бы в другие поля
+// (`x` или дата в начале, `+project`, `key:value`, несколько пробелов подряд), тоже
+// записывается расширением - `title:`.
+//
+// Пробелы, `%` и `:` в значениях расширений, проектах и контекстах записываются процентным
+// кодированием. Ключи расширений, совпадающие с полями задачи или содержащие недопустимые
+// символы, кодируются целиком начиная с первого символа, чтобы не спутать их с полями.
+
+const DATE_FORMAT: &str = "%Y-%m-%d";
+// Ключи расширений, которыми записываются поля задачи
+const RESERVED_KEYS: [&str; 7] = ["id", "title", "description", "due", "created", "completed", "pri"];
+
+// Разбирает одну строку todo.txt. Описанием задачи, если оно не записано
+// расширением, становится название: в todo.txt отдельного описания нет.
+pub fn parse_line(line: &str) -> Result<ImportRecord, String> {
+    let mut record = ImportRecord::default();
+    let mut words = line.split_whitespace().peekable();
+
+    if words.next_if_eq(&"x").is_some() {
+        record.status = true;
+    }
+    if let Some(priority) = words.peek().and_then(|word| parse_priority(word)) {
+        record.priority = Some(priority);
+        words.next();
+    }
+    if let Some(date) = words.peek().and_then(|word| parse_date(word)) {
+        words.next();
+        // У выполненной задачи первая дата - дата выполнения, за ней может идти дата создания
+        if record.status {
+            record.completed = Some(date);
+            if let Some(date) = words.peek().and_then(|word| parse_date(word)) {
+                record.created = Some(date);
+                words.next();
+            }
+        } else {
+            record.created = Some(date);
+        }
+    }
+
+    let mut title = Vec::new();
+    let mut encoded_title = None;
+    let mut description = None;
+    for word in words {
+        if let Some(project) = word.strip_prefix('+').filter(|project| !project.is_empty()) {
+            record.projects.push(decode_or_raw(project));
+        } else if let Some(context) = word.strip_prefix('@').filter(|context| !context.is_empty()) {
+            record.tags.push(decode_or_raw(context));
+        } else if let Some((key, value)) = extension(word) {
+            match key {
+                "id" if record.id.is_none() && value.parse::<i64>().is_ok() => record.id = value.parse().ok(),
+                "title" => encoded_title = Some(decode(value).ok_or_else(|| format!("invalid title encoding: {}", value))?),
+                "description" => description = Some(decode(value).ok_or_else(|| format!("invalid description encoding: {}", value))?),
+                "due" if record.due.is_none() && parse_date(value).is_some() => record.due = parse_date(value),
+                "created" if record.created.is_none() && parse_date(value).is_some() => record.created = parse_date(value),
+                "completed" if record.completed.is_none() && parse_date(value).is_some() => record.completed = parse_date(value),
+                "pri" if record.priority.is_none() && parse_priority_letter(value).is_some() => record.priority = parse_priority_letter(value),
+                _ => {
+                    record.extensions.insert(decode_or_raw(key), decode_or_raw(value));
+                }
+            }
+        } else {
+            title.push(word);
+        }
+    }
+    record.title = encoded_title.unwrap_or_else(|| title.join(" "));
+    record.description = description.unwrap_or_else(|| record.title.clone());
+    Ok(record)
+}
+
+// Записывает задачу строкой todo.txt так, чтобы parse_line вернул те же поля
+pub fn format_line(task: &Task) -> String {
+    // Название пишется текстом, если строка с ним разбирается обратно в ту же задачу
+    let line = compose(task, true);
+    match parse_line(&line).map(|record| record.into_task(task.id)) {
+        Ok(parsed) if parsed == *task => line,
+        _ => compose(task, false),
+    }
+}
+
+fn compose(task: &Task, title_as_text: bool) -> String {
+    let mut words = Vec::new();
+    let mut fallback = Vec::new(); // Поля, которые нельзя записать на их позиции
+
+    if task.status {
+        words.push("x".to_string());
+        match (task.completed, task.created) {
+            (Some(completed), created) => {
+                words.push(completed.format(DATE_FORMAT).to_string());
+                words.extend(created.map(|created| created.format(DATE_FORMAT).to_string()));
+            }
+            (None, Some(created)) => fallback.push(format!("created:{}", created.format(DATE_FORMAT))),
+            (None, None) => {}
+        }
+        fallback.extend(task.priority.map(|priority| format!("pri:{}", priority)));
+    } else {
+        words.extend(task.priority.map(|priority| format!("({})", priority)));
+        words.extend(task.created.map(|created| created.format(DATE_FORMAT).to_string()));
+        fallback.extend(task.completed.map(|completed| format!("completed:{}", completed.format(DATE_FORMAT))));
+    }
+
+    if title_as_text {
+        words.extend(task.title.split_whitespace().map(str::to_string));
+    }
+    words.extend(task.projects.iter().map(|project| format!("+{}", encode(project))));
+    words.extend(task.tags.iter().map(|tag| format!("@{}", encode(tag))));
+    words.extend(task.due.map(|due| format!("due:{}", due.format(DATE_FORMAT))));
+    words.extend(fallback);
+    if !title_as_text {
+        words.push(format!("title:{}", encode_value(&task.title)));
+    }
+    if task.description != task.title {
+        words.push(format!("description:{}", encode_value(&task.description)));
+    }
+    words.push(format!("id:{}", task.id));
+    words.extend(task.extensions.iter().map(|(key, value)| format!("{}:{}", encode_key(key), encode_value(value))));
+    words.join(" ")
+}
+
+fn parse_date(word: &str) -> Option<NaiveDate> {
+    // Проверка длины отсекает даты без ведущих нулей, которые chrono тоже принимает
+    if word.len() != 10 {
+        return None;
+    }
+    NaiveDate::parse_from_str(word, DATE_FORMAT).ok()
+}
+
+// Приоритет в скобках: `(A)`
+fn parse_priority(word: &str) -> Option<char> {
+    parse_priority_letter(word.strip_prefix('(')?.strip_suffix(')')?)
+}
+
+fn parse_priority_letter(value: &str) -> Option<char> {
+    let mut chars = value.chars();
+    match (chars.next(), chars.next()) {
+        (Some(letter), None) if letter.is_ascii_uppercase() => Some(letter),
+        _ => None,
+    }
+}
+
+fn is_key_char(c: char) -> bool {
+    c.is_alphanumeric() || c == '-' || c == '_'
+}
+
+// Расширение `key:value`. Ссылки вида `https://...` остаются частью названия.
+fn extension(word: &str) -> Option<(&str, &str)> {
+    let (key, value) = word.split_once(':')?;
+    let valid_key = !key.is_empty() && key.chars().all(|c| is_key_char(c) || c == '%');
+    let valid_value = !value.is_empty() && !value.contains(':') && !value.starts_with('/');
+    (valid_key && valid_value).then_some((key, value))
+}
+
+// Процентное кодирование байтов символа
+fn push_encoded(encoded: &mut String, c: char) {
+    for byte in c.encode_utf8(&mut [0; 4]).bytes() {
+        encoded.push_str(&format!("%{:02X}", byte));
+    }
+}
+
+// Значение расширения, проект и контекст не могут содержать пробелы и двоеточия,
+// поэтому эти символы записываются с процентным кодированием
+fn encode(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '%' | ':' => push_encoded(&mut encoded, c),
+            c if c.is_whitespace() => push_encoded(&mut encoded, c),
+            c => encoded.push(c),
+        }
+    }
+    encoded
+}
+
+// Значение расширения к тому же не может начинаться с `/`, как путь ссылки
+fn encode_value(value: &str) -> String {
+    match encode(value).strip_prefix('/') {
+        Some(rest) => format!("%2F{}", rest),
+        None => encode(value),
+    }
+}
+
+// Ключ, совпадающий с полем задачи или с недопустимыми символами, кодируется с первого
+// символа: при разборе закодированный ключ не совпадет ни с одним полем
+fn encode_key(key: &str) -> String {
+    if !RESERVED_KEYS.contains(&key) && !key.is_empty() && key.chars().all(is_key_char) {
+        return key.to_string();
+    }
+    let mut encoded = String::with_capacity(key.len() * 3);
+    for (index, c) in key.chars().enumerate() {
+        if index == 0 || !is_key_char(c) {
+            push_encoded(&mut encoded, c);
+        } else {
+            encoded.push(c);
+        }
+    }
+    encoded
+}
+
+// Файлы todo.txt пишут и вручную: значение, которое не удалось раскодировать, берется как есть
+fn decode_or_raw(value: &str) -> String {
+    decode(value).unwrap_or_else(|| value.to_string())
+}
+
+fn decode(value: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(value.len());
+    let mut rest = value.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+// Проверяем разбор строк todo.txt и обратную запись без потери данных
+#[cfg(test)]
+mod todo_txt_tests {
+    use chrono::NaiveDate;
+
+    use crate::application::todo_txt::{format_line, parse_line};
+    use crate::domain::entities::Task;
+
+    fn date(value: &str) -> NaiveDate {
+        NaiveDate::parse_from_str(value, "%Y-%m-%d").unwrap()
+    }
+
+    #[test]
+    fn parses_all_parts_of_line() {
+        // Проверяем отметку выполнения, даты, проекты, контексты и расширения
+        let record = parse_line("x 2026-10-02 2026-10-01 Call +Family mom @phone due:2026-10-05 pri:B see https://example.com note:urgent").unwrap();
+        assert!(record.status);
+        assert_eq!(record.completed, Some(date("2026-10-02")));
+        assert_eq!(record.created, Some(date("2026-10-01")));
+        assert_eq!(record.priority, Some('B'));
+        assert_eq!(record.due, Some(date("2026-10-05")));
+        assert_eq!(record.title, "Call mom see https://example.com");
+        assert_eq!(record.description, record.title);
+        assert_eq!(record.projects, vec!["Family"]);
+        assert_eq!(record.tags, vec!["phone"]);
+        assert_eq!(record.extensions.into_iter().collect::<Vec<_>>(), vec![("note".to_string(), "urgent".to_string())]);
+    }
+
+    #[test]
+    fn priority_and_date_only_at_start() {
+        // Проверяем, что приоритет и дата в середине строки считаются частью названия
+        let record = parse_line("(A) 2026-10-01 Review (B) 2026-10-03 plan").unwrap();
+        assert!(!record.status);
+        assert_eq!(record.priority, Some('A'));
+        assert_eq!(record.created, Some(date("2026-10-01")));
+        assert_eq!(record.title, "Review (B) 2026-10-03 plan");
+
+        let record = parse_line("xylophone lesson").unwrap();
+        assert!(!record.status);
+        assert_eq!(record.title, "xylophone lesson");
+    }
+
+    #[test]
+    fn lines_roundtrip() {
+        // Проверяем, что строки в каноническом виде записываются обратно без изменений
+        for line in [
+            "Buy milk id:1",
+            "(A) 2026-10-01 Call mom +Family @phone due:2026-10-05 id:2",
+            "x 2026-10-02 2026-10-01 Call mom +Family @phone pri:A id:3",
+            "x Done without dates @home id:4",
+            "Plan trip +Travel +Fun @home @laptop description:Book%20hotel%3A%20Rome%0Aand%20flights id:42 rec:1w",
+            "+Big%20Project @at%20home title:x%20marks%20the%20+spot%20@home%20key%3Avalue id:5",
+            "Keep fields id:6 %2Bodd%20key:1 %64ue:tomorrow %69d:7 note:two%20words url:%2Fpath",
+        ] {
+            let record = parse_line(line).unwrap();
+            let id = record.id.unwrap();
+            assert_eq!(format_line(&record.into_task(id)), line);
+        }
+    }
+
+    #[test]
+    fn tasks_roundtrip() {
+        // Проверяем, что задача с полями, не имеющими позиции в todo.txt, переживает запись и разбор
+        let extensions = [("due", "someday"), ("pri", "Z"), ("description", "other"), ("id", "7"), ("note", "a  b"), ("url", "/path"), ("+odd key", "1")];
+        let tasks = vec![
+            Task { id: 1, title: "Write report".to_string(), description: "Q3: 100% done".to_string(), status: true, created: Some(date("2026-09-01")), priority: Some('C'), ..Default::default() },
+            Task { id: 2, title: "Reopened".to_string(), description: "Reopened".to_string(), completed: Some(date("2026-09-02")), ..Default::default() },
+            Task {
+                id: 3,
+                title: "Keep extensions".to_string(),
+                description: "Keep extensions".to_string(),
+                extensions: extensions.iter().map(|(key, value)| (key.to_string(), value.to_string())).collect(),
+                ..Default::default()
+            },
+            Task {
+                id: 4,
+                title: "Spaced".to_string(),
+                description: "Spaced".to_string(),
+                tags: vec!["at home".to_string(), "50%".to_string()],
+                projects: vec!["Big Project".to_string()],
+                ..Default::default()
+            },
+        ];
+        // Названия, которые при разборе текстом стали бы другими полями
+        let titles = ["x marks the spot", "(A) first", "2026-10-01 release", "ship +site", "call @home", "see note:urgent", "two  spaces", " padded ", "line\nbreak", ""];
+        let titled = titles.iter().enumerate().map(|(index, title)| Task {
+            id: 10 + index as i64,
+            title: title.to_string(),
+            description: "Desc".to_string(),
+            ..Default::default()
+        });
+        for task in tasks.into_iter().chain(titled) {
+            let record = parse_line(&format_line(&task)).unwrap();
+            assert_eq!(record.id, Some(task.id));
+            assert_eq!(record.into_task(task.id), task);
+        }
+    }
+
+    #[test]
+    fn broken_description_is_rejected() {
+        // Проверяем, что некорректное кодирование описания отклоняет строку
+        assert!(parse_line("Task description:50%").is_err());
+    }
+}
@@ -0,0 +1,158 @@
+use chrono::NaiveDate;
+
+// Наибольшая длина названия и описания задачи в символах по умолчанию
+pub const DEFAULT_MAX_TITLE_LENGTH: usize = 200;
+pub const DEFAULT_MAX_DESCRIPTION_LENGTH: usize = 10_000;
+
+// Правила проверки полей задачи. Применяются при создании задачи и при каждом изменении
+// названия и описания: запросом, в пакете, при загрузке из файла и через CalDAV.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ValidationPolicy {
+    pub trim: bool, // Обрезать пробельные символы по краям названия и описания перед сохранением
+    pub max_title_length: usize, // Наибольшая длина названия в символах
+    pub max_description_length: usize, // Наибольшая длина описания в символах
+    pub forbidden_characters: Vec<char>, // Символы, недопустимые в названии и описании
+    pub require_description: bool, // Описание обязательно
+}
+
+impl Default for ValidationPolicy {
+    fn default() -> Self {
+        ValidationPolicy {
+            trim: true,
+            max_title_length: DEFAULT_MAX_TITLE_LENGTH,
+            max_description_length: DEFAULT_MAX_DESCRIPTION_LENGTH,
+            forbidden_characters: Vec::new(),
+            require_description: true,
+        }
+    }
+}
+
+// Поле задачи, к которому относится ошибка
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TaskField {
+    Title,
+    Description,
+}
+
+// Ошибка в отдельном поле задачи
+#[derive(Clone, Debug, PartialEq)]
+pub enum ValidationError {
+    MissingTitle, // Название пустое или состоит из пробелов
+    MissingDescription, // Описание обязательно, но пустое или состоит из пробелов
+    TitleTooLong { max: usize }, // Название длиннее допустимого
+    DescriptionTooLong { max: usize }, // Описание длиннее допустимого
+    ForbiddenCharacter { field: TaskField, character: char }, // Поле содержит недопустимый символ
+    InvalidDue(String), // Срок не является датой
+}
+
+// Поля задачи в том виде, в каком они будут сохранены
+#[derive(Debug, PartialEq)]
+pub struct CheckedFields {
+    pub title: String,
+    pub description: String,
+    pub due: Option<NaiveDate>,
+}
+
+impl ValidationPolicy {
+    // Проверяет поля задачи и возвращает их после обрезки пробелов и разбора срока.
+    // Проверяются все поля сразу, чтобы клиент получил полный список ошибок.
+    pub fn check(&self, title: &str, description: &str, due: Option<&str>) -> Result<CheckedFields, Vec<ValidationError>> {
+        let (title, description) = if self.trim { (title.trim(), description.trim()) } else { (title, description) };
+        let mut errors = Vec::new();
+        // Строка из одних пробелов пуста независимо от того, обрезаются ли поля
+        if title.trim().is_empty() {
+            errors.push(ValidationError::MissingTitle);
+        } else if title.chars().count() > self.max_title_length {
+            errors.push(ValidationError::TitleTooLong { max: self.max_title_length });
+        }
+        if let Some(character) = self.forbidden(title) {
+            errors.push(ValidationError::ForbiddenCharacter { field: TaskField::Title, character });
+        }
+        if description.trim().is_empty() && self.require_description {
+            errors.push(ValidationError::MissingDescription);
+        } else if description.chars().count() > self.max_description_length {
+            errors.push(ValidationError::DescriptionTooLong { max: self.max_description_length });
+        }
+        if let Some(character) = self.forbidden(description) {
+            errors.push(ValidationError::ForbiddenCharacter { field: TaskField::Description, character });
+        }
+        let due = match due {
+            Some(due) => match NaiveDate::parse_from_str(due, "%Y-%m-%d") {
+                Ok(due) => Some(due),
+                Err(_) => {
+                    errors.push(ValidationError::InvalidDue(due.to_string()));
+                    None
+                }
+            },
+            None => None,
+        };
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+        Ok(CheckedFields { title: title.to_string(), description: description.to_string(), due })
+    }
+
+    // Первый недопустимый символ строки
+    fn forbidden(&self, value: &str) -> Option<char> {
+        value.chars().find(|character| self.forbidden_characters.contains(character))
+    }
+}
+
+// Проверяем правила проверки полей задачи
+#[cfg(test)]
+mod validation_tests {
+    use chrono::NaiveDate;
+
+    use crate::application::validation::{CheckedFields, TaskField, ValidationError, ValidationPolicy};
+
+    #[test]
+    fn trims_and_parses_fields() {
+        // Проверяем, что поля обрезаются перед сохранением, а срок разбирается
+        let checked = ValidationPolicy::default().check("  Title\n", "\tDescription ", Some("2026-10-25")).unwrap();
+        assert_eq!(
+            checked,
+            CheckedFields {
+                title: "Title".to_string(),
+                description: "Description".to_string(),
+                due: NaiveDate::from_ymd_opt(2026, 10, 25),
+            }
+        );
+    }
+
+    #[test]
+    fn whitespace_is_missing_even_without_trimming() {
+        // Проверяем, что поле из пробелов считается пустым, а без обрезки остальные поля сохраняются как есть
+        let policy = ValidationPolicy { trim: false, ..Default::default() };
+        assert_eq!(policy.check("   ", "Description", None), Err(vec![ValidationError::MissingTitle]));
+        assert_eq!(policy.check(" Title ", "Description", None).unwrap().title, " Title ");
+    }
+
+    #[test]
+    fn reports_lengths_and_forbidden_characters() {
+        // Проверяем ограничения длины и недопустимые символы в обоих полях
+        let policy = ValidationPolicy {
+            max_title_length: 5,
+            max_description_length: 10,
+            forbidden_characters: vec!['<', '>'],
+            ..Default::default()
+        };
+        let errors = policy.check("Title<", "<b>too long</b>", None).unwrap_err();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::TitleTooLong { max: 5 },
+                ValidationError::ForbiddenCharacter { field: TaskField::Title, character: '<' },
+                ValidationError::DescriptionTooLong { max: 10 },
+                ValidationError::ForbiddenCharacter { field: TaskField::Description, character: '<' },
+            ]
+        );
+    }
+
+    #[test]
+    fn description_can_be_optional() {
+        // Проверяем, что пустое описание допускается, если оно не обязательно
+        let policy = ValidationPolicy { require_description: false, ..Default::default() };
+        assert_eq!(policy.check("Title", "  ", None).unwrap().description, "");
+        assert_eq!(ValidationPolicy::default().check("Title", "  ", None), Err(vec![ValidationError::MissingDescription]));
+    }
+}
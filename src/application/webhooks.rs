@@ -0,0 +1,502 @@
+use std::net::IpAddr;
+use std::ops::Range;
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
+
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+
+use crate::application::feed::{FeedEvent, FeedItem, Subscription};
+use crate::domain::{
+    events::ChangeKind,
+    repositories::RepositoryError,
+    webhooks::{DeliveryAttempt, NewWebhook, Webhook, WebhookId, WebhookRepository},
+};
+
+// Заголовки уведомления
+pub const EVENT_HEADER: &str = "X-Webhook-Event"; // Вид изменения
+pub const DELIVERY_HEADER: &str = "X-Webhook-Delivery"; // Идентификатор доставки, общий для повторов
+pub const TIMESTAMP_HEADER: &str = "X-Webhook-Timestamp"; // Время попытки в секундах Unix
+pub const SIGNATURE_HEADER: &str = "X-Webhook-Signature"; // sha256=<HMAC-SHA256 в hex>
+
+// Подпись уведомления: HMAC-SHA256 секрета подписки над "<timestamp>.<тело>".
+// Время входит в подпись, чтобы получатель мог отвергать старые повторно присланные запросы.
+pub fn sign(secret: &str, timestamp: i64, body: &str) -> String {
+    // HMAC принимает ключ любой длины
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(format!("{}.{}", timestamp, body).as_bytes());
+    format!("{:x}", mac.finalize().into_bytes())
+}
+
+// Узел из адреса http или https: без схемы, учетных данных, порта и пути; IPv6 - без скобок
+pub fn host_of(url: &str) -> Option<&str> {
+    let rest = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://"))?;
+    let authority = rest.split(['/', '?', '#']).next().unwrap_or_default();
+    let host = authority.rsplit_once('@').map_or(authority, |(_, host)| host);
+    let host = match host.strip_prefix('[') {
+        Some(bracketed) => bracketed.split_once(']')?.0,
+        None => host.split(':').next().unwrap_or_default(),
+    };
+    (!host.is_empty()).then_some(host)
+}
+
+// Адрес этого компьютера, локальной сети или служебного диапазона. Уведомления на такие
+// адреса по умолчанию не отправляются, чтобы подписка не открывала доступ к внутренним
+// сервисам (SSRF).
+pub fn is_private_address(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => {
+            let [first, second, ..] = ip.octets();
+            // 100.64.0.0/10 - адреса за NAT провайдера
+            let shared = first == 100 && (64..128).contains(&second);
+            ip.is_loopback() || ip.is_private() || ip.is_link_local() || ip.is_unspecified() || ip.is_broadcast() || shared
+        }
+        IpAddr::V6(ip) => match ip.to_ipv4_mapped() {
+            Some(ip) => is_private_address(IpAddr::V4(ip)),
+            None => ip.is_loopback() || ip.is_unspecified() || ip.is_unique_local() || ip.is_unicast_link_local(),
+        },
+    }
+}
+
+// Узел, который без разрешения запрещен еще до обращения к DNS: имя localhost или внутренний IP-адрес
+pub fn is_private_host(host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_ascii_lowercase();
+    host == "localhost" || host.ends_with(".localhost") || host.parse().is_ok_and(is_private_address)
+}
+
+// Случайный идентификатор доставки для заголовка X-Webhook-Delivery
+fn delivery_id() -> String {
+    format!("{:032x}", rand::random::<u128>())
+}
+
+// Повторы недоставленного уведомления с экспоненциально растущей паузой
+#[derive(Clone, Debug, PartialEq)]
+pub struct RetryPolicy {
+    pub attempts: u32, // Сколько всего попыток, включая первую
+    pub initial_delay: Duration, // Пауза перед первым повтором
+    pub max_delay: Duration, // Предел паузы между попытками
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy { attempts: 5, initial_delay: Duration::from_secs(1), max_delay: Duration::from_secs(60) }
+    }
+}
+
+impl RetryPolicy {
+    // Пауза после неудачной попытки с номером `attempt`: удваивается с каждой попыткой
+    pub fn delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.saturating_pow(attempt.saturating_sub(1));
+        self.initial_delay.saturating_mul(factor).min(self.max_delay)
+    }
+}
+
+// Отправка уведомления по HTTP. Ok - код ответа получателя, Err - описание ошибки соединения.
+#[async_trait::async_trait(?Send)]
+pub trait WebhookSender {
+    async fn send(&self, url: &str, headers: &[(&'static str, String)], body: String) -> Result<u16, String>;
+}
+
+// Управление подписками на изменения задач
+pub struct WebhookService {
+    repository: Arc<dyn WebhookRepository>,
+    allow_private_hosts: bool, // Разрешены ли адреса этого компьютера и локальной сети
+}
+
+impl WebhookService {
+    // Конструктор
+    pub fn new(repository: Arc<dyn WebhookRepository>) -> WebhookService {
+        WebhookService { repository, allow_private_hosts: false }
+    }
+
+    // Разрешает подписки на адреса этого компьютера и локальной сети
+    pub fn with_private_hosts(self, allowed: bool) -> WebhookService {
+        WebhookService { allow_private_hosts: allowed, ..self }
+    }
+
+    pub async fn list(&self) -> Result<Vec<Webhook>, WebhookError> {
+        self.repository.list().await.map_err(WebhookError::from)
+    }
+
+    pub async fn get(&self, id: WebhookId) -> Result<Webhook, WebhookError> {
+        self.repository.get(id).await?.ok_or(WebhookError::NotFound)
+    }
+
+    // Проверяет адрес и секрет и сохраняет подписку
+    pub async fn create(&self, webhook: NewWebhook) -> Result<Webhook, WebhookError> {
+        let url = webhook.url.trim().to_string();
+        let host = url.strip_prefix("https://").or_else(|| url.strip_prefix("http://")).unwrap_or_default();
+        if host.is_empty() || host.starts_with('/') || url.chars().any(char::is_whitespace) {
+            return Err(WebhookError::InvalidUrl(webhook.url));
+        }
+        if !self.allow_private_hosts && host_of(&url).is_some_and(is_private_host) {
+            return Err(WebhookError::PrivateHost(url));
+        }
+        if webhook.secret.trim().is_empty() {
+            return Err(WebhookError::MissingSecret);
+        }
+        let mut events = Vec::new();
+        for kind in webhook.events {
+            if !events.contains(&kind) {
+                events.push(kind);
+            }
+        }
+        self.repository.create(NewWebhook { url, events, ..webhook }).await.map_err(WebhookError::from)
+    }
+
+    pub async fn delete(&self, id: WebhookId) -> Result<(), WebhookError> {
+        if self.repository.delete(id).await? { Ok(()) } else { Err(WebhookError::NotFound) }
+    }
+
+    // Журнал доставки подписки
+    pub async fn deliveries(&self, id: WebhookId) -> Result<Vec<DeliveryAttempt>, WebhookError> {
+        self.get(id).await?;
+        self.repository.deliveries(id).await.map_err(WebhookError::from)
+    }
+}
+
+#[derive(Debug, PartialEq)]
+pub enum WebhookError {
+    InvalidUrl(String), // Адрес подписки не является адресом http или https
+    PrivateHost(String), // Адрес подписки указывает на этот компьютер или локальную сеть
+    MissingSecret, // Не задан ключ подписи
+    NotFound, // Подписка не найдена
+    UnexpectedError, // Непредвиденная ошибка
+}
+
+impl From<RepositoryError> for WebhookError {
+    fn from(_: RepositoryError) -> Self {
+        WebhookError::UnexpectedError
+    }
+}
+
+// Рассылает события ленты изменений подписчикам. Каждое уведомление доставляется
+// отдельной задачей, чтобы медленный получатель не задерживал остальных.
+pub struct WebhookDispatcher {
+    repository: Arc<dyn WebhookRepository>,
+    sender: Rc<dyn WebhookSender>,
+    policy: RetryPolicy,
+}
+
+impl WebhookDispatcher {
+    // Конструктор
+    pub fn new(repository: Arc<dyn WebhookRepository>, sender: Rc<dyn WebhookSender>, policy: RetryPolicy) -> WebhookDispatcher {
+        WebhookDispatcher { repository, sender, policy }
+    }
+
+    // Читает ленту, пока она не закрыта; `render` превращает событие в тело уведомления.
+    // Должна выполняться внутри tokio::task::LocalSet, как в рантайме actix.
+    pub async fn run(self, mut subscription: Subscription, render: impl Fn(FeedEvent) -> String) {
+        let dispatcher = Rc::new(self);
+        let mut last_id = 0; // Номер последнего полученного события
+        let mut lagged = false; // Лента сообщила о потерянных событиях
+        while let Some(item) = subscription.next().await {
+            let FeedItem::Event(event) = item else {
+                // Какие события потеряны, станет ясно по номеру следующего события
+                lagged = true;
+                continue;
+            };
+            let lost = (last_id + 1)..event.id;
+            last_id = event.id;
+            let webhooks = match dispatcher.repository.list().await {
+                Ok(webhooks) => webhooks,
+                Err(e) => {
+                    eprintln!("Error loading webhooks for event {}: {:?}", event.id, e);
+                    continue;
+                }
+            };
+            if std::mem::take(&mut lagged) && !lost.is_empty() {
+                dispatcher.record_lost(&webhooks, lost).await;
+            }
+            let (id, kind) = (event.id, event.kind);
+            let body = render(event);
+            for webhook in webhooks.into_iter().filter(|webhook| webhook.wants(kind)) {
+                let (dispatcher, body) = (dispatcher.clone(), body.clone());
+                tokio::task::spawn_local(async move { dispatcher.deliver(webhook, id, kind, body).await });
+            }
+        }
+    }
+
+    // Записывает в журнал каждой подписки неудачную доставку событий, которые
+    // рассылка пропустила, отстав от ленты: их вид и содержимое уже неизвестны
+    async fn record_lost(&self, webhooks: &[Webhook], lost: Range<u64>) {
+        let (first, last) = (lost.start, lost.end - 1);
+        eprintln!("Webhook dispatcher lagged behind the change feed, events {}..={} were not delivered", first, last);
+        let error = format!("Events {} to {} were lost because the dispatcher fell behind the change feed", first, last);
+        for webhook in webhooks {
+            let record = DeliveryAttempt {
+                webhook_id: webhook.id,
+                delivery: delivery_id(),
+                event_id: first,
+                event: None,
+                attempt: 0,
+                attempted_at: Utc::now(),
+                status_code: None,
+                error: Some(error.clone()),
+                succeeded: false,
+            };
+            if let Err(e) = self.repository.record(&record).await {
+                eprintln!("Error recording lost events for webhook {}: {:?}", webhook.id, e);
+            }
+        }
+    }
+
+    // Доставляет уведомление с повторами и записывает каждую попытку в журнал.
+    // Возвращает true, если получатель принял уведомление.
+    pub async fn deliver(&self, webhook: Webhook, event_id: u64, event: ChangeKind, body: String) -> bool {
+        let delivery = delivery_id();
+        for attempt in 1..=self.policy.attempts.max(1) {
+            if attempt > 1 {
+                tokio::time::sleep(self.policy.delay(attempt - 1)).await;
+                // Подписку могли удалить, пока уведомление ждало повтора
+                if !matches!(self.repository.get(webhook.id).await, Ok(Some(_))) {
+                    return false;
+                }
+            }
+            let attempted_at = Utc::now();
+            let timestamp = attempted_at.timestamp();
+            let headers = [
+                (EVENT_HEADER, event.as_str().to_string()),
+                (DELIVERY_HEADER, delivery.clone()),
+                (TIMESTAMP_HEADER, timestamp.to_string()),
+                (SIGNATURE_HEADER, format!("sha256={}", sign(&webhook.secret, timestamp, &body))),
+            ];
+            let (status_code, error) = match self.sender.send(&webhook.url, &headers, body.clone()).await {
+                Ok(code) if (200..300).contains(&code) => (Some(code), None),
+                Ok(code) => (Some(code), Some(format!("Receiver responded with status {}", code))),
+                Err(e) => (None, Some(e)),
+            };
+            let succeeded = error.is_none();
+            let record = DeliveryAttempt {
+                webhook_id: webhook.id,
+                delivery: delivery.clone(),
+                event_id,
+                event: Some(event),
+                attempt,
+                attempted_at,
+                status_code,
+                error,
+                succeeded,
+            };
+            if let Err(e) = self.repository.record(&record).await {
+                eprintln!("Error recording delivery {} attempt {}: {:?}", delivery, attempt, e);
+            }
+            if succeeded {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+// Проверяем подпись, паузы между повторами, проверку подписок и доставку уведомлений
+#[cfg(test)]
+mod webhook_tests {
+    use std::cell::RefCell;
+    use std::collections::VecDeque;
+    use std::rc::Rc;
+    use std::sync::Arc;
+    use std::time::Duration;
+
+    use chrono::Utc;
+    use sqlx::SqlitePool;
+
+    use crate::application::feed::{ChangeFeed, FeedFilter};
+    use crate::application::history::Change;
+    use crate::application::webhooks::{
+        RetryPolicy, SIGNATURE_HEADER, TIMESTAMP_HEADER, WebhookDispatcher, WebhookError, WebhookSender, WebhookService, host_of,
+        is_private_host, sign,
+    };
+    use crate::domain::entities::Task;
+    use crate::domain::events::ChangeKind;
+    use crate::domain::webhooks::{MockWebhookRepository, NewWebhook, Webhook, WebhookRepository};
+    use crate::infrastructure::schema::MIGRATOR;
+    use crate::infrastructure::sqlite_webhooks::SqliteWebhookRepository;
+
+    // Запрос к получателю: адрес, заголовки уведомления и тело
+    type Request = (String, Vec<(&'static str, String)>, String);
+
+    // Получатель, отвечающий заранее заданными результатами и запоминающий запросы
+    struct ScriptedSender {
+        responses: RefCell<VecDeque<Result<u16, String>>>,
+        requests: RefCell<Vec<Request>>,
+    }
+
+    impl ScriptedSender {
+        fn new(responses: Vec<Result<u16, String>>) -> Rc<ScriptedSender> {
+            Rc::new(ScriptedSender { responses: RefCell::new(responses.into()), requests: RefCell::new(Vec::new()) })
+        }
+    }
+
+    #[async_trait::async_trait(?Send)]
+    impl WebhookSender for ScriptedSender {
+        async fn send(&self, url: &str, headers: &[(&'static str, String)], body: String) -> Result<u16, String> {
+            self.requests.borrow_mut().push((url.to_string(), headers.to_vec(), body));
+            self.responses.borrow_mut().pop_front().unwrap_or(Ok(200))
+        }
+    }
+
+    async fn repository() -> Arc<SqliteWebhookRepository> {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        Arc::new(SqliteWebhookRepository::new(pool))
+    }
+
+    fn instant_retries(attempts: u32) -> RetryPolicy {
+        RetryPolicy { attempts, initial_delay: Duration::ZERO, max_delay: Duration::ZERO }
+    }
+
+    fn header<'a>(headers: &'a [(&'static str, String)], name: &str) -> &'a str {
+        headers.iter().find(|(header, _)| *header == name).map(|(_, value)| value.as_str()).unwrap()
+    }
+
+    #[test]
+    fn signature_and_backoff() {
+        // Проверяем подпись по известному значению и удвоение паузы до предела
+        assert_eq!(sign("secret", 1700000000, "{}"), "b8569b78799ff9e3cbff0fc2d63a33a2b57f3282abd07c37ae5e8e7d79a5f163");
+        assert_ne!(sign("secret", 1700000001, "{}"), sign("secret", 1700000000, "{}"));
+
+        let policy = RetryPolicy { attempts: 5, initial_delay: Duration::from_secs(1), max_delay: Duration::from_secs(5) };
+        let delays: Vec<_> = (1..=5).map(|attempt| policy.delay(attempt).as_secs()).collect();
+        assert_eq!(delays, vec![1, 2, 4, 5, 5]);
+    }
+
+    #[tokio::test]
+    async fn create_validates_url_and_secret() {
+        // Проверяем, что подписка без адреса http(s) или без секрета не сохраняется
+        let mut repository = MockWebhookRepository::new();
+        repository.expect_create().never();
+        let service = WebhookService::new(Arc::new(repository));
+        for url in ["ftp://example.com/hook", "https://", "example.com", "https://exa mple.com"] {
+            let webhook = NewWebhook { url: url.to_string(), events: Vec::new(), secret: "secret".to_string() };
+            assert_eq!(service.create(webhook).await, Err(WebhookError::InvalidUrl(url.to_string())));
+        }
+        let webhook = NewWebhook { url: "https://example.com/hook".to_string(), events: Vec::new(), secret: " ".to_string() };
+        assert_eq!(service.create(webhook).await, Err(WebhookError::MissingSecret));
+        let private = [
+            "http://localhost:8080/hook", "http://api.localhost./", "http://127.0.0.1/hook", "https://user@10.1.2.3/", "http://169.254.169.254/latest",
+            "http://192.168.0.1:80", "http://[::1]:8080/hook", "http://[fe80::1]/", "http://[::ffff:127.0.0.1]/", "http://0.0.0.0/",
+        ];
+        for url in private {
+            let webhook = NewWebhook { url: url.to_string(), events: Vec::new(), secret: "secret".to_string() };
+            assert_eq!(service.create(webhook).await, Err(WebhookError::PrivateHost(url.to_string())), "{}", url);
+        }
+
+        let mut repository = MockWebhookRepository::new();
+        repository.expect_get().returning(|_| Ok(None));
+        repository.expect_delete().returning(|_| Ok(false));
+        let service = WebhookService::new(Arc::new(repository));
+        assert_eq!(service.deliveries(1).await, Err(WebhookError::NotFound));
+        assert_eq!(service.delete(1).await, Err(WebhookError::NotFound));
+    }
+
+    #[tokio::test]
+    async fn private_hosts_need_permission() {
+        // Проверяем, что внутренние адреса принимаются только с разрешением, а похожие внешние - всегда
+        let mut repository = MockWebhookRepository::new();
+        repository.expect_create().times(3).returning(|webhook| {
+            Ok(Webhook { id: 1, url: webhook.url, events: webhook.events, secret: webhook.secret, created_at: Utc::now() })
+        });
+        let service = WebhookService::new(Arc::new(repository));
+        for url in ["https://93.184.216.34/hook", "https://localhost.example/hook"] {
+            let webhook = NewWebhook { url: url.to_string(), events: Vec::new(), secret: "secret".to_string() };
+            assert!(service.create(webhook).await.is_ok(), "{}", url);
+        }
+        let service = service.with_private_hosts(true);
+        let webhook = NewWebhook { url: "http://127.0.0.1:9000/hook".to_string(), events: Vec::new(), secret: "secret".to_string() };
+        assert!(service.create(webhook).await.is_ok());
+
+        assert_eq!(host_of("https://user:pass@[2001:db8::1]:8443/path"), Some("2001:db8::1"));
+        assert_eq!(host_of("http://example.com?query"), Some("example.com"));
+        assert!(!is_private_host("100.128.0.1") && is_private_host("100.100.0.1"));
+    }
+
+    #[tokio::test]
+    async fn failed_delivery_is_retried_and_logged() {
+        // Проверяем повтор после ошибки соединения и ответа 500 и запись всех попыток
+        let repository = repository().await;
+        let webhook = repository
+            .create(NewWebhook { url: "https://ci.example/hook".to_string(), events: Vec::new(), secret: "secret".to_string() })
+            .await
+            .unwrap();
+        let sender = ScriptedSender::new(vec![Err("connection refused".to_string()), Ok(500), Ok(204)]);
+        let dispatcher = WebhookDispatcher::new(repository.clone(), sender.clone(), instant_retries(5));
+
+        assert!(dispatcher.deliver(webhook.clone(), 3, ChangeKind::Created, "{}".to_string()).await);
+        let log = repository.deliveries(webhook.id).await.unwrap();
+        let outcomes: Vec<_> = log.iter().map(|attempt| (attempt.attempt, attempt.status_code, attempt.succeeded)).collect();
+        assert_eq!(outcomes, vec![(1, None, false), (2, Some(500), false), (3, Some(204), true)]);
+        assert_eq!(log[0].error.as_deref(), Some("connection refused"));
+        assert!(log.iter().all(|attempt| attempt.delivery == log[0].delivery && attempt.event_id == 3));
+
+        let (_, headers, body) = sender.requests.borrow()[2].clone();
+        let timestamp: i64 = header(&headers, TIMESTAMP_HEADER).parse().unwrap();
+        assert_eq!(header(&headers, SIGNATURE_HEADER), format!("sha256={}", sign("secret", timestamp, &body)));
+
+        // Попытки заканчиваются
+        let sender = ScriptedSender::new(vec![Ok(500), Ok(502)]);
+        let dispatcher = WebhookDispatcher::new(repository.clone(), sender.clone(), instant_retries(2));
+        assert!(!dispatcher.deliver(webhook, 4, ChangeKind::Deleted, "{}".to_string()).await);
+        assert_eq!(sender.requests.borrow().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn events_are_sent_to_interested_webhooks() {
+        // Проверяем, что уведомление получают только подписки на этот вид изменения
+        let repository = repository().await;
+        for (url, events) in [("https://all.example", vec![]), ("https://deleted.example", vec![ChangeKind::Deleted])] {
+            let webhook = NewWebhook { url: url.to_string(), events, secret: "secret".to_string() };
+            repository.create(webhook).await.unwrap();
+        }
+        let feed = ChangeFeed::new(10);
+        let sender = ScriptedSender::new(Vec::new());
+        let dispatcher = WebhookDispatcher::new(repository.clone(), sender.clone(), instant_retries(1));
+        let subscription = feed.subscribe(None, FeedFilter::default());
+        let task = Task { id: 1, title: "Task".to_string(), ..Default::default() };
+        feed.publish(&[Change { before: None, after: Some(task) }]);
+        drop(feed);
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                dispatcher.run(subscription, |event| format!("{}:{}", event.id, event.kind.as_str())).await;
+                // Даем запущенным доставкам завершиться
+                tokio::task::yield_now().await;
+            })
+            .await;
+        let requests = sender.requests.borrow();
+        let sent: Vec<_> = requests.iter().map(|(url, _, body)| (url.as_str(), body.as_str())).collect();
+        assert_eq!(sent, vec![("https://all.example", "1:created")]);
+    }
+
+    #[tokio::test]
+    async fn lost_events_are_logged() {
+        // Проверяем, что события, пропущенные отставшей рассылкой, попадают в журнал неудачной записью
+        let repository = repository().await;
+        let webhook = NewWebhook { url: "https://ci.example/hook".to_string(), events: Vec::new(), secret: "secret".to_string() };
+        let webhook = repository.create(webhook).await.unwrap();
+        let feed = ChangeFeed::new(2);
+        let sender = ScriptedSender::new(Vec::new());
+        let dispatcher = WebhookDispatcher::new(repository.clone(), sender.clone(), instant_retries(1));
+        let subscription = feed.subscribe(None, FeedFilter::default());
+        // Лента помнит два события, а рассылка еще не прочла ни одного из четырех
+        for id in 1..=4 {
+            feed.publish(&[Change { before: None, after: Some(Task { id, title: "Task".to_string(), ..Default::default() }) }]);
+        }
+        drop(feed);
+
+        tokio::task::LocalSet::new()
+            .run_until(async {
+                dispatcher.run(subscription, |event| event.id.to_string()).await;
+                tokio::task::yield_now().await;
+            })
+            .await;
+        let sent: Vec<_> = sender.requests.borrow().iter().map(|(_, _, body)| body.clone()).collect();
+        assert_eq!(sent, vec!["3", "4"]);
+        // Запись о потере делается до запуска доставок, поэтому она первая в журнале
+        let log = repository.deliveries(webhook.id).await.unwrap();
+        assert_eq!((log[0].event_id, log[0].event, log[0].attempt, log[0].succeeded), (1, None, 0, false));
+        assert_eq!(log[0].error.as_deref(), Some("Events 1 to 2 were lost because the dispatcher fell behind the change feed"));
+    }
+}
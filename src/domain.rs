@@ -1,2 +1,5 @@
 pub mod entities;
-pub mod repositories;
\ No newline at end of file
+pub mod events;
+pub mod queries;
+pub mod repositories;
+pub mod webhooks;
\ No newline at end of file
@@ -1,9 +1,27 @@
-#[derive(Clone, Debug, PartialEq)]
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+
+#[derive(Clone, Debug, Default, PartialEq, serde::Serialize, serde::Deserialize)]
 pub struct Task {
-    pub id: TaskId, // Уникальный идентификатор задачи
+    pub id: TaskId, // Уникальный идентификатор задачи
     pub title: String, // Название задачи
     pub description: String, // Описание задачи
-    pub status: bool // Статус выполнения задачи
+    pub status: bool, // Статус выполнения задачи
+    #[serde(default)]
+    pub tags: Vec<String>, // Метки задачи; в todo.txt это контексты `@context`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub priority: Option<char>, // Приоритет от A (высший) до Z
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created: Option<NaiveDate>, // Дата создания
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub completed: Option<NaiveDate>, // Дата выполнения
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>, // Срок выполнения
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>, // Проекты, к которым относится задача (`+project` в todo.txt)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, String> // Прочие расширения todo.txt `key:value`
 }
 
 // Псевдоним для идентификатора задачи
@@ -0,0 +1,33 @@
+use crate::domain::entities::{Task, TaskId};
+
+// Доменные события, происходящие с задачами
+#[derive(Clone, Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+#[serde(tag = "type")]
+#[allow(clippy::enum_variant_names)]
+pub enum TaskEvent {
+    TaskCreated { task: Task }, // Задача создана
+    TaskUpdated { task: Task }, // Задача изменена
+    TaskToggled { id: TaskId }, // Статус задачи переключен
+    TaskDeleted { id: TaskId }, // Задача удалена
+}
+
+// Вид изменения задачи, о котором узнают подписчики: лента изменений и веб-хуки
+#[derive(Clone, Copy, Debug, PartialEq, serde::Serialize, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum ChangeKind {
+    Created,
+    Updated,
+    Toggled,
+    Deleted,
+}
+
+impl ChangeKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChangeKind::Created => "created",
+            ChangeKind::Updated => "updated",
+            ChangeKind::Toggled => "toggled",
+            ChangeKind::Deleted => "deleted",
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use crate::domain::entities::Task;
+
+// Условие отбора задач. Пустые поля не участвуют в отборе.
+#[derive(Clone, Debug, Default, PartialEq, serde::Deserialize, utoipa::ToSchema)]
+pub struct TaskFilter {
+    pub status: Option<bool>, // Статус выполнения
+    pub tag: Option<String>, // Метка, которая должна быть у задачи
+    pub title: Option<String>, // Шаблон названия: `*` - любая строка, `?` - любой символ
+}
+
+impl TaskFilter {
+    // Не задано ни одного условия, под фильтр попадают все задачи
+    pub fn is_empty(&self) -> bool {
+        self.status.is_none() && self.tag.is_none() && self.title.is_none()
+    }
+
+    pub fn matches(&self, task: &Task) -> bool {
+        self.status.is_none_or(|status| task.status == status)
+            && self.tag.as_ref().is_none_or(|tag| task.tags.contains(tag))
+            && self.title.as_ref().is_none_or(|pattern| glob_match(pattern, &task.title))
+    }
+}
+
+// Изменение, применяемое ко всем отобранным задачам
+#[derive(Clone, Debug, PartialEq, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum TaskMutation {
+    SetStatus(bool), // Установить статус выполнения
+    AddTag(String), // Добавить метку, если ее еще нет
+    RemoveTag(String), // Убрать метку
+    SetTags(Vec<String>), // Заменить все метки
+}
+
+impl TaskMutation {
+    pub fn apply(&self, task: &mut Task) {
+        match self {
+            TaskMutation::SetStatus(status) => task.status = *status,
+            TaskMutation::AddTag(tag) => {
+                if !task.tags.contains(tag) {
+                    task.tags.push(tag.clone());
+                }
+            }
+            TaskMutation::RemoveTag(tag) => task.tags.retain(|t| t != tag),
+            TaskMutation::SetTags(tags) => task.tags = tags.clone(),
+        }
+    }
+}
+
+// Сопоставление строки с шаблоном в духе SQLite GLOB (без классов символов)
+fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None; // Позиция последней `*` и точка возврата в тексте
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some('?') => {
+                p += 1;
+                t += 1;
+            }
+            Some(c) if *c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, matched)) => {
+                    p = star + 1;
+                    t = matched + 1;
+                    backtrack = Some((star, matched + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+// Проверяем отбор задач и применение изменений
+#[cfg(test)]
+mod task_filter_tests {
+    use crate::domain::entities::Task;
+    use crate::domain::queries::{TaskFilter, TaskMutation, glob_match};
+
+    fn task(title: &str, status: bool, tags: &[&str]) -> Task {
+        Task {
+            id: 1,
+            title: title.to_string(),
+            description: "Desc".to_string(),
+            status,
+            tags: tags.iter().map(|t| t.to_string()).collect(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn glob_supports_wildcards() {
+        // Проверяем поддержку `*` и `?` в шаблоне названия
+        assert!(glob_match("*fix*", "hotfix for login"));
+        assert!(glob_match("Task ?", "Task 1"));
+        assert!(!glob_match("Task ?", "Task 10"));
+        assert!(glob_match("*", ""));
+        assert!(!glob_match("fix", "Fix"));
+    }
+
+    #[test]
+    fn filter_combines_conditions() {
+        // Проверяем, что все заданные условия фильтра должны выполняться одновременно
+        let filter = TaskFilter { status: Some(false), tag: Some("release-1.2".to_string()), title: None };
+        assert!(filter.matches(&task("A", false, &["release-1.2"])));
+        assert!(!filter.matches(&task("A", true, &["release-1.2"])));
+        assert!(!filter.matches(&task("A", false, &["release-1.3"])));
+        assert!(TaskFilter::default().matches(&task("A", true, &[])));
+    }
+
+    #[test]
+    fn mutations_change_tags() {
+        // Проверяем добавление, удаление и замену меток
+        let mut t = task("A", false, &["a"]);
+        TaskMutation::AddTag("a".to_string()).apply(&mut t);
+        TaskMutation::AddTag("b".to_string()).apply(&mut t);
+        assert_eq!(t.tags, vec!["a", "b"]);
+        TaskMutation::RemoveTag("a".to_string()).apply(&mut t);
+        assert_eq!(t.tags, vec!["b"]);
+        TaskMutation::SetTags(vec!["c".to_string()]).apply(&mut t);
+        assert_eq!(t.tags, vec!["c"]);
+    }
+}
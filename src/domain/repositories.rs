@@ -1,26 +1,128 @@
+use chrono::{DateTime, Utc};
+
 use crate::domain::entities::{Task, TaskId};
+use crate::domain::queries::{TaskFilter, TaskMutation};
 
 #[mockall::automock]
 #[async_trait::async_trait]
 pub trait TaskRepository: Send + Sync {
     // Получение всех задач
-    async fn get_all(&self) -> Vec<Task>;
+    async fn get_all(&self) -> Result<Vec<Task>, RepositoryError>;
     // Получение задачи по идентификатору
     async fn get_by_id(&self, id: TaskId) -> Result<Task, RepositoryError>;
+    // Получение состояния всех задач на указанный момент времени.
+    // Поддерживается только хранилищами, которые помнят историю изменений.
+    async fn get_all_as_of(&self, _at: DateTime<Utc>) -> Result<Vec<Task>, RepositoryError> {
+        Err(RepositoryError::Unsupported)
+    }
+    // Поиск задач, подходящих под условие
+    async fn find(&self, filter: &TaskFilter) -> Result<Vec<Task>, RepositoryError> {
+        Ok(self.get_all().await?.into_iter().filter(|task| filter.matches(task)).collect())
+    }
+    // Применение изменения ко всем задачам, подходящим под условие.
+    // Возвращает идентификаторы измененных задач.
+    async fn update_matching(&mut self, filter: &TaskFilter, mutation: &TaskMutation) -> Result<Vec<TaskId>, RepositoryError> {
+        let tasks = self.find(filter).await?;
+        let ids = tasks.iter().map(|task| task.id).collect();
+        let operations = tasks
+            .into_iter()
+            .map(|mut task| {
+                mutation.apply(&mut task);
+                TaskOperation::Update(task)
+            })
+            .collect();
+        for result in self.execute_batch(operations, BatchMode::AllOrNothing).await {
+            result?;
+        }
+        Ok(ids)
+    }
     // Создание новой задачи
     async fn create(&mut self, task: Task) -> Result<(), RepositoryError>;
+    // Обновление существующей задачи целиком
+    async fn update(&mut self, task: Task) -> Result<(), RepositoryError>;
     // Удаление задачи
     async fn delete(&mut self, id: TaskId) -> Result<(), RepositoryError>;
     // Переключение статуса задачи
     async fn toggle(&mut self, id: TaskId) -> Result<(), RepositoryError>;
+    // Выполнение пакета операций. Результат возвращается для каждой операции в том же порядке.
+    // В режиме AllOrNothing при первой ошибке все изменения пакета откатываются,
+    // а остальные операции получают RepositoryError::Aborted.
+    async fn execute_batch(&mut self, operations: Vec<TaskOperation>, mode: BatchMode) -> Vec<Result<(), RepositoryError>>;
     // Получение следующего доступного идентификатора для новой задачи
     async fn next_id(&mut self) -> TaskId;
 }
 
+// Репозиторий в коробке тоже репозиторий: позволяет оборачивать декораторами
+// хранилище, выбранное во время выполнения
+#[async_trait::async_trait]
+impl<R: TaskRepository + ?Sized> TaskRepository for Box<R> {
+    async fn get_all(&self) -> Result<Vec<Task>, RepositoryError> {
+        (**self).get_all().await
+    }
+    async fn get_by_id(&self, id: TaskId) -> Result<Task, RepositoryError> {
+        (**self).get_by_id(id).await
+    }
+    async fn get_all_as_of(&self, at: DateTime<Utc>) -> Result<Vec<Task>, RepositoryError> {
+        (**self).get_all_as_of(at).await
+    }
+    async fn find(&self, filter: &TaskFilter) -> Result<Vec<Task>, RepositoryError> {
+        (**self).find(filter).await
+    }
+    async fn update_matching(&mut self, filter: &TaskFilter, mutation: &TaskMutation) -> Result<Vec<TaskId>, RepositoryError> {
+        (**self).update_matching(filter, mutation).await
+    }
+    async fn create(&mut self, task: Task) -> Result<(), RepositoryError> {
+        (**self).create(task).await
+    }
+    async fn update(&mut self, task: Task) -> Result<(), RepositoryError> {
+        (**self).update(task).await
+    }
+    async fn delete(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        (**self).delete(id).await
+    }
+    async fn toggle(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        (**self).toggle(id).await
+    }
+    async fn execute_batch(&mut self, operations: Vec<TaskOperation>, mode: BatchMode) -> Vec<Result<(), RepositoryError>> {
+        (**self).execute_batch(operations, mode).await
+    }
+    async fn next_id(&mut self) -> TaskId {
+        (**self).next_id().await
+    }
+}
+
+// Операция над задачей в составе пакета
+#[derive(Clone, Debug, PartialEq)]
+pub enum TaskOperation {
+    Create(Task), // Создать задачу
+    Update(Task), // Обновить задачу целиком
+    Toggle(TaskId), // Переключить статус
+    Delete(TaskId), // Удалить задачу
+}
+
+// Режим выполнения пакета операций
+#[derive(Clone, Copy, Debug, PartialEq, serde::Deserialize, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchMode {
+    AllOrNothing, // Все операции в одной транзакции: либо все, либо ни одной
+    BestEffort, // Выполнить все операции, которые получится
+}
+
+// Результаты пакета, в котором операция `failed` завершилась ошибкой `error`,
+// а все остальные отменены
+pub fn aborted_batch(len: usize, failed: usize, error: RepositoryError) -> Vec<Result<(), RepositoryError>> {
+    let mut results: Vec<_> = (0..len).map(|_| Err(RepositoryError::Aborted)).collect();
+    results[failed] = Err(error);
+    results
+}
+
 // Перечисление возможных ошибок, которые могут возникнуть при работе с репозиторием
 #[derive(Debug)]
 pub enum RepositoryError {
     TaskNotFound, // Задача не найдена
     TaskAlreadyExists, // Задача уже существует
+    Unsupported, // Операция не поддерживается хранилищем
+    Aborted, // Операция отменена из-за ошибки в другой операции пакета
+    Timeout, // Хранилище не ответило вовремя
     InternalError // Внутренняя ошибка репозитория
 }
@@ -0,0 +1,61 @@
+use chrono::{DateTime, Utc};
+
+use crate::domain::events::ChangeKind;
+use crate::domain::repositories::RepositoryError;
+
+// Псевдоним для идентификатора подписки
+pub type WebhookId = i64;
+
+// Подписка внешнего сервиса на изменения задач
+#[derive(Clone, Debug, PartialEq)]
+pub struct Webhook {
+    pub id: WebhookId,
+    pub url: String, // Адрес, на который отправляются уведомления
+    pub events: Vec<ChangeKind>, // Интересующие виды изменений; пустой список - все
+    pub secret: String, // Ключ подписи уведомлений
+    pub created_at: DateTime<Utc>,
+}
+
+impl Webhook {
+    pub fn wants(&self, kind: ChangeKind) -> bool {
+        self.events.is_empty() || self.events.contains(&kind)
+    }
+}
+
+// Новая подписка; идентификатор и время создания назначает хранилище
+#[derive(Clone, Debug, PartialEq)]
+pub struct NewWebhook {
+    pub url: String,
+    pub events: Vec<ChangeKind>,
+    pub secret: String,
+}
+
+// Попытка доставить уведомление
+#[derive(Clone, Debug, PartialEq)]
+pub struct DeliveryAttempt {
+    pub webhook_id: WebhookId,
+    pub delivery: String, // Идентификатор доставки, общий для всех ее попыток
+    pub event_id: u64, // Номер события в ленте изменений
+    pub event: Option<ChangeKind>, // Вид изменения; None, если событие потеряно до доставки
+    pub attempt: u32, // Номер попытки, начиная с 1; 0, если уведомление не отправлялось
+    pub attempted_at: DateTime<Utc>,
+    pub status_code: Option<u16>, // Код ответа получателя, если он ответил
+    pub error: Option<String>, // Ошибка соединения или код ответа, если получатель не принял уведомление
+    pub succeeded: bool,
+}
+
+#[mockall::automock]
+#[async_trait::async_trait]
+pub trait WebhookRepository: Send + Sync {
+    // Все подписки в порядке создания
+    async fn list(&self) -> Result<Vec<Webhook>, RepositoryError>;
+    // Подписка по идентификатору; None, если ее нет
+    async fn get(&self, id: WebhookId) -> Result<Option<Webhook>, RepositoryError>;
+    async fn create(&self, webhook: NewWebhook) -> Result<Webhook, RepositoryError>;
+    // Удаляет подписку вместе с журналом доставки; false, если ее не было
+    async fn delete(&self, id: WebhookId) -> Result<bool, RepositoryError>;
+    // Записывает попытку доставки в журнал
+    async fn record(&self, attempt: &DeliveryAttempt) -> Result<(), RepositoryError>;
+    // Журнал доставки подписки, от старых попыток к новым
+    async fn deliveries(&self, id: WebhookId) -> Result<Vec<DeliveryAttempt>, RepositoryError>;
+}
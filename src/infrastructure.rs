@@ -1,2 +1,9 @@
+pub mod caching;
+pub mod chaos;
+pub mod event_sourced;
 pub mod in_memory;
-pub mod sqlite;
\ No newline at end of file
+pub mod maintenance;
+pub mod schema;
+pub mod sqlite;
+pub mod sqlite_webhooks;
+pub mod webhook_sender;
\ No newline at end of file
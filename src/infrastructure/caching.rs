@@ -0,0 +1,229 @@
+use std::num::NonZeroUsize;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+
+use chrono::{DateTime, Utc};
+use lru::LruCache;
+
+use crate::domain::entities::{Task, TaskId};
+use crate::domain::queries::{TaskFilter, TaskMutation};
+use crate::domain::repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository};
+
+// Счетчики попаданий и промахов кэша
+#[derive(Debug, Default)]
+pub struct CacheStats {
+    hits: AtomicU64, // Ответ взят из кэша
+    misses: AtomicU64, // Пришлось обратиться к хранилищу
+}
+
+impl CacheStats {
+    pub fn hits(&self) -> u64 {
+        self.hits.load(Ordering::Relaxed)
+    }
+
+    pub fn misses(&self) -> u64 {
+        self.misses.load(Ordering::Relaxed)
+    }
+
+    fn hit(&self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+    }
+
+    fn miss(&self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+    }
+}
+
+// Декоратор, кэширующий чтение задач из любого репозитория.
+// Отдельные задачи хранятся в LRU, список всех задач - целиком.
+// Любая изменяющая операция сбрасывает затронутые записи кэша.
+pub struct CachingTaskRepository<R: TaskRepository> {
+    inner: R, // Оборачиваемый репозиторий
+    tasks: Mutex<LruCache<TaskId, Task>>, // Задачи по идентификатору
+    all: Mutex<Option<Vec<Task>>>, // Снимок результата get_all
+    stats: Arc<CacheStats>,
+}
+
+impl<R: TaskRepository> CachingTaskRepository<R> {
+    pub fn new(inner: R, capacity: NonZeroUsize) -> CachingTaskRepository<R> {
+        CachingTaskRepository {
+            inner,
+            tasks: Mutex::new(LruCache::new(capacity)),
+            all: Mutex::new(None),
+            stats: Arc::new(CacheStats::default()),
+        }
+    }
+
+    // Счетчики остаются доступны и после того, как репозиторий передан в сервис
+    pub fn stats(&self) -> Arc<CacheStats> {
+        self.stats.clone()
+    }
+
+    // Сбрасывает кэш одной задачи и снимок списка
+    fn invalidate(&self, id: TaskId) {
+        self.tasks.lock().unwrap().pop(&id);
+        self.all.lock().unwrap().take();
+    }
+
+    // Сбрасывает весь кэш
+    fn invalidate_all(&self) {
+        self.tasks.lock().unwrap().clear();
+        self.all.lock().unwrap().take();
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: TaskRepository> TaskRepository for CachingTaskRepository<R> {
+    async fn get_all(&self) -> Result<Vec<Task>, RepositoryError> {
+        if let Some(tasks) = self.all.lock().unwrap().as_ref() {
+            self.stats.hit();
+            return Ok(tasks.clone());
+        }
+        self.stats.miss();
+        let tasks = self.inner.get_all().await?;
+        *self.all.lock().unwrap() = Some(tasks.clone());
+        Ok(tasks)
+    }
+
+    async fn get_by_id(&self, id: TaskId) -> Result<Task, RepositoryError> {
+        if let Some(task) = self.tasks.lock().unwrap().get(&id) {
+            self.stats.hit();
+            return Ok(task.clone());
+        }
+        self.stats.miss();
+        let task = self.inner.get_by_id(id).await?;
+        self.tasks.lock().unwrap().put(id, task.clone());
+        Ok(task)
+    }
+
+    async fn get_all_as_of(&self, at: DateTime<Utc>) -> Result<Vec<Task>, RepositoryError> {
+        self.inner.get_all_as_of(at).await
+    }
+
+    async fn find(&self, filter: &TaskFilter) -> Result<Vec<Task>, RepositoryError> {
+        self.inner.find(filter).await
+    }
+
+    async fn update_matching(&mut self, filter: &TaskFilter, mutation: &TaskMutation) -> Result<Vec<TaskId>, RepositoryError> {
+        self.invalidate_all();
+        self.inner.update_matching(filter, mutation).await
+    }
+
+    async fn create(&mut self, task: Task) -> Result<(), RepositoryError> {
+        self.invalidate(task.id);
+        self.inner.create(task).await
+    }
+
+    async fn update(&mut self, task: Task) -> Result<(), RepositoryError> {
+        self.invalidate(task.id);
+        self.inner.update(task).await
+    }
+
+    async fn delete(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        self.invalidate(id);
+        self.inner.delete(id).await
+    }
+
+    async fn toggle(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        self.invalidate(id);
+        self.inner.toggle(id).await
+    }
+
+    async fn execute_batch(&mut self, operations: Vec<TaskOperation>, mode: BatchMode) -> Vec<Result<(), RepositoryError>> {
+        self.invalidate_all();
+        self.inner.execute_batch(operations, mode).await
+    }
+
+    async fn next_id(&mut self) -> TaskId {
+        self.inner.next_id().await
+    }
+}
+
+// Проверяем кэширование и сброс кэша, подсчитывая обращения к оборачиваемому репозиторию
+#[cfg(test)]
+mod caching_task_repository_tests {
+    use std::num::NonZeroUsize;
+
+    use mockall::predicate::*;
+
+    use crate::domain::entities::Task;
+    use crate::domain::repositories::{MockTaskRepository, RepositoryError, TaskRepository};
+    use crate::infrastructure::caching::CachingTaskRepository;
+
+    fn task(id: i64) -> Task {
+        Task { id, title: "Task".to_string(), description: "Desc".to_string(), ..Default::default() }
+    }
+
+    fn capacity(value: usize) -> NonZeroUsize {
+        NonZeroUsize::new(value).unwrap()
+    }
+
+    #[tokio::test]
+    async fn get_by_id_is_served_from_cache() {
+        // Проверяем, что повторное чтение задачи не доходит до хранилища
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_get_by_id().with(eq(1)).times(1).returning(|id| Ok(task(id)));
+
+        let repo = CachingTaskRepository::new(mock_repo, capacity(10));
+        assert_eq!(repo.get_by_id(1).await.unwrap(), task(1));
+        assert_eq!(repo.get_by_id(1).await.unwrap(), task(1));
+        assert_eq!(repo.stats().hits(), 1);
+        assert_eq!(repo.stats().misses(), 1);
+    }
+
+    #[tokio::test]
+    async fn not_found_is_not_cached() {
+        // Проверяем, что отсутствие задачи не запоминается
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_get_by_id().times(2).returning(|_| Err(RepositoryError::TaskNotFound));
+
+        let repo = CachingTaskRepository::new(mock_repo, capacity(10));
+        assert!(repo.get_by_id(1).await.is_err());
+        assert!(repo.get_by_id(1).await.is_err());
+        assert_eq!(repo.stats().misses(), 2);
+    }
+
+    #[tokio::test]
+    async fn least_recently_used_task_is_evicted() {
+        // Проверяем вытеснение давно не использованной задачи при переполнении
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_get_by_id().with(eq(1)).times(2).returning(|id| Ok(task(id)));
+        mock_repo.expect_get_by_id().with(eq(2)).times(1).returning(|id| Ok(task(id)));
+
+        let repo = CachingTaskRepository::new(mock_repo, capacity(1));
+        repo.get_by_id(1).await.unwrap();
+        repo.get_by_id(2).await.unwrap();
+        repo.get_by_id(1).await.unwrap();
+        assert_eq!(repo.stats().hits(), 0);
+    }
+
+    #[tokio::test]
+    async fn get_all_snapshot_is_invalidated_by_mutation() {
+        // Проверяем, что снимок списка задач сбрасывается изменяющей операцией
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_get_all().times(2).returning(|| Ok(vec![task(1)]));
+        mock_repo.expect_toggle().with(eq(1)).times(1).returning(|_| Ok(()));
+
+        let mut repo = CachingTaskRepository::new(mock_repo, capacity(10));
+        repo.get_all().await.unwrap();
+        repo.get_all().await.unwrap();
+        repo.toggle(1).await.unwrap();
+        repo.get_all().await.unwrap();
+        assert_eq!(repo.stats().hits(), 1);
+        assert_eq!(repo.stats().misses(), 2);
+    }
+
+    #[tokio::test]
+    async fn mutation_invalidates_cached_task() {
+        // Проверяем, что после изменения задача снова читается из хранилища
+        let mut mock_repo = MockTaskRepository::new();
+        mock_repo.expect_get_by_id().with(eq(1)).times(2).returning(|id| Ok(task(id)));
+        mock_repo.expect_update().times(1).returning(|_| Ok(()));
+
+        let mut repo = CachingTaskRepository::new(mock_repo, capacity(10));
+        repo.get_by_id(1).await.unwrap();
+        repo.update(task(1)).await.unwrap();
+        repo.get_by_id(1).await.unwrap();
+        assert_eq!(repo.stats().misses(), 2);
+    }
+}
@@ -0,0 +1,233 @@
+use std::sync::Mutex;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+use crate::domain::entities::{Task, TaskId};
+use crate::domain::queries::{TaskFilter, TaskMutation};
+use crate::domain::repositories::{aborted_batch, BatchMode, RepositoryError, TaskOperation, TaskRepository};
+
+// Операции репозитория, в которые можно внедрять сбои
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum ChaosOperation {
+    GetAll,
+    GetById,
+    GetAllAsOf,
+    Find,
+    UpdateMatching,
+    Create,
+    Update,
+    Delete,
+    Toggle,
+    ExecuteBatch,
+}
+
+// Настройки внедряемых сбоев
+#[derive(Clone, Debug, Default)]
+pub struct ChaosConfig {
+    pub seed: u64, // Зерно генератора, чтобы сбои можно было воспроизвести
+    pub max_latency: Duration, // Задержка перед каждой операцией выбирается случайно от нуля до этого значения
+    pub failure_rate: f64, // Доля операций, завершающихся RepositoryError::InternalError
+    pub timeout_rate: f64, // Доля операций, завершающихся RepositoryError::Timeout
+    pub timeout: Duration, // Сколько операция "висит" перед тайм-аутом
+    pub operations: Vec<ChaosOperation>, // Затрагиваемые операции; пустой список - все операции
+}
+
+// Сбой, выпавший операции
+enum Fault {
+    Failure,
+    Timeout,
+}
+
+// Декоратор для проверки устойчивости: добавляет к операциям оборачиваемого
+// репозитория случайные задержки, ошибки и тайм-ауты
+pub struct ChaosTaskRepository<R: TaskRepository> {
+    inner: R, // Оборачиваемый репозиторий
+    config: ChaosConfig,
+    rng: Mutex<StdRng>, // Генератор со всеми бросками по порядку операций
+}
+
+impl<R: TaskRepository> ChaosTaskRepository<R> {
+    pub fn new(inner: R, config: ChaosConfig) -> ChaosTaskRepository<R> {
+        let rng = Mutex::new(StdRng::seed_from_u64(config.seed));
+        ChaosTaskRepository { inner, config, rng }
+    }
+
+    // Выдерживает задержку и решает, завершится ли операция сбоем
+    async fn inject(&self, operation: ChaosOperation) -> Result<(), RepositoryError> {
+        if !self.config.operations.is_empty() && !self.config.operations.contains(&operation) {
+            return Ok(());
+        }
+        let (latency, fault) = {
+            let mut rng = self.rng.lock().unwrap();
+            let max_latency = self.config.max_latency.as_millis() as u64;
+            let latency = Duration::from_millis(rng.random_range(0..=max_latency));
+            let roll: f64 = rng.random();
+            let fault = if roll < self.config.failure_rate {
+                Some(Fault::Failure)
+            } else if roll < self.config.failure_rate + self.config.timeout_rate {
+                Some(Fault::Timeout)
+            } else {
+                None
+            };
+            (latency, fault)
+        };
+
+        if !latency.is_zero() {
+            tokio::time::sleep(latency).await;
+        }
+        match fault {
+            Some(Fault::Failure) => {
+                eprintln!("Внедрена ошибка в операцию {:?}", operation);
+                Err(RepositoryError::InternalError)
+            }
+            Some(Fault::Timeout) => {
+                tokio::time::sleep(self.config.timeout).await;
+                eprintln!("Внедрен тайм-аут в операцию {:?}", operation);
+                Err(RepositoryError::Timeout)
+            }
+            None => Ok(()),
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl<R: TaskRepository> TaskRepository for ChaosTaskRepository<R> {
+    async fn get_all(&self) -> Result<Vec<Task>, RepositoryError> {
+        self.inject(ChaosOperation::GetAll).await?;
+        self.inner.get_all().await
+    }
+
+    async fn get_by_id(&self, id: TaskId) -> Result<Task, RepositoryError> {
+        self.inject(ChaosOperation::GetById).await?;
+        self.inner.get_by_id(id).await
+    }
+
+    async fn get_all_as_of(&self, at: DateTime<Utc>) -> Result<Vec<Task>, RepositoryError> {
+        self.inject(ChaosOperation::GetAllAsOf).await?;
+        self.inner.get_all_as_of(at).await
+    }
+
+    async fn find(&self, filter: &TaskFilter) -> Result<Vec<Task>, RepositoryError> {
+        self.inject(ChaosOperation::Find).await?;
+        self.inner.find(filter).await
+    }
+
+    async fn update_matching(&mut self, filter: &TaskFilter, mutation: &TaskMutation) -> Result<Vec<TaskId>, RepositoryError> {
+        self.inject(ChaosOperation::UpdateMatching).await?;
+        self.inner.update_matching(filter, mutation).await
+    }
+
+    async fn create(&mut self, task: Task) -> Result<(), RepositoryError> {
+        self.inject(ChaosOperation::Create).await?;
+        self.inner.create(task).await
+    }
+
+    async fn update(&mut self, task: Task) -> Result<(), RepositoryError> {
+        self.inject(ChaosOperation::Update).await?;
+        self.inner.update(task).await
+    }
+
+    async fn delete(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        self.inject(ChaosOperation::Delete).await?;
+        self.inner.delete(id).await
+    }
+
+    async fn toggle(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        self.inject(ChaosOperation::Toggle).await?;
+        self.inner.toggle(id).await
+    }
+
+    async fn execute_batch(&mut self, operations: Vec<TaskOperation>, mode: BatchMode) -> Vec<Result<(), RepositoryError>> {
+        // Сбой затрагивает пакет целиком: хранилище до операций не дошло
+        if let Err(e) = self.inject(ChaosOperation::ExecuteBatch).await {
+            if operations.is_empty() {
+                return Vec::new();
+            }
+            return aborted_batch(operations.len(), 0, e);
+        }
+        self.inner.execute_batch(operations, mode).await
+    }
+
+    async fn next_id(&mut self) -> TaskId {
+        self.inner.next_id().await
+    }
+}
+
+// Проверяем внедрение сбоев и воспроизводимость при одинаковом зерне
+#[cfg(test)]
+mod chaos_task_repository_tests {
+    use crate::domain::entities::Task;
+    use crate::domain::repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository};
+    use crate::infrastructure::chaos::{ChaosConfig, ChaosOperation, ChaosTaskRepository};
+    use crate::infrastructure::in_memory::InMemoryTaskRepository;
+
+    fn task(id: i64) -> Task {
+        Task { id, title: "Task".to_string(), description: "Desc".to_string(), ..Default::default() }
+    }
+
+    async fn repo_with(config: ChaosConfig) -> ChaosTaskRepository<InMemoryTaskRepository> {
+        let mut inner = InMemoryTaskRepository::new();
+        inner.create(task(1)).await.unwrap();
+        ChaosTaskRepository::new(inner, config)
+    }
+
+    #[tokio::test]
+    async fn no_faults_by_default() {
+        // Проверяем, что без настроенных сбоев операции проходят без изменений
+        let repo = repo_with(ChaosConfig::default()).await;
+        assert_eq!(repo.get_by_id(1).await.unwrap(), task(1));
+        assert_eq!(repo.get_all().await.unwrap(), vec![task(1)]);
+    }
+
+    #[tokio::test]
+    async fn failure_rate_one_always_fails() {
+        // Проверяем, что при доле ошибок 1 каждая операция завершается InternalError
+        let repo = repo_with(ChaosConfig { failure_rate: 1.0, ..Default::default() }).await;
+        assert!(matches!(repo.get_by_id(1).await, Err(RepositoryError::InternalError)));
+        assert!(matches!(repo.get_all().await, Err(RepositoryError::InternalError)));
+    }
+
+    #[tokio::test]
+    async fn timeout_rate_one_always_times_out() {
+        // Проверяем, что при доле тайм-аутов 1 каждая операция завершается Timeout
+        let mut repo = repo_with(ChaosConfig { timeout_rate: 1.0, ..Default::default() }).await;
+        assert!(matches!(repo.toggle(1).await, Err(RepositoryError::Timeout)));
+        // Операция не дошла до хранилища
+        assert!(!repo.inner.get_by_id(1).await.unwrap().status);
+    }
+
+    #[tokio::test]
+    async fn only_selected_operations_are_affected() {
+        // Проверяем, что сбои внедряются только в перечисленные операции
+        let config = ChaosConfig { failure_rate: 1.0, operations: vec![ChaosOperation::GetAll], ..Default::default() };
+        let repo = repo_with(config).await;
+        assert!(repo.get_all().await.is_err());
+        assert!(repo.get_by_id(1).await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn batch_fault_aborts_every_operation() {
+        // Проверяем, что сбой пакета возвращает ошибку для каждой операции
+        let mut repo = repo_with(ChaosConfig { failure_rate: 1.0, ..Default::default() }).await;
+        let results = repo
+            .execute_batch(vec![TaskOperation::Toggle(1), TaskOperation::Delete(1)], BatchMode::BestEffort)
+            .await;
+        assert!(matches!(results[0], Err(RepositoryError::InternalError)));
+        assert!(matches!(results[1], Err(RepositoryError::Aborted)));
+        assert_eq!(repo.inner.get_all().await.unwrap(), vec![task(1)]);
+    }
+
+    #[tokio::test]
+    async fn same_seed_gives_same_faults() {
+        // Проверяем, что одинаковое зерно дает одинаковую последовательность сбоев
+        let config = ChaosConfig { seed: 42, failure_rate: 0.5, ..Default::default() };
+        let first = repo_with(config.clone()).await;
+        let second = repo_with(config).await;
+        for _ in 0..20 {
+            assert_eq!(first.get_by_id(1).await.is_ok(), second.get_by_id(1).await.is_ok());
+        }
+    }
+}
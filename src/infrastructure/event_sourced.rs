@@ -0,0 +1,394 @@
+use std::collections::BTreeMap;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::Path;
+
+use chrono::{DateTime, TimeDelta, Utc};
+
+use crate::domain::entities::{Task, TaskId};
+use crate::domain::events::TaskEvent;
+use crate::domain::repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository, aborted_batch};
+
+// Количество событий между снимками состояния по умолчанию
+const DEFAULT_SNAPSHOT_INTERVAL: usize = 100;
+
+// Событие, записанное в журнал
+#[derive(Clone, Debug, serde::Serialize, serde::Deserialize)]
+pub struct RecordedEvent {
+    pub recorded_at: DateTime<Utc>, // Время записи события
+    pub event: TaskEvent, // Само событие
+}
+
+// Снимок состояния задач после применения первых `version` событий журнала
+struct Snapshot {
+    version: usize,
+    tasks: BTreeMap<TaskId, Task>,
+}
+
+// Репозиторий, хранящий не задачи, а журнал событий над ними.
+// Состояние восстанавливается воспроизведением событий от ближайшего снимка.
+pub struct EventSourcedTaskRepository {
+    log: Vec<RecordedEvent>, // Журнал событий, только дозапись
+    snapshots: Vec<Snapshot>, // Периодические снимки состояния
+    snapshot_interval: usize, // Через сколько событий делать снимок
+    file: Option<File>, // Файл журнала (NDJSON), если журнал сохраняется на диск
+    last_id: TaskId, // Последний ID
+}
+
+impl EventSourcedTaskRepository {
+    pub fn new() -> EventSourcedTaskRepository {
+        EventSourcedTaskRepository::with_snapshot_interval(DEFAULT_SNAPSHOT_INTERVAL)
+    }
+
+    pub fn with_snapshot_interval(snapshot_interval: usize) -> EventSourcedTaskRepository {
+        EventSourcedTaskRepository {
+            log: Vec::new(),
+            snapshots: vec![Snapshot { version: 0, tasks: BTreeMap::new() }],
+            snapshot_interval: snapshot_interval.max(1),
+            file: None,
+            last_id: 0,
+        }
+    }
+
+    // Открывает журнал из файла, воспроизводя уже записанные события.
+    // Новые события дописываются в конец этого же файла.
+    pub fn open(path: &Path) -> std::io::Result<EventSourcedTaskRepository> {
+        let mut repository = EventSourcedTaskRepository::new();
+        if path.exists() {
+            for line in BufReader::new(File::open(path)?).lines() {
+                let line = line?;
+                if line.trim().is_empty() {
+                    continue;
+                }
+                let recorded: RecordedEvent = serde_json::from_str(&line)
+                    .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+                repository.push(recorded);
+            }
+        }
+        repository.file = Some(OpenOptions::new().create(true).append(true).open(path)?);
+        Ok(repository)
+    }
+
+    // Восстанавливает состояние после первых `version` событий
+    fn state(&self, version: usize) -> BTreeMap<TaskId, Task> {
+        let snapshot = self
+            .snapshots
+            .iter()
+            .rev()
+            .find(|snapshot| snapshot.version <= version)
+            .expect("snapshot for version 0 always exists");
+        let mut tasks = snapshot.tasks.clone();
+        for recorded in &self.log[snapshot.version..version] {
+            apply(&mut tasks, &recorded.event);
+        }
+        tasks
+    }
+
+    fn current_state(&self) -> BTreeMap<TaskId, Task> {
+        self.state(self.log.len())
+    }
+
+    // Выполняет одну операцию над текущим состоянием
+    fn execute(&mut self, operation: TaskOperation) -> Result<(), RepositoryError> {
+        let event = to_event(&self.current_state(), operation)?;
+        self.append(event)
+    }
+
+    // Записывает новое событие в журнал (и в файл, если он есть)
+    fn append(&mut self, event: TaskEvent) -> Result<(), RepositoryError> {
+        let now = Utc::now();
+        let recorded_at = match self.log.last() {
+            Some(last) if last.recorded_at >= now => last.recorded_at + TimeDelta::nanoseconds(1),
+            _ => now,
+        };
+        let recorded = RecordedEvent { recorded_at, event };
+
+        if let Some(file) = self.file.as_mut() {
+            let line = serde_json::to_string(&recorded).map_err(|e| {
+                eprintln!("Ошибка при сериализации события: {:?}", e);
+                RepositoryError::InternalError
+            })?;
+            writeln!(file, "{line}").and_then(|_| file.flush()).map_err(|e| {
+                eprintln!("Ошибка при записи события в журнал: {:?}", e);
+                RepositoryError::InternalError
+            })?;
+        }
+
+        self.push(recorded);
+        Ok(())
+    }
+
+    // Добавляет событие в журнал в памяти и при необходимости делает снимок
+    fn push(&mut self, recorded: RecordedEvent) {
+        if let TaskEvent::TaskCreated { task } = &recorded.event {
+            self.last_id = self.last_id.max(task.id);
+        }
+        self.log.push(recorded);
+
+        let version = self.log.len();
+        if version.is_multiple_of(self.snapshot_interval) {
+            let tasks = self.state(version);
+            self.snapshots.push(Snapshot { version, tasks });
+        }
+    }
+}
+
+impl Default for EventSourcedTaskRepository {
+    fn default() -> Self {
+        EventSourcedTaskRepository::new()
+    }
+}
+
+// Проверяет операцию относительно состояния задач и превращает ее в событие
+fn to_event(tasks: &BTreeMap<TaskId, Task>, operation: TaskOperation) -> Result<TaskEvent, RepositoryError> {
+    match operation {
+        TaskOperation::Create(task) if tasks.contains_key(&task.id) => Err(RepositoryError::TaskAlreadyExists),
+        TaskOperation::Create(task) => Ok(TaskEvent::TaskCreated { task }),
+        TaskOperation::Update(task) if tasks.contains_key(&task.id) => Ok(TaskEvent::TaskUpdated { task }),
+        TaskOperation::Toggle(id) if tasks.contains_key(&id) => Ok(TaskEvent::TaskToggled { id }),
+        TaskOperation::Delete(id) if tasks.contains_key(&id) => Ok(TaskEvent::TaskDeleted { id }),
+        _ => Err(RepositoryError::TaskNotFound),
+    }
+}
+
+// Применяет событие к состоянию задач
+fn apply(tasks: &mut BTreeMap<TaskId, Task>, event: &TaskEvent) {
+    match event {
+        TaskEvent::TaskCreated { task } | TaskEvent::TaskUpdated { task } => {
+            tasks.insert(task.id, task.clone());
+        }
+        TaskEvent::TaskToggled { id } => {
+            if let Some(task) = tasks.get_mut(id) {
+                task.status = !task.status;
+            }
+        }
+        TaskEvent::TaskDeleted { id } => {
+            tasks.remove(id);
+        }
+    }
+}
+
+#[async_trait::async_trait]
+impl TaskRepository for EventSourcedTaskRepository {
+    async fn next_id(&mut self) -> TaskId {
+        self.last_id += 1;
+        self.last_id
+    }
+
+    async fn get_all(&self) -> Result<Vec<Task>, RepositoryError> {
+        Ok(self.current_state().into_values().collect())
+    }
+
+    async fn get_by_id(&self, id: TaskId) -> Result<Task, RepositoryError> {
+        self.current_state()
+            .remove(&id)
+            .ok_or(RepositoryError::TaskNotFound)
+    }
+
+    async fn get_all_as_of(&self, at: DateTime<Utc>) -> Result<Vec<Task>, RepositoryError> {
+        // Время записи в журнале строго возрастает, поэтому достаточно найти границу
+        let version = self.log.partition_point(|recorded| recorded.recorded_at <= at);
+        Ok(self.state(version).into_values().collect())
+    }
+
+    async fn create(&mut self, task: Task) -> Result<(), RepositoryError> {
+        self.execute(TaskOperation::Create(task))
+    }
+
+    async fn update(&mut self, task: Task) -> Result<(), RepositoryError> {
+        self.execute(TaskOperation::Update(task))
+    }
+
+    async fn delete(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        self.execute(TaskOperation::Delete(id))
+    }
+
+    async fn toggle(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        self.execute(TaskOperation::Toggle(id))
+    }
+
+    async fn execute_batch(&mut self, operations: Vec<TaskOperation>, mode: BatchMode) -> Vec<Result<(), RepositoryError>> {
+        if mode == BatchMode::BestEffort {
+            return operations.into_iter().map(|operation| self.execute(operation)).collect();
+        }
+
+        // Сначала проверяем весь пакет на копии состояния и только потом пишем события в журнал
+        let len = operations.len();
+        let mut tasks = self.current_state();
+        let mut events = Vec::with_capacity(len);
+        for (index, operation) in operations.into_iter().enumerate() {
+            match to_event(&tasks, operation) {
+                Ok(event) => {
+                    apply(&mut tasks, &event);
+                    events.push(event);
+                }
+                Err(e) => return aborted_batch(len, index, e),
+            }
+        }
+        events.into_iter().map(|event| self.append(event)).collect()
+    }
+}
+
+// Проверяем реализацию репозитория на основе журнала событий
+#[cfg(test)]
+mod event_sourced_task_repository_tests {
+    use crate::domain::entities::Task;
+    use crate::domain::events::TaskEvent;
+    use crate::domain::repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository};
+    use crate::infrastructure::event_sourced::EventSourcedTaskRepository;
+
+    fn task(id: i64, title: &str) -> Task {
+        Task { id, title: title.to_string(), description: "Desc".to_string(), status: false, ..Default::default() }
+    }
+
+    #[tokio::test]
+    async fn create_and_get_all_tasks() {
+        // Проверяем создание задачи и получение всех задач
+        let mut repo = EventSourcedTaskRepository::new();
+        repo.create(task(1, "Task 1")).await.unwrap();
+        repo.create(task(2, "Task 2")).await.unwrap();
+
+        let all_tasks = repo.get_all().await.unwrap();
+        assert_eq!(all_tasks, vec![task(1, "Task 1"), task(2, "Task 2")]);
+        assert_eq!(repo.log.len(), 2);
+    }
+
+    #[tokio::test]
+    async fn create_task_already_exists() {
+        // Проверяем попытку создать задачу с уже существующим ID
+        let mut repo = EventSourcedTaskRepository::new();
+        repo.create(task(1, "Task")).await.unwrap();
+        let result = repo.create(task(1, "Task")).await;
+        assert!(matches!(result, Err(RepositoryError::TaskAlreadyExists)));
+        assert_eq!(repo.log.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn changes_are_recorded_as_events() {
+        // Проверяем, что переключение, изменение и удаление попадают в журнал и меняют состояние
+        let mut repo = EventSourcedTaskRepository::new();
+        repo.create(task(1, "Task")).await.unwrap();
+        repo.toggle(1).await.unwrap();
+        assert!(repo.get_by_id(1).await.unwrap().status);
+
+        repo.update(task(1, "Renamed")).await.unwrap();
+        assert_eq!(repo.get_by_id(1).await.unwrap().title, "Renamed");
+
+        repo.delete(1).await.unwrap();
+        assert!(matches!(repo.get_by_id(1).await, Err(RepositoryError::TaskNotFound)));
+        assert_eq!(
+            repo.log.iter().map(|e| e.event.clone()).collect::<Vec<_>>(),
+            vec![
+                TaskEvent::TaskCreated { task: task(1, "Task") },
+                TaskEvent::TaskToggled { id: 1 },
+                TaskEvent::TaskUpdated { task: task(1, "Renamed") },
+                TaskEvent::TaskDeleted { id: 1 },
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn toggle_and_delete_non_existing_task() {
+        // Проверяем, что для несуществующей задачи события не записываются
+        let mut repo = EventSourcedTaskRepository::new();
+        assert!(matches!(repo.toggle(99).await, Err(RepositoryError::TaskNotFound)));
+        assert!(matches!(repo.delete(99).await, Err(RepositoryError::TaskNotFound)));
+        assert!(repo.log.is_empty());
+    }
+
+    #[tokio::test]
+    async fn replay_with_snapshots_matches_full_replay() {
+        // Проверяем, что состояние, восстановленное от снимков, совпадает с полным воспроизведением
+        let mut with_snapshots = EventSourcedTaskRepository::with_snapshot_interval(3);
+        let mut without_snapshots = EventSourcedTaskRepository::with_snapshot_interval(usize::MAX);
+        for repo in [&mut with_snapshots, &mut without_snapshots] {
+            for id in 1..=10 {
+                repo.create(task(id, "Task")).await.unwrap();
+                if id % 2 == 0 {
+                    repo.toggle(id).await.unwrap();
+                }
+                if id % 3 == 0 {
+                    repo.delete(id).await.unwrap();
+                }
+            }
+        }
+
+        assert!(with_snapshots.snapshots.len() > 1);
+        assert_eq!(without_snapshots.snapshots.len(), 1);
+        assert_eq!(with_snapshots.get_all().await.unwrap(), without_snapshots.get_all().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn get_all_as_of_returns_past_state() {
+        // Проверяем запрос состояния задач на момент времени в прошлом
+        let mut repo = EventSourcedTaskRepository::with_snapshot_interval(2);
+        repo.create(task(1, "Task 1")).await.unwrap();
+        repo.create(task(2, "Task 2")).await.unwrap();
+        let after_create = repo.log[1].recorded_at;
+        repo.toggle(1).await.unwrap();
+        let after_toggle = repo.log[2].recorded_at;
+        repo.delete(2).await.unwrap();
+
+        let before_everything = repo.log[0].recorded_at - chrono::TimeDelta::seconds(1);
+        assert!(repo.get_all_as_of(before_everything).await.unwrap().is_empty());
+        assert_eq!(repo.get_all_as_of(after_create).await.unwrap(), vec![task(1, "Task 1"), task(2, "Task 2")]);
+
+        let toggled = repo.get_all_as_of(after_toggle).await.unwrap();
+        assert_eq!(toggled.len(), 2);
+        assert!(toggled[0].status);
+
+        assert_eq!(repo.get_all_as_of(chrono::Utc::now()).await.unwrap(), repo.get_all().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn open_replays_events_from_file() {
+        // Проверяем, что журнал сохраняется в файл и восстанавливается при повторном открытии
+        let path = std::env::temp_dir().join(format!("events-{}-{}.ndjson", std::process::id(), chrono::Utc::now().timestamp_nanos_opt().unwrap()));
+        {
+            let mut repo = EventSourcedTaskRepository::open(&path).unwrap();
+            let id = repo.next_id().await;
+            repo.create(task(id, "Persisted")).await.unwrap();
+            repo.toggle(id).await.unwrap();
+        }
+
+        let mut reopened = EventSourcedTaskRepository::open(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(reopened.log.len(), 2);
+        let tasks = reopened.get_all().await.unwrap();
+        assert_eq!(tasks.len(), 1);
+        assert_eq!(tasks[0].title, "Persisted");
+        assert!(tasks[0].status);
+        assert_eq!(reopened.next_id().await, 2);
+    }
+
+    #[tokio::test]
+    async fn batch_all_or_nothing_writes_nothing_on_error() {
+        // Проверяем, что пакет с ошибкой не оставляет событий в журнале
+        let mut repo = EventSourcedTaskRepository::new();
+        repo.create(task(1, "Task")).await.unwrap();
+
+        let results = repo.execute_batch(vec![
+            TaskOperation::Create(task(2, "Second")),
+            TaskOperation::Toggle(2),
+            TaskOperation::Delete(99),
+        ], BatchMode::AllOrNothing).await;
+
+        assert!(matches!(results[0], Err(RepositoryError::Aborted)));
+        assert!(matches!(results[1], Err(RepositoryError::Aborted)));
+        assert!(matches!(results[2], Err(RepositoryError::TaskNotFound)));
+        assert_eq!(repo.log.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn batch_all_or_nothing_sees_earlier_operations() {
+        // Проверяем, что операции пакета видят результат предыдущих операций того же пакета
+        let mut repo = EventSourcedTaskRepository::new();
+        let results = repo.execute_batch(vec![
+            TaskOperation::Create(task(1, "Task")),
+            TaskOperation::Toggle(1),
+        ], BatchMode::AllOrNothing).await;
+
+        assert!(results.iter().all(Result::is_ok));
+        assert!(repo.get_by_id(1).await.unwrap().status);
+    }
+}
\ No newline at end of file
@@ -1,5 +1,5 @@
 use crate::domain::entities::{Task, TaskId};
-use crate::domain::repositories::{TaskRepository, RepositoryError};
+use crate::domain::repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository, aborted_batch};
 
 pub struct InMemoryTaskRepository {
     tasks: Vec<Task>, // Вектор задач
@@ -12,6 +12,12 @@ impl InMemoryTaskRepository {
     }
 }
 
+impl Default for InMemoryTaskRepository {
+    fn default() -> Self {
+        InMemoryTaskRepository::new()
+    }
+}
+
 #[async_trait::async_trait]
 impl TaskRepository for InMemoryTaskRepository {
     async fn next_id(&mut self) -> TaskId {
@@ -19,8 +25,8 @@ impl TaskRepository for InMemoryTaskRepository {
         self.last_id
     }
 
-    async fn get_all(&self) -> Vec<Task> {
-        self.tasks.clone()
+    async fn get_all(&self) -> Result<Vec<Task>, RepositoryError> {
+        Ok(self.tasks.clone())
     }
 
     async fn get_by_id(&self, id: TaskId) -> Result<Task, RepositoryError> {
@@ -35,10 +41,21 @@ impl TaskRepository for InMemoryTaskRepository {
         if self.tasks.iter().any(|t| t.id == task.id) {
             return Err(RepositoryError::TaskAlreadyExists);
         }
+        // Задача могла прийти с собственным ID (например, при импорте)
+        self.last_id = self.last_id.max(task.id);
         self.tasks.push(task);
         Ok(())
     }
 
+    async fn update(&mut self, task: Task) -> Result<(), RepositoryError> {
+        if let Some(existing) = self.tasks.iter_mut().find(|t| t.id == task.id) {
+            *existing = task;
+            Ok(())
+        } else {
+            Err(RepositoryError::TaskNotFound)
+        }
+    }
+
     async fn delete(&mut self, id: TaskId) -> Result<(), RepositoryError> {
         if let Some(index) = self.tasks.iter().position(|t| t.id == id) {
             self.tasks.remove(index);
@@ -56,13 +73,35 @@ impl TaskRepository for InMemoryTaskRepository {
             Err(RepositoryError::TaskNotFound)
         }
     }
+
+    async fn execute_batch(&mut self, operations: Vec<TaskOperation>, mode: BatchMode) -> Vec<Result<(), RepositoryError>> {
+        let snapshot = self.tasks.clone();
+        let len = operations.len();
+        let mut results = Vec::with_capacity(len);
+        for (index, operation) in operations.into_iter().enumerate() {
+            let result = match operation {
+                TaskOperation::Create(task) => self.create(task).await,
+                TaskOperation::Update(task) => self.update(task).await,
+                TaskOperation::Toggle(id) => self.toggle(id).await,
+                TaskOperation::Delete(id) => self.delete(id).await,
+            };
+            match result {
+                Err(e) if mode == BatchMode::AllOrNothing => {
+                    self.tasks = snapshot;
+                    return aborted_batch(len, index, e);
+                }
+                result => results.push(result),
+            }
+        }
+        results
+    }
 }
 
 // Проверяем реализацию репозитория в памяти
 #[cfg(test)]
 mod in_memory_task_repository_tests {
     use crate::domain::entities::Task;
-    use crate::domain::repositories::{TaskRepository, RepositoryError};
+    use crate::domain::repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository};
     use crate::infrastructure::in_memory::InMemoryTaskRepository;
 
     #[tokio::test]
@@ -77,13 +116,13 @@ mod in_memory_task_repository_tests {
     async fn create_and_get_all_tasks() {
         // Проверяем создание задачи и получение всех задач
         let mut repo = InMemoryTaskRepository::new();
-        let task1 = Task { id: 1, title: "Task 1".to_string(), description: "Desc 1".to_string(), status: false };
-        let task2 = Task { id: 2, title: "Task 2".to_string(), description: "Desc 2".to_string(), status: true };
+        let task1 = Task { id: 1, title: "Task 1".to_string(), description: "Desc 1".to_string(), status: false, ..Default::default() };
+        let task2 = Task { id: 2, title: "Task 2".to_string(), description: "Desc 2".to_string(), status: true, ..Default::default() };
 
         repo.create(task1.clone()).await.unwrap();
         repo.create(task2.clone()).await.unwrap();
 
-        let all_tasks = repo.get_all().await;
+        let all_tasks = repo.get_all().await.unwrap();
         assert_eq!(all_tasks.len(), 2);
         assert!(all_tasks.contains(&task1));
         assert!(all_tasks.contains(&task2));
@@ -93,7 +132,7 @@ mod in_memory_task_repository_tests {
     async fn get_by_id_existing_task() {
         // Проверяем получение существующей задачи по ID
         let mut repo = InMemoryTaskRepository::new();
-        let task = Task { id: 1, title: "Test Task".to_string(), description: "Description".to_string(), status: false };
+        let task = Task { id: 1, title: "Test Task".to_string(), description: "Description".to_string(), status: false, ..Default::default() };
         repo.create(task.clone()).await.unwrap();
 
         let fetched_task = repo.get_by_id(1).await.unwrap();
@@ -113,21 +152,42 @@ mod in_memory_task_repository_tests {
     async fn create_task_already_exists() {
         // Проверяем попытку создать задачу с уже существующим ID
         let mut repo = InMemoryTaskRepository::new();
-        let task = Task { id: 1, title: "Task".to_string(), description: "Desc".to_string(), status: false };
+        let task = Task { id: 1, title: "Task".to_string(), description: "Desc".to_string(), status: false, ..Default::default() };
         repo.create(task.clone()).await.unwrap();
         let result = repo.create(task.clone()).await;
         assert!(matches!(result, Err(RepositoryError::TaskAlreadyExists)));
     }
 
+    #[tokio::test]
+    async fn update_existing_task() {
+        // Проверяем обновление существующей задачи
+        let mut repo = InMemoryTaskRepository::new();
+        let task = Task { id: 1, title: "Test Task".to_string(), description: "Description".to_string(), status: false, ..Default::default() };
+        repo.create(task.clone()).await.unwrap();
+
+        let updated = Task { title: "Updated".to_string(), status: true, ..task };
+        repo.update(updated.clone()).await.unwrap();
+        assert_eq!(repo.get_by_id(1).await.unwrap(), updated);
+    }
+
+    #[tokio::test]
+    async fn update_non_existing_task() {
+        // Проверяем попытку обновить несуществующую задачу
+        let mut repo = InMemoryTaskRepository::new();
+        let task = Task { id: 99, title: "Task".to_string(), description: "Desc".to_string(), status: false, ..Default::default() };
+        let result = repo.update(task).await;
+        assert!(matches!(result, Err(RepositoryError::TaskNotFound)));
+    }
+
     #[tokio::test]
     async fn delete_existing_task() {
         // Проверяем удаление существующей задачи
         let mut repo = InMemoryTaskRepository::new();
-        let task = Task { id: 1, title: "Test Task".to_string(), description: "Description".to_string(), status: false };
+        let task = Task { id: 1, title: "Test Task".to_string(), description: "Description".to_string(), status: false, ..Default::default() };
         repo.create(task.clone()).await.unwrap();
 
         repo.delete(1).await.unwrap();
-        let all_tasks = repo.get_all().await;
+        let all_tasks = repo.get_all().await.unwrap();
         assert!(all_tasks.is_empty());
     }
 
@@ -143,7 +203,7 @@ mod in_memory_task_repository_tests {
     async fn toggle_existing_task() {
         // Проверяем переключение статуса существующей задачи
         let mut repo = InMemoryTaskRepository::new();
-        let task = Task { id: 1, title: "Test Task".to_string(), description: "Description".to_string(), status: false };
+        let task = Task { id: 1, title: "Test Task".to_string(), description: "Description".to_string(), status: false, ..Default::default() };
         repo.create(task.clone()).await.unwrap();
 
         repo.toggle(1).await.unwrap();
@@ -162,4 +222,45 @@ mod in_memory_task_repository_tests {
         let result = repo.toggle(99).await;
         assert!(matches!(result, Err(RepositoryError::TaskNotFound)));
     }
+
+    #[tokio::test]
+    async fn batch_all_or_nothing_rolls_back_on_error() {
+        // Проверяем, что при ошибке в режиме AllOrNothing ни одно изменение пакета не сохраняется
+        let mut repo = InMemoryTaskRepository::new();
+        let task = Task { id: 1, title: "Task".to_string(), description: "Desc".to_string(), status: false, ..Default::default() };
+        repo.create(task.clone()).await.unwrap();
+
+        let results = repo.execute_batch(vec![
+            TaskOperation::Toggle(1),
+            TaskOperation::Create(Task { id: 2, ..task.clone() }),
+            TaskOperation::Delete(99),
+            TaskOperation::Delete(1),
+        ], BatchMode::AllOrNothing).await;
+
+        assert!(matches!(results[0], Err(RepositoryError::Aborted)));
+        assert!(matches!(results[1], Err(RepositoryError::Aborted)));
+        assert!(matches!(results[2], Err(RepositoryError::TaskNotFound)));
+        assert!(matches!(results[3], Err(RepositoryError::Aborted)));
+        assert_eq!(repo.get_all().await.unwrap(), vec![task]);
+    }
+
+    #[tokio::test]
+    async fn batch_best_effort_keeps_successful_operations() {
+        // Проверяем, что в режиме BestEffort успешные операции сохраняются несмотря на ошибки
+        let mut repo = InMemoryTaskRepository::new();
+        let task = Task { id: 1, title: "Task".to_string(), description: "Desc".to_string(), status: false, ..Default::default() };
+        repo.create(task.clone()).await.unwrap();
+
+        let results = repo.execute_batch(vec![
+            TaskOperation::Toggle(1),
+            TaskOperation::Delete(99),
+            TaskOperation::Create(Task { id: 2, ..task.clone() }),
+        ], BatchMode::BestEffort).await;
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(RepositoryError::TaskNotFound)));
+        assert!(results[2].is_ok());
+        assert!(repo.get_by_id(1).await.unwrap().status);
+        assert_eq!(repo.get_all().await.unwrap().len(), 2);
+    }
 }
\ No newline at end of file
@@ -0,0 +1,347 @@
+use std::fmt;
+use std::path::Path;
+use std::ptr::NonNull;
+
+use libsqlite3_sys::{sqlite3, sqlite3_backup_finish, sqlite3_backup_init, sqlite3_backup_step, sqlite3_errcode, SQLITE_DONE, SQLITE_OK};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Connection, SqliteConnection, SqlitePool};
+
+use crate::application::validation::{TaskField, ValidationError, ValidationPolicy};
+use crate::domain::entities::TaskId;
+use crate::infrastructure::schema::{self, SchemaError};
+
+#[derive(Debug)]
+pub enum MaintenanceError {
+    NotATaskDatabase, // В файле нет схемы задач
+    NewerThanBinary { backup: i64, binary: i64 }, // Резервная копия сделана более новой версией программы
+    Backup(i32), // Код ошибки SQLite backup API
+    Schema(SchemaError), // Ошибка проверки или применения миграций
+    Database(sqlx::Error), // Ошибка при работе с базой
+}
+
+impl From<sqlx::Error> for MaintenanceError {
+    fn from(e: sqlx::Error) -> Self {
+        MaintenanceError::Database(e)
+    }
+}
+
+impl From<SchemaError> for MaintenanceError {
+    fn from(e: SchemaError) -> Self {
+        MaintenanceError::Schema(e)
+    }
+}
+
+impl fmt::Display for MaintenanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MaintenanceError::NotATaskDatabase => write!(f, "file does not contain a task database"),
+            MaintenanceError::NewerThanBinary { backup, binary } => {
+                write!(f, "backup schema version {} is newer than the latest known version {}", backup, binary)
+            }
+            MaintenanceError::Backup(code) => write!(f, "sqlite backup failed with code {}", code),
+            MaintenanceError::Schema(e) => write!(f, "{}", e),
+            MaintenanceError::Database(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+// Проблема, найденная проверкой целостности
+#[derive(Debug, PartialEq)]
+pub enum IntegrityProblem {
+    Storage(String), // Сообщение PRAGMA integrity_check
+    DanglingReference { table: String, parent: String }, // Строка ссылается на несуществующую запись
+    EmptyTitle(TaskId), // Задача без названия, которую не пропустил бы TaskService
+    EmptyDescription(TaskId), // Задача без описания, хотя правила его требуют
+    TitleTooLong { id: TaskId, max: usize }, // Название длиннее допустимого правилами
+    DescriptionTooLong { id: TaskId, max: usize }, // Описание длиннее допустимого правилами
+    ForbiddenCharacter { id: TaskId, field: TaskField, character: char }, // Поле содержит запрещенный правилами символ
+    MissingStatus(TaskId), // Статус задачи не задан
+    InvalidTags(TaskId), // Метки не являются JSON-массивом
+    InvalidProjects(TaskId), // Проекты не являются JSON-массивом
+    InvalidExtensions(TaskId), // Расширения не являются JSON-объектом
+}
+
+impl fmt::Display for IntegrityProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            IntegrityProblem::Storage(message) => write!(f, "storage: {}", message),
+            IntegrityProblem::DanglingReference { table, parent } => write!(f, "row in {} references missing row in {}", table, parent),
+            IntegrityProblem::EmptyTitle(id) => write!(f, "task {} has an empty title", id),
+            IntegrityProblem::EmptyDescription(id) => write!(f, "task {} has an empty description", id),
+            IntegrityProblem::TitleTooLong { id, max } => write!(f, "task {} has a title longer than {} characters", id, max),
+            IntegrityProblem::DescriptionTooLong { id, max } => write!(f, "task {} has a description longer than {} characters", id, max),
+            IntegrityProblem::ForbiddenCharacter { id, field, character } => {
+                let field = match field {
+                    TaskField::Title => "title",
+                    TaskField::Description => "description",
+                };
+                write!(f, "task {} has a forbidden character {:?} in its {}", id, character, field)
+            }
+            IntegrityProblem::MissingStatus(id) => write!(f, "task {} has no status", id),
+            IntegrityProblem::InvalidTags(id) => write!(f, "task {} has tags that are not a JSON array", id),
+            IntegrityProblem::InvalidProjects(id) => write!(f, "task {} has projects that are not a JSON array", id),
+            IntegrityProblem::InvalidExtensions(id) => write!(f, "task {} has extensions that are not a JSON object", id),
+        }
+    }
+}
+
+// Копирует базу `source` в `destination` средствами SQLite backup API.
+// Источник при этом остается доступен для чтения и записи.
+//
+// Safety: оба указателя должны быть открытыми соединениями, которые не используются
+// другими потоками до завершения копирования.
+unsafe fn copy_database(destination: NonNull<sqlite3>, source: NonNull<sqlite3>) -> Result<(), MaintenanceError> {
+    let main = c"main";
+    unsafe {
+        let backup = sqlite3_backup_init(destination.as_ptr(), main.as_ptr(), source.as_ptr(), main.as_ptr());
+        if backup.is_null() {
+            return Err(MaintenanceError::Backup(sqlite3_errcode(destination.as_ptr())));
+        }
+        let step = sqlite3_backup_step(backup, -1);
+        let finish = sqlite3_backup_finish(backup);
+        if step != SQLITE_DONE {
+            return Err(MaintenanceError::Backup(step));
+        }
+        if finish != SQLITE_OK {
+            return Err(MaintenanceError::Backup(finish));
+        }
+    }
+    Ok(())
+}
+
+// Сохраняет копию базы в файл `path`, не останавливая работу с ней
+pub async fn backup(pool: &SqlitePool, path: &Path) -> Result<(), MaintenanceError> {
+    let mut source = pool.acquire().await?;
+    let mut destination = SqliteConnection::connect_with(&SqliteConnectOptions::new().filename(path).create_if_missing(true)).await?;
+    {
+        let mut source_handle = source.lock_handle().await?;
+        let mut destination_handle = destination.lock_handle().await?;
+        unsafe { copy_database(destination_handle.as_raw_handle(), source_handle.as_raw_handle())? };
+    }
+    destination.close().await?;
+    Ok(())
+}
+
+// Заменяет содержимое базы резервной копией из файла `path`.
+// Копия, сделанная более новой версией программы, отвергается; более старая
+// после восстановления доводится миграциями до текущей схемы.
+// Возвращает версию схемы восстановленной копии.
+pub async fn restore(pool: &SqlitePool, path: &Path) -> Result<i64, MaintenanceError> {
+    let options = SqliteConnectOptions::new().filename(path).read_only(true);
+    let backup_pool = SqlitePoolOptions::new().max_connections(1).connect_with(options).await?;
+    let version = match schema::applied_versions(&backup_pool).await.map_err(SchemaError::from)?.last() {
+        None => return Err(MaintenanceError::NotATaskDatabase),
+        Some(&backup) if backup > schema::latest_version() => {
+            return Err(MaintenanceError::NewerThanBinary { backup, binary: schema::latest_version() });
+        }
+        Some(&backup) => backup,
+    };
+
+    {
+        let mut source = backup_pool.acquire().await?;
+        let mut destination = pool.acquire().await?;
+        let mut source_handle = source.lock_handle().await?;
+        let mut destination_handle = destination.lock_handle().await?;
+        unsafe { copy_database(destination_handle.as_raw_handle(), source_handle.as_raw_handle())? };
+    }
+    backup_pool.close().await;
+    schema::up(pool).await?;
+    Ok(version)
+}
+
+// Проверяет физическую целостность файла и соблюдение правил предметной области:
+// поля задач проверяются теми же правилами `policy`, что и при сохранении через TaskService
+pub async fn check_integrity(pool: &SqlitePool, policy: &ValidationPolicy) -> Result<Vec<IntegrityProblem>, MaintenanceError> {
+    let mut problems = Vec::new();
+
+    let messages: Vec<String> = sqlx::query_scalar("PRAGMA integrity_check").fetch_all(pool).await?;
+    problems.extend(messages.into_iter().filter(|message| message != "ok").map(IntegrityProblem::Storage));
+
+    let references: Vec<(String, String)> = sqlx::query_as("SELECT \"table\", parent FROM pragma_foreign_key_check")
+        .fetch_all(pool)
+        .await?;
+    problems.extend(references.into_iter().map(|(table, parent)| IntegrityProblem::DanglingReference { table, parent }));
+
+    let rows = sqlx::query!(
+        r#"SELECT id as "id!",
+            title,
+            description,
+            status IS NULL as "missing_status!: bool",
+            CASE WHEN json_valid(tags) THEN json_type(tags) <> 'array' ELSE 1 END as "invalid_tags!: bool",
+            CASE WHEN json_valid(projects) THEN json_type(projects) <> 'array' ELSE 1 END as "invalid_projects!: bool",
+            CASE WHEN json_valid(extensions) THEN json_type(extensions) <> 'object' ELSE 1 END as "invalid_extensions!: bool"
+        FROM tasks
+        ORDER BY id"#
+    )
+    .fetch_all(pool)
+    .await?;
+    for row in rows {
+        let id = row.id;
+        // Срок хранится датой и разбирается при чтении, поэтому проверяются только название и описание
+        let errors = policy.check(&row.title, &row.description, None).err().unwrap_or_default();
+        problems.extend(errors.into_iter().filter_map(|error| match error {
+            ValidationError::MissingTitle => Some(IntegrityProblem::EmptyTitle(id)),
+            ValidationError::MissingDescription => Some(IntegrityProblem::EmptyDescription(id)),
+            ValidationError::TitleTooLong { max } => Some(IntegrityProblem::TitleTooLong { id, max }),
+            ValidationError::DescriptionTooLong { max } => Some(IntegrityProblem::DescriptionTooLong { id, max }),
+            ValidationError::ForbiddenCharacter { field, character } => Some(IntegrityProblem::ForbiddenCharacter { id, field, character }),
+            ValidationError::InvalidDue(_) => None,
+        }));
+        if row.missing_status {
+            problems.push(IntegrityProblem::MissingStatus(id));
+        }
+        if row.invalid_tags {
+            problems.push(IntegrityProblem::InvalidTags(id));
+        }
+        if row.invalid_projects {
+            problems.push(IntegrityProblem::InvalidProjects(id));
+        }
+        if row.invalid_extensions {
+            problems.push(IntegrityProblem::InvalidExtensions(id));
+        }
+    }
+    Ok(problems)
+}
+
+// Проверяем резервное копирование, восстановление и проверку целостности на временных файлах
+#[cfg(test)]
+mod maintenance_tests {
+    use std::path::PathBuf;
+
+    use sqlx::SqlitePool;
+    use tempfile::TempDir;
+
+    use crate::application::validation::{TaskField, ValidationPolicy};
+    use crate::infrastructure::maintenance::{self, IntegrityProblem, MaintenanceError};
+    use crate::infrastructure::schema::{self, ConnectionSettings};
+
+    // Файлы баз создаются во временном каталоге, который удаляется вместе с TempDir
+    fn temp_path(dir: &TempDir, name: &str) -> PathBuf {
+        dir.path().join(format!("{}.db", name))
+    }
+
+    async fn setup_db(dir: &TempDir, name: &str) -> SqlitePool {
+        let pool = schema::connect(&format!("sqlite:{}", temp_path(dir, name).display()), &ConnectionSettings::default()).await.unwrap();
+        schema::up(&pool).await.unwrap();
+        pool
+    }
+
+    async fn insert(pool: &SqlitePool, title: &str, description: &str) {
+        sqlx::query("INSERT INTO tasks (title, description, status, tags) VALUES (?, ?, 0, '[]')")
+            .bind(title)
+            .bind(description)
+            .execute(pool)
+            .await
+            .unwrap();
+    }
+
+    async fn titles(pool: &SqlitePool) -> Vec<String> {
+        sqlx::query_scalar("SELECT title FROM tasks ORDER BY id").fetch_all(pool).await.unwrap()
+    }
+
+    #[tokio::test]
+    async fn backup_and_restore_roundtrip() {
+        // Проверяем, что восстановление возвращает базу к состоянию на момент копии
+        let dir = tempfile::tempdir().unwrap();
+        let pool = setup_db(&dir, "tasks").await;
+        insert(&pool, "First", "Desc").await;
+        let path = temp_path(&dir, "backup");
+        maintenance::backup(&pool, &path).await.unwrap();
+
+        insert(&pool, "Second", "Desc").await;
+        let version = maintenance::restore(&pool, &path).await.unwrap();
+
+        assert_eq!(version, schema::latest_version());
+        assert_eq!(titles(&pool).await, vec!["First".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_newer_backup() {
+        // Проверяем, что копия с более новой схемой не восстанавливается
+        let dir = tempfile::tempdir().unwrap();
+        let source = setup_db(&dir, "source").await;
+        sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (?, 'future', 1, x'00', 0)")
+            .bind(schema::latest_version() + 1)
+            .execute(&source)
+            .await
+            .unwrap();
+        let path = temp_path(&dir, "backup");
+        maintenance::backup(&source, &path).await.unwrap();
+
+        let pool = setup_db(&dir, "tasks").await;
+        insert(&pool, "Kept", "Desc").await;
+        assert!(matches!(maintenance::restore(&pool, &path).await, Err(MaintenanceError::NewerThanBinary { .. })));
+        assert_eq!(titles(&pool).await, vec!["Kept".to_string()]);
+    }
+
+    #[tokio::test]
+    async fn restore_rejects_foreign_database() {
+        // Проверяем, что файл без схемы задач не восстанавливается
+        let dir = tempfile::tempdir().unwrap();
+        let path = temp_path(&dir, "foreign");
+        let foreign = schema::connect(&format!("sqlite:{}", path.display()), &ConnectionSettings::default()).await.unwrap();
+        sqlx::query("CREATE TABLE other (id INTEGER)").execute(&foreign).await.unwrap();
+        foreign.close().await;
+
+        let pool = setup_db(&dir, "tasks").await;
+        assert!(matches!(maintenance::restore(&pool, &path).await, Err(MaintenanceError::NotATaskDatabase)));
+    }
+
+    #[tokio::test]
+    async fn integrity_check_passes_on_valid_data() {
+        // Проверяем, что корректная база не вызывает замечаний
+        let dir = tempfile::tempdir().unwrap();
+        let pool = setup_db(&dir, "tasks").await;
+        insert(&pool, "Title", "Desc").await;
+        assert!(maintenance::check_integrity(&pool, &ValidationPolicy::default()).await.unwrap().is_empty());
+    }
+
+    #[tokio::test]
+    async fn integrity_check_reports_domain_violations() {
+        // Проверяем, что находятся задачи, которые TaskService не пропустил бы
+        let dir = tempfile::tempdir().unwrap();
+        let pool = setup_db(&dir, "tasks").await;
+        insert(&pool, "", "Desc").await;
+        insert(&pool, "Title", " ").await;
+        sqlx::query("INSERT INTO tasks (title, description, status, tags) VALUES ('T', 'D', NULL, 'oops')")
+            .execute(&pool)
+            .await
+            .unwrap();
+        sqlx::query("INSERT INTO tasks (title, description, status, projects, extensions) VALUES ('T', 'D', 0, '{}', '[]')")
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            maintenance::check_integrity(&pool, &ValidationPolicy::default()).await.unwrap(),
+            vec![
+                IntegrityProblem::EmptyTitle(1),
+                IntegrityProblem::EmptyDescription(2),
+                IntegrityProblem::MissingStatus(3),
+                IntegrityProblem::InvalidTags(3),
+                IntegrityProblem::InvalidProjects(4),
+                IntegrityProblem::InvalidExtensions(4),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn integrity_check_applies_validation_policy() {
+        // Проверяем, что длина и запрещенные символы проверяются по переданным правилам
+        let dir = tempfile::tempdir().unwrap();
+        let pool = setup_db(&dir, "tasks").await;
+        insert(&pool, "Too long title", "Desc").await;
+        insert(&pool, "Title", "<b>Desc</b>").await;
+        insert(&pool, "Title", "").await;
+
+        let policy = ValidationPolicy { max_title_length: 5, forbidden_characters: vec!['<'], require_description: false, ..Default::default() };
+        assert_eq!(
+            maintenance::check_integrity(&pool, &policy).await.unwrap(),
+            vec![
+                IntegrityProblem::TitleTooLong { id: 1, max: 5 },
+                IntegrityProblem::ForbiddenCharacter { id: 2, field: TaskField::Description, character: '<' },
+            ]
+        );
+        assert!(maintenance::check_integrity(&pool, &ValidationPolicy::default()).await.unwrap().contains(&IntegrityProblem::EmptyDescription(3)));
+    }
+}
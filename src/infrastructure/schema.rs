@@ -0,0 +1,262 @@
+use std::str::FromStr;
+use std::time::Duration;
+
+use sqlx::migrate::{Migrate, MigrateError, Migrator};
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode, SqlitePoolOptions, SqliteSynchronous};
+use sqlx::SqlitePool;
+
+// Миграции из каталога migrations/, встроенные в бинарник при сборке
+pub static MIGRATOR: Migrator = sqlx::migrate!();
+
+// Состояние одной миграции
+pub struct MigrationStatus {
+    pub version: i64, // Версия миграции
+    pub description: String, // Описание из имени файла
+    pub applied: bool, // Применена ли к базе
+}
+
+#[derive(Debug)]
+pub enum SchemaError {
+    NewerThanBinary { database: i64, binary: i64 }, // База изменена более новой версией программы
+    Migrate(MigrateError), // Ошибка при применении или откате миграций
+}
+
+impl std::fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SchemaError::NewerThanBinary { database, binary } => {
+                write!(f, "database schema version {} is newer than the latest known version {}", database, binary)
+            }
+            SchemaError::Migrate(e) => write!(f, "{}", e),
+        }
+    }
+}
+
+impl From<MigrateError> for SchemaError {
+    fn from(e: MigrateError) -> Self {
+        SchemaError::Migrate(e)
+    }
+}
+
+// Режим журнала SQLite
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum JournalMode {
+    Delete,
+    Truncate,
+    Persist,
+    Memory,
+    Wal, // Читатели не блокируют писателя
+    Off,
+}
+
+// Насколько тщательно SQLite сбрасывает данные на диск
+#[derive(Clone, Copy, Debug, PartialEq, clap::ValueEnum)]
+pub enum Synchronous {
+    Off,
+    Normal, // В режиме WAL безопасно и заметно быстрее Full
+    Full,
+    Extra,
+}
+
+// Настройки соединений с базой
+#[derive(Clone, Debug)]
+pub struct ConnectionSettings {
+    pub journal_mode: JournalMode,
+    pub synchronous: Synchronous,
+    pub busy_timeout: Duration, // Сколько ждать освобождения блокировки вместо немедленного SQLITE_BUSY
+    pub max_connections: u32, // Размер пула соединений
+    pub statement_cache_capacity: usize, // Количество подготовленных запросов, кэшируемых на соединение
+}
+
+impl Default for ConnectionSettings {
+    fn default() -> Self {
+        ConnectionSettings {
+            journal_mode: JournalMode::Wal,
+            synchronous: Synchronous::Normal,
+            busy_timeout: Duration::from_secs(5),
+            max_connections: 10,
+            statement_cache_capacity: 100,
+        }
+    }
+}
+
+// Подключается к базе с заданными настройками, создавая файл, если его еще нет
+pub async fn connect(url: &str, settings: &ConnectionSettings) -> Result<SqlitePool, sqlx::Error> {
+    let journal_mode = match settings.journal_mode {
+        JournalMode::Delete => SqliteJournalMode::Delete,
+        JournalMode::Truncate => SqliteJournalMode::Truncate,
+        JournalMode::Persist => SqliteJournalMode::Persist,
+        JournalMode::Memory => SqliteJournalMode::Memory,
+        JournalMode::Wal => SqliteJournalMode::Wal,
+        JournalMode::Off => SqliteJournalMode::Off,
+    };
+    let synchronous = match settings.synchronous {
+        Synchronous::Off => SqliteSynchronous::Off,
+        Synchronous::Normal => SqliteSynchronous::Normal,
+        Synchronous::Full => SqliteSynchronous::Full,
+        Synchronous::Extra => SqliteSynchronous::Extra,
+    };
+    let options = SqliteConnectOptions::from_str(url)?
+        .create_if_missing(true)
+        .journal_mode(journal_mode)
+        .synchronous(synchronous)
+        .busy_timeout(settings.busy_timeout)
+        .statement_cache_capacity(settings.statement_cache_capacity);
+    SqlitePoolOptions::new()
+        .max_connections(settings.max_connections)
+        .connect_with(options)
+        .await
+}
+
+// Последняя версия схемы, известная этому бинарнику
+pub fn latest_version() -> i64 {
+    MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| migration.version)
+        .max()
+        .unwrap_or(0)
+}
+
+// Версии миграций, уже примененных к базе, по возрастанию.
+// Таблица учета миграций не создается, если ее нет.
+pub async fn applied_versions(pool: &SqlitePool) -> Result<Vec<i64>, MigrateError> {
+    let exists: Option<String> = sqlx::query_scalar("SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'")
+        .fetch_optional(pool)
+        .await?;
+    if exists.is_none() {
+        return Ok(Vec::new());
+    }
+    let mut conn = pool.acquire().await?;
+    let mut versions: Vec<i64> = conn
+        .list_applied_migrations()
+        .await?
+        .into_iter()
+        .map(|migration| migration.version)
+        .collect();
+    versions.sort();
+    Ok(versions)
+}
+
+// Отказывает в работе с базой, схема которой новее, чем знает бинарник
+pub async fn check_version(pool: &SqlitePool) -> Result<(), SchemaError> {
+    let binary = latest_version();
+    match applied_versions(pool).await?.last() {
+        Some(&database) if database > binary => Err(SchemaError::NewerThanBinary { database, binary }),
+        _ => Ok(()),
+    }
+}
+
+// Состояние всех известных бинарнику миграций
+pub async fn status(pool: &SqlitePool) -> Result<Vec<MigrationStatus>, SchemaError> {
+    let applied = applied_versions(pool).await?;
+    Ok(MIGRATOR
+        .iter()
+        .filter(|migration| !migration.migration_type.is_down_migration())
+        .map(|migration| MigrationStatus {
+            version: migration.version,
+            description: migration.description.to_string(),
+            applied: applied.contains(&migration.version),
+        })
+        .collect())
+}
+
+// Применяет все еще не примененные миграции
+pub async fn up(pool: &SqlitePool) -> Result<(), SchemaError> {
+    check_version(pool).await?;
+    MIGRATOR.run(pool).await?;
+    Ok(())
+}
+
+// Откатывает миграции новее `target`; без `target` откатывает последнюю примененную.
+// Возвращает версию схемы после отката.
+pub async fn down(pool: &SqlitePool, target: Option<i64>) -> Result<i64, SchemaError> {
+    check_version(pool).await?;
+    let applied = applied_versions(pool).await?;
+    let target = match target {
+        Some(target) => target,
+        None => applied.iter().rev().nth(1).copied().unwrap_or(0),
+    };
+    MIGRATOR.undo(pool, target).await?;
+    Ok(applied_versions(pool).await?.last().copied().unwrap_or(0))
+}
+
+// Проверяем применение, откат и отказ работать с более новой схемой
+#[cfg(test)]
+mod schema_tests {
+    use sqlx::SqlitePool;
+
+    use crate::infrastructure::schema::{self, SchemaError};
+
+    async fn empty_db() -> SqlitePool {
+        SqlitePool::connect("sqlite::memory:")
+            .await
+            .expect("Failed to connect to in-memory SQLite")
+    }
+
+    #[tokio::test]
+    async fn fresh_database_has_no_applied_migrations() {
+        // Проверяем, что у пустой базы нет примененных миграций и таблица учета не создается
+        let pool = empty_db().await;
+        assert!(schema::applied_versions(&pool).await.unwrap().is_empty());
+        assert!(schema::status(&pool).await.unwrap().iter().all(|migration| !migration.applied));
+    }
+
+    #[tokio::test]
+    async fn up_applies_all_migrations() {
+        // Проверяем, что up применяет все миграции и повторный вызов ничего не ломает
+        let pool = empty_db().await;
+        schema::up(&pool).await.unwrap();
+        schema::up(&pool).await.unwrap();
+
+        assert!(schema::status(&pool).await.unwrap().iter().all(|migration| migration.applied));
+        assert_eq!(schema::applied_versions(&pool).await.unwrap().last(), Some(&schema::latest_version()));
+        sqlx::query("INSERT INTO tasks (title, description, status, tags) VALUES ('T', 'D', 0, '[]')")
+            .execute(&pool)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn down_reverts_last_migration() {
+        // Проверяем, что down без цели откатывает только последнюю миграцию
+        let pool = empty_db().await;
+        schema::up(&pool).await.unwrap();
+        let applied = schema::applied_versions(&pool).await.unwrap();
+
+        let version = schema::down(&pool, None).await.unwrap();
+        assert_eq!(version, applied[applied.len() - 2]);
+        assert!(!schema::status(&pool).await.unwrap().last().unwrap().applied);
+
+        schema::up(&pool).await.unwrap();
+        assert_eq!(schema::applied_versions(&pool).await.unwrap(), applied);
+    }
+
+    #[tokio::test]
+    async fn down_to_zero_reverts_everything() {
+        // Проверяем, что откат до нулевой версии удаляет схему целиком
+        let pool = empty_db().await;
+        schema::up(&pool).await.unwrap();
+        assert_eq!(schema::down(&pool, Some(0)).await.unwrap(), 0);
+        assert!(sqlx::query("SELECT * FROM tasks").fetch_all(&pool).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn newer_schema_is_refused() {
+        // Проверяем отказ работать с базой, к которой применена неизвестная бинарнику миграция
+        let pool = empty_db().await;
+        schema::up(&pool).await.unwrap();
+        let future = schema::latest_version() + 1;
+        sqlx::query("INSERT INTO _sqlx_migrations (version, description, success, checksum, execution_time) VALUES (?, 'future', 1, x'00', 0)")
+            .bind(future)
+            .execute(&pool)
+            .await
+            .unwrap();
+
+        assert!(matches!(
+            schema::check_version(&pool).await,
+            Err(SchemaError::NewerThanBinary { database, .. }) if database == future
+        ));
+        assert!(schema::up(&pool).await.is_err());
+    }
+}
@@ -1,8 +1,12 @@
-use sqlx::SqlitePool;
+use std::collections::BTreeMap;
+
+use chrono::NaiveDate;
+use sqlx::{SqliteExecutor, SqlitePool, types::Json};
 
 use crate::domain::{
     entities::{Task, TaskId},
-    repositories::{RepositoryError, TaskRepository},
+    queries::{TaskFilter, TaskMutation},
+    repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository, aborted_batch},
 };
 
 pub struct SqliteTaskRepository {
@@ -27,45 +31,143 @@ impl SqliteTaskRepository {
     }
 }
 
+// Строка таблицы tasks; метки и проекты хранятся JSON-массивами, расширения - JSON-объектом
+struct TaskRow {
+    id: TaskId,
+    title: String,
+    description: String,
+    status: bool,
+    tags: Json<Vec<String>>,
+    priority: Option<String>, // Одна буква
+    created: Option<NaiveDate>,
+    completed: Option<NaiveDate>,
+    due: Option<NaiveDate>,
+    projects: Json<Vec<String>>,
+    extensions: Json<BTreeMap<String, String>>,
+}
+
+impl From<TaskRow> for Task {
+    fn from(row: TaskRow) -> Self {
+        Task {
+            id: row.id,
+            title: row.title,
+            description: row.description,
+            status: row.status,
+            tags: row.tags.0,
+            priority: row.priority.and_then(|priority| priority.chars().next()),
+            created: row.created,
+            completed: row.completed,
+            due: row.due,
+            projects: row.projects.0,
+            extensions: row.extensions.0,
+        }
+    }
+}
+
 #[async_trait::async_trait]
 impl TaskRepository for SqliteTaskRepository {
-    async fn get_all(&self) -> Vec<Task> {
-        sqlx::query_as!(Task, r#"SELECT id as "id!", title as "title!", description as "description!", status as "status!" FROM tasks"#)
+    async fn get_all(&self) -> Result<Vec<Task>, RepositoryError> {
+        sqlx::query_as!(TaskRow, r#"SELECT id as "id!", title as "title!", description as "description!", status as "status!", tags as "tags!: Json<Vec<String>>", priority, created as "created: NaiveDate", completed as "completed: NaiveDate", due as "due: NaiveDate", projects as "projects!: Json<Vec<String>>", extensions as "extensions!: Json<BTreeMap<String, String>>" FROM tasks"#)
         .fetch_all(&self.pool)
         .await
-        .unwrap_or_else(|e| {
+        .map(|rows| rows.into_iter().map(Task::from).collect())
+        .map_err(|e| {
             eprintln!("Ошибка при получении всех задач: {:?}", e);
-            Vec::new()
+            RepositoryError::InternalError
         })
     }
     
-    async fn create(&mut self, task: Task) -> Result<(), RepositoryError> {
-        sqlx::query!(
-            r#"INSERT INTO tasks (id, title, description, status) VALUES (?, ?, ?, ?)"#,
-            task.id, task.title, task.description, task.status
-        )
-        .execute(&self.pool)
+    async fn get_by_id(&self, id: TaskId) -> Result<Task, RepositoryError> {
+        let task = sqlx::query_as!(TaskRow, r#"SELECT id as "id!", title as "title!", description as "description!", status as "status!", tags as "tags!: Json<Vec<String>>", priority, created as "created: NaiveDate", completed as "completed: NaiveDate", due as "due: NaiveDate", projects as "projects!: Json<Vec<String>>", extensions as "extensions!: Json<BTreeMap<String, String>>" FROM tasks WHERE id = ?"#, id)
+        .fetch_optional(&self.pool)
         .await
         .map_err(|e| {
-            eprintln!("Ошибка при создании задачи: {:?}", e);
-            match e {
-                sqlx::Error::Database(e) if e.code().unwrap_or_default() == "1555" => {
-                    RepositoryError::TaskAlreadyExists
-                }
-                _ => RepositoryError::InternalError,
-            }
+            eprintln!("Ошибка при получении задачи по ID: {:?}", e);
+            RepositoryError::InternalError
         })?;
-        Ok(())
+        task.map(Task::from).ok_or(RepositoryError::TaskNotFound)
     }
-    async fn get_by_id(&self, id: TaskId) -> Result<Task, RepositoryError> {
-        let task = sqlx::query_as!(Task, r#"SELECT id as "id!", title as "title!", description as "description!", status as "status!" FROM tasks WHERE id = ?"#, id)
-        .fetch_optional(&self.pool)
+
+    async fn find(&self, filter: &TaskFilter) -> Result<Vec<Task>, RepositoryError> {
+        let rows = sqlx::query_as!(
+            TaskRow,
+            r#"SELECT id as "id!", title as "title!", description as "description!", status as "status!", tags as "tags!: Json<Vec<String>>", priority, created as "created: NaiveDate", completed as "completed: NaiveDate", due as "due: NaiveDate", projects as "projects!: Json<Vec<String>>", extensions as "extensions!: Json<BTreeMap<String, String>>"
+            FROM tasks
+            WHERE (?1 IS NULL OR status = ?1)
+              AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(tasks.tags) WHERE value = ?2))
+              AND (?3 IS NULL OR title GLOB ?3)"#,
+            filter.status, filter.tag, filter.title
+        )
+        .fetch_all(&self.pool)
         .await
         .map_err(|e| {
-            eprintln!("Ошибка при получении задачи по ID: {:?}", e);
+            eprintln!("Ошибка при поиске задач: {:?}", e);
             RepositoryError::InternalError
         })?;
-        task.ok_or(RepositoryError::TaskNotFound)
+        Ok(rows.into_iter().map(Task::from).collect())
+    }
+
+    // Каждое изменение выполняется одним оператором UPDATE ... RETURNING
+    async fn update_matching(&mut self, filter: &TaskFilter, mutation: &TaskMutation) -> Result<Vec<TaskId>, RepositoryError> {
+        let (status, tag, title) = (filter.status, filter.tag.as_deref(), filter.title.as_deref());
+        let result = match mutation {
+            TaskMutation::SetStatus(value) => {
+                sqlx::query_scalar!(
+                    r#"UPDATE tasks SET status = ?4
+                    WHERE (?1 IS NULL OR status = ?1)
+                      AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(tasks.tags) WHERE value = ?2))
+                      AND (?3 IS NULL OR title GLOB ?3)
+                    RETURNING id"#,
+                    status, tag, title, value
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+            TaskMutation::AddTag(value) => {
+                sqlx::query_scalar!(
+                    r#"UPDATE tasks SET tags = CASE
+                        WHEN EXISTS (SELECT 1 FROM json_each(tasks.tags) WHERE value = ?4) THEN tags
+                        ELSE json_insert(tags, '$[#]', ?4)
+                    END
+                    WHERE (?1 IS NULL OR status = ?1)
+                      AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(tasks.tags) WHERE value = ?2))
+                      AND (?3 IS NULL OR title GLOB ?3)
+                    RETURNING id"#,
+                    status, tag, title, value
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+            TaskMutation::RemoveTag(value) => {
+                sqlx::query_scalar!(
+                    r#"UPDATE tasks SET tags = (SELECT json_group_array(value) FROM json_each(tasks.tags) WHERE value != ?4)
+                    WHERE (?1 IS NULL OR status = ?1)
+                      AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(tasks.tags) WHERE value = ?2))
+                      AND (?3 IS NULL OR title GLOB ?3)
+                    RETURNING id"#,
+                    status, tag, title, value
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+            TaskMutation::SetTags(value) => {
+                let value = Json(value);
+                sqlx::query_scalar!(
+                    r#"UPDATE tasks SET tags = ?4
+                    WHERE (?1 IS NULL OR status = ?1)
+                      AND (?2 IS NULL OR EXISTS (SELECT 1 FROM json_each(tasks.tags) WHERE value = ?2))
+                      AND (?3 IS NULL OR title GLOB ?3)
+                    RETURNING id"#,
+                    status, tag, title, value
+                )
+                .fetch_all(&self.pool)
+                .await
+            }
+        };
+        result.map_err(|e| {
+            eprintln!("Ошибка при массовом изменении задач: {:?}", e);
+            RepositoryError::InternalError
+        })
     }
     
     async fn next_id(&mut self) -> TaskId {
@@ -73,50 +175,158 @@ impl TaskRepository for SqliteTaskRepository {
         self.last_id
     }
 
+    async fn create(&mut self, task: Task) -> Result<(), RepositoryError> {
+        insert_task(&self.pool, &task).await?;
+        // Задача могла прийти с собственным ID (например, при импорте)
+        self.last_id = self.last_id.max(task.id);
+        Ok(())
+    }
+
+    async fn update(&mut self, task: Task) -> Result<(), RepositoryError> {
+        update_task(&self.pool, &task).await
+    }
+
     async fn delete(&mut self, id: TaskId) -> Result<(), RepositoryError> {
-        let affected_rows = sqlx::query!(r#"DELETE FROM tasks WHERE id = ?"#, id)
-            .execute(&self.pool)
+        delete_task(&self.pool, id).await
+    }
+
+    async fn toggle(&mut self, id: TaskId) -> Result<(), RepositoryError> {
+        toggle_task(&self.pool, id).await
+    }
+
+    // Весь пакет выполняется в одной транзакции. Ошибка отдельного оператора в SQLite
+    // не откатывает транзакцию, поэтому в режиме BestEffort остальные операции фиксируются.
+    async fn execute_batch(&mut self, operations: Vec<TaskOperation>, mode: BatchMode) -> Vec<Result<(), RepositoryError>> {
+        let len = operations.len();
+        for operation in &operations {
+            if let TaskOperation::Create(task) = operation {
+                self.last_id = self.last_id.max(task.id);
+            }
+        }
+        let mut transaction = match self.pool.begin().await {
+            Ok(transaction) => transaction,
+            Err(e) => {
+                eprintln!("Ошибка при открытии транзакции: {:?}", e);
+                return (0..len).map(|_| Err(RepositoryError::InternalError)).collect();
+            }
+        };
+
+        let mut results = Vec::with_capacity(len);
+        for (index, operation) in operations.iter().enumerate() {
+            let result = match operation {
+                TaskOperation::Create(task) => insert_task(&mut *transaction, task).await,
+                TaskOperation::Update(task) => update_task(&mut *transaction, task).await,
+                TaskOperation::Toggle(id) => toggle_task(&mut *transaction, *id).await,
+                TaskOperation::Delete(id) => delete_task(&mut *transaction, *id).await,
+            };
+            match result {
+                Err(e) if mode == BatchMode::AllOrNothing => {
+                    if let Err(e) = transaction.rollback().await {
+                        eprintln!("Ошибка при откате транзакции: {:?}", e);
+                    }
+                    return aborted_batch(len, index, e);
+                }
+                result => results.push(result),
+            }
+        }
+
+        if let Err(e) = transaction.commit().await {
+            eprintln!("Ошибка при фиксации транзакции: {:?}", e);
+            return (0..len).map(|_| Err(RepositoryError::InternalError)).collect();
+        }
+        results
+    }
+}
+
+async fn insert_task(executor: impl SqliteExecutor<'_>, task: &Task) -> Result<(), RepositoryError> {
+    let (tags, projects, extensions) = (Json(&task.tags), Json(&task.projects), Json(&task.extensions));
+    let priority = task.priority.map(String::from);
+    sqlx::query!(
+        r#"INSERT INTO tasks (id, title, description, status, tags, priority, created, completed, due, projects, extensions)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+        task.id, task.title, task.description, task.status, tags, priority, task.created, task.completed, task.due, projects, extensions
+    )
+    .execute(executor)
+    .await
+    .map_err(|e| {
+        eprintln!("Ошибка при создании задачи: {:?}", e);
+        match e {
+            sqlx::Error::Database(e) if e.code().unwrap_or_default() == "1555" => {
+                RepositoryError::TaskAlreadyExists
+            }
+            _ => RepositoryError::InternalError,
+        }
+    })?;
+    Ok(())
+}
+
+async fn update_task(executor: impl SqliteExecutor<'_>, task: &Task) -> Result<(), RepositoryError> {
+    let (tags, projects, extensions) = (Json(&task.tags), Json(&task.projects), Json(&task.extensions));
+    let priority = task.priority.map(String::from);
+    let affected_rows = sqlx::query!(
+        r#"UPDATE tasks SET title = ?, description = ?, status = ?, tags = ?, priority = ?, created = ?, completed = ?, due = ?, projects = ?, extensions = ?
+        WHERE id = ?"#,
+        task.title, task.description, task.status, tags, priority, task.created, task.completed, task.due, projects, extensions, task.id
+    )
+    .execute(executor)
+    .await
+    .map_err(|e| {
+        eprintln!("Ошибка при обновлении задачи: {:?}", e);
+        RepositoryError::InternalError
+    })?
+    .rows_affected();
+
+    if affected_rows == 0 {
+        Err(RepositoryError::TaskNotFound)
+    } else {
+        Ok(())
+    }
+}
+
+async fn delete_task(executor: impl SqliteExecutor<'_>, id: TaskId) -> Result<(), RepositoryError> {
+    let affected_rows = sqlx::query!(r#"DELETE FROM tasks WHERE id = ?"#, id)
+        .execute(executor)
+        .await
+        .map_err(|e| {
+            eprintln!("Ошибка при удалении задачи: {:?}", e);
+            RepositoryError::InternalError
+        })?
+        .rows_affected();
+
+    if affected_rows == 0 {
+        Err(RepositoryError::TaskNotFound)
+    } else {
+        Ok(())
+    }
+}
+
+async fn toggle_task(executor: impl SqliteExecutor<'_>, id: TaskId) -> Result<(), RepositoryError> {
+    let affected_rows =
+        sqlx::query!(r#"UPDATE tasks SET status = NOT status WHERE id = ?"#, id)
+            .execute(executor)
             .await
             .map_err(|e| {
-                eprintln!("Ошибка при удалении задачи: {:?}", e);
+                eprintln!("Ошибка при переключении статуса задачи: {:?}", e);
                 RepositoryError::InternalError
             })?
             .rows_affected();
 
-        if affected_rows == 0 {
-            Err(RepositoryError::TaskNotFound)
-        } else {
-            Ok(())
-        }
-    }
-
-    async fn toggle(&mut self, id: TaskId) -> Result<(), RepositoryError> {
-        let affected_rows =
-            sqlx::query!(r#"UPDATE tasks SET status = NOT status WHERE id = ?"#, id)
-                .execute(&self.pool)
-                .await
-                .map_err(|e| {
-                    eprintln!("Ошибка при переключении статуса задачи: {:?}", e);
-                    RepositoryError::InternalError
-                })?
-                .rows_affected();
-
-        if affected_rows == 0 {
-            Err(RepositoryError::TaskNotFound)
-        } else {
-            Ok(())
-        }
+    if affected_rows == 0 {
+        Err(RepositoryError::TaskNotFound)
+    } else {
+        Ok(())
     }
 }
 
 // Проверяем реализацию репозитория c SQLite
 #[cfg(test)]
 mod sqlite_task_repository_tests {
-    use std::path::Path;
+    use chrono::NaiveDate;
     use sqlx::SqlitePool;
-    use sqlx::migrate::Migrator;
     use crate::domain::entities::Task;
-    use crate::domain::repositories::{RepositoryError, TaskRepository};
+    use crate::domain::queries::{TaskFilter, TaskMutation};
+    use crate::domain::repositories::{BatchMode, RepositoryError, TaskOperation, TaskRepository};
+    use crate::infrastructure::schema::MIGRATOR;
     use crate::infrastructure::sqlite::SqliteTaskRepository;
 
     async fn setup_db() -> SqlitePool {
@@ -124,9 +334,7 @@ mod sqlite_task_repository_tests {
             .await
             .expect("Failed to connect to in-memory SQLite");
 
-        Migrator::new(Path::new("./migrations"))
-            .await
-            .expect("Failed to create migrator")
+        MIGRATOR
             .run(&pool)
             .await
             .expect("Failed to run migrations");
@@ -140,13 +348,13 @@ mod sqlite_task_repository_tests {
         let pool = setup_db().await;
         let mut repo = SqliteTaskRepository::new(pool).await;
 
-        let task1 = Task { id: repo.next_id().await, title: "Task 1".to_string(), description: "Desc 1".to_string(), status: false };
-        let task2 = Task { id: repo.next_id().await, title: "Task 2".to_string(), description: "Desc 2".to_string(), status: true };
+        let task1 = Task { id: repo.next_id().await, title: "Task 1".to_string(), description: "Desc 1".to_string(), status: false, ..Default::default() };
+        let task2 = Task { id: repo.next_id().await, title: "Task 2".to_string(), description: "Desc 2".to_string(), status: true, ..Default::default() };
 
         repo.create(task1.clone()).await.unwrap();
         repo.create(task2.clone()).await.unwrap();
 
-        let all_tasks = repo.get_all().await;
+        let all_tasks = repo.get_all().await.unwrap();
         assert_eq!(all_tasks.len(), 2);
         assert!(all_tasks.contains(&task1));
         assert!(all_tasks.contains(&task2));
@@ -172,6 +380,7 @@ mod sqlite_task_repository_tests {
             title: "Test Task".to_string(),
             description: "Description".to_string(),
             status: false,
+            ..Default::default()
         };
         repo.create(task.clone()).await.unwrap();
 
@@ -180,6 +389,34 @@ mod sqlite_task_repository_tests {
         assert_eq!(fetched_task.title, "Test Task");
     }
 
+    #[tokio::test]
+    async fn todo_txt_fields_are_stored() {
+        // Проверяем сохранение приоритета, дат, проектов и расширений при создании и изменении
+        let pool = setup_db().await;
+        let mut repo = SqliteTaskRepository::new(pool).await;
+
+        let mut task = Task {
+            id: repo.next_id().await,
+            title: "Call mom".to_string(),
+            description: "Call mom".to_string(),
+            tags: vec!["phone".to_string()],
+            priority: Some('A'),
+            created: NaiveDate::from_ymd_opt(2026, 10, 1),
+            due: NaiveDate::from_ymd_opt(2026, 10, 5),
+            projects: vec!["Family".to_string()],
+            extensions: [("note".to_string(), "urgent".to_string())].into(),
+            ..Default::default()
+        };
+        repo.create(task.clone()).await.unwrap();
+        assert_eq!(repo.get_by_id(task.id).await.unwrap(), task);
+
+        task.status = true;
+        task.completed = NaiveDate::from_ymd_opt(2026, 10, 2);
+        task.priority = None;
+        repo.update(task.clone()).await.unwrap();
+        assert_eq!(repo.get_all().await.unwrap(), vec![task]);
+    }
+
     #[tokio::test]
     async fn create_task_already_exists() {
         // Проверяем попытку создать задачу с уже существующим ID
@@ -190,12 +427,42 @@ mod sqlite_task_repository_tests {
             title: "Task".to_string(),
             description: "Desc".to_string(),
             status: false,
+            ..Default::default()
         };
         repo.create(task.clone()).await.unwrap();
         let result = repo.create(task.clone()).await;
         assert!(matches!(result, Err(RepositoryError::TaskAlreadyExists)));
     }
 
+    #[tokio::test]
+    async fn update_existing_task() {
+        // Проверяем обновление существующей задачи
+        let pool = setup_db().await;
+        let mut repo = SqliteTaskRepository::new(pool).await;
+        let task = Task {
+            id: repo.next_id().await,
+            title: "Test Task".to_string(),
+            description: "Description".to_string(),
+            status: false,
+            ..Default::default()
+        };
+        repo.create(task.clone()).await.unwrap();
+
+        let updated = Task { title: "Updated".to_string(), status: true, ..task };
+        repo.update(updated.clone()).await.unwrap();
+        assert_eq!(repo.get_by_id(updated.id).await.unwrap(), updated);
+    }
+
+    #[tokio::test]
+    async fn update_non_existing_task() {
+        // Проверяем попытку обновить несуществующую задачу
+        let pool = setup_db().await;
+        let mut repo = SqliteTaskRepository::new(pool).await;
+        let task = Task { id: 99, title: "Task".to_string(), description: "Desc".to_string(), status: false, ..Default::default() };
+        let result = repo.update(task).await;
+        assert!(matches!(result, Err(RepositoryError::TaskNotFound)));
+    }
+
     #[tokio::test]
     async fn delete_existing_task() {
         // Проверяем удаление существующей задачи
@@ -206,11 +473,12 @@ mod sqlite_task_repository_tests {
             title: "Test Task".to_string(),
             description: "Description".to_string(),
             status: false,
+            ..Default::default()
         };
         repo.create(task.clone()).await.unwrap();
 
         repo.delete(task.id).await.unwrap();
-        let all_tasks = repo.get_all().await;
+        let all_tasks = repo.get_all().await.unwrap();
         assert!(all_tasks.is_empty());
     }
 
@@ -233,6 +501,7 @@ mod sqlite_task_repository_tests {
             title: "Test Task".to_string(),
             description: "Description".to_string(),
             status: false,
+            ..Default::default()
         };
         repo.create(task.clone()).await.unwrap();
 
@@ -253,4 +522,112 @@ mod sqlite_task_repository_tests {
         let result = repo.toggle(99).await;
         assert!(matches!(result, Err(RepositoryError::TaskNotFound)));
     }
+
+    #[tokio::test]
+    async fn batch_all_or_nothing_rolls_back_transaction() {
+        // Проверяем, что при ошибке в режиме AllOrNothing транзакция откатывается целиком
+        let pool = setup_db().await;
+        let mut repo = SqliteTaskRepository::new(pool).await;
+        let task = Task { id: 1, title: "Task".to_string(), description: "Desc".to_string(), status: false, ..Default::default() };
+        repo.create(task.clone()).await.unwrap();
+
+        let results = repo.execute_batch(vec![
+            TaskOperation::Toggle(1),
+            TaskOperation::Create(Task { id: 2, ..task.clone() }),
+            TaskOperation::Create(Task { id: 1, ..task.clone() }),
+        ], BatchMode::AllOrNothing).await;
+
+        assert!(matches!(results[0], Err(RepositoryError::Aborted)));
+        assert!(matches!(results[1], Err(RepositoryError::Aborted)));
+        assert!(matches!(results[2], Err(RepositoryError::TaskAlreadyExists)));
+        assert_eq!(repo.get_all().await.unwrap(), vec![task]);
+    }
+
+    #[tokio::test]
+    async fn batch_best_effort_commits_successful_operations() {
+        // Проверяем, что в режиме BestEffort фиксируются все успешные операции
+        let pool = setup_db().await;
+        let mut repo = SqliteTaskRepository::new(pool).await;
+        let task = Task { id: 1, title: "Task".to_string(), description: "Desc".to_string(), status: false, ..Default::default() };
+        repo.create(task.clone()).await.unwrap();
+
+        let results = repo.execute_batch(vec![
+            TaskOperation::Toggle(1),
+            TaskOperation::Delete(99),
+            TaskOperation::Update(Task { id: 1, title: "Updated".to_string(), ..task.clone() }),
+            TaskOperation::Create(Task { id: 2, ..task.clone() }),
+        ], BatchMode::BestEffort).await;
+
+        assert!(results[0].is_ok());
+        assert!(matches!(results[1], Err(RepositoryError::TaskNotFound)));
+        assert!(results[2].is_ok());
+        assert!(results[3].is_ok());
+        assert_eq!(repo.get_by_id(1).await.unwrap().title, "Updated");
+        assert_eq!(repo.get_all().await.unwrap().len(), 2);
+    }
+
+    async fn seed_tagged(repo: &mut SqliteTaskRepository) {
+        for (title, tags) in [("Release notes", vec!["release-1.2"]), ("Release build", vec!["release-1.2", "ci"]), ("Bug fix", vec![])] {
+            let task = Task {
+                id: repo.next_id().await,
+                title: title.to_string(),
+                description: "Desc".to_string(),
+                tags: tags.into_iter().map(str::to_string).collect(),
+                ..Default::default()
+            };
+            repo.create(task).await.unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn find_by_tag_status_and_title() {
+        // Проверяем поиск задач по метке, статусу и шаблону названия
+        let pool = setup_db().await;
+        let mut repo = SqliteTaskRepository::new(pool).await;
+        seed_tagged(&mut repo).await;
+
+        let by_tag = repo.find(&TaskFilter { tag: Some("release-1.2".to_string()), ..Default::default() }).await.unwrap();
+        assert_eq!(by_tag.iter().map(|t| t.id).collect::<Vec<_>>(), vec![1, 2]);
+        assert_eq!(by_tag[1].tags, vec!["release-1.2", "ci"]);
+
+        let by_title = repo.find(&TaskFilter { title: Some("*fix".to_string()), status: Some(false), ..Default::default() }).await.unwrap();
+        assert_eq!(by_title.iter().map(|t| t.id).collect::<Vec<_>>(), vec![3]);
+    }
+
+    #[tokio::test]
+    async fn update_matching_in_single_statement() {
+        // Проверяем массовые изменения статуса и меток
+        let pool = setup_db().await;
+        let mut repo = SqliteTaskRepository::new(pool).await;
+        seed_tagged(&mut repo).await;
+
+        let release = TaskFilter { tag: Some("release-1.2".to_string()), ..Default::default() };
+        let ids = repo.update_matching(&release, &TaskMutation::SetStatus(true)).await.unwrap();
+        assert_eq!(ids, vec![1, 2]);
+        assert!(repo.get_by_id(2).await.unwrap().status);
+        assert!(!repo.get_by_id(3).await.unwrap().status);
+
+        let titled = TaskFilter { title: Some("Release*".to_string()), ..Default::default() };
+        repo.update_matching(&titled, &TaskMutation::AddTag("ci".to_string())).await.unwrap();
+        assert_eq!(repo.get_by_id(1).await.unwrap().tags, vec!["release-1.2", "ci"]);
+        assert_eq!(repo.get_by_id(2).await.unwrap().tags, vec!["release-1.2", "ci"]);
+
+        repo.update_matching(&titled, &TaskMutation::RemoveTag("release-1.2".to_string())).await.unwrap();
+        assert_eq!(repo.get_by_id(1).await.unwrap().tags, vec!["ci"]);
+
+        let ids = repo.update_matching(&titled, &TaskMutation::SetTags(vec!["release-1.3".to_string()])).await.unwrap();
+        assert_eq!(ids, vec![1, 2]);
+        assert_eq!(repo.get_by_id(2).await.unwrap().tags, vec!["release-1.3"]);
+    }
+
+    #[tokio::test]
+    async fn status_filter_uses_index() {
+        // Проверяем, что отбор по статусу идет по индексу, а не полным просмотром таблицы
+        let pool = setup_db().await;
+        let plan: Vec<(i64, i64, i64, String)> = sqlx::query_as("EXPLAIN QUERY PLAN SELECT id FROM tasks WHERE status = 1")
+            .fetch_all(&pool)
+            .await
+            .unwrap();
+        assert!(plan.iter().any(|(_, _, _, detail)| detail.contains("idx_tasks_status")));
+    }
 }
\ No newline at end of file
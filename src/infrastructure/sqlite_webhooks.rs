@@ -0,0 +1,216 @@
+use chrono::{DateTime, Utc};
+use sqlx::{SqlitePool, types::Json};
+
+use crate::domain::{
+    events::ChangeKind,
+    repositories::RepositoryError,
+    webhooks::{DeliveryAttempt, NewWebhook, Webhook, WebhookId, WebhookRepository},
+};
+
+// Подписки на изменения задач и журнал доставки в той же базе SQLite, что и задачи
+pub struct SqliteWebhookRepository {
+    pool: SqlitePool,
+}
+
+impl SqliteWebhookRepository {
+    pub fn new(pool: SqlitePool) -> SqliteWebhookRepository {
+        SqliteWebhookRepository { pool }
+    }
+}
+
+// Строка таблицы webhooks; виды изменений хранятся JSON-массивом
+struct WebhookRow {
+    id: WebhookId,
+    url: String,
+    events: Json<Vec<ChangeKind>>,
+    secret: String,
+    created_at: DateTime<Utc>,
+}
+
+impl From<WebhookRow> for Webhook {
+    fn from(row: WebhookRow) -> Self {
+        Webhook { id: row.id, url: row.url, events: row.events.0, secret: row.secret, created_at: row.created_at }
+    }
+}
+
+// Строка таблицы webhook_deliveries; вид изменения хранится его именем или NULL для потерянного события
+struct DeliveryRow {
+    webhook_id: WebhookId,
+    delivery: String,
+    event_id: i64,
+    event: Option<String>,
+    attempt: i64,
+    attempted_at: DateTime<Utc>,
+    status_code: Option<i64>,
+    error: Option<String>,
+    succeeded: bool,
+}
+
+impl TryFrom<DeliveryRow> for DeliveryAttempt {
+    type Error = serde_json::Error;
+
+    fn try_from(row: DeliveryRow) -> Result<Self, Self::Error> {
+        Ok(DeliveryAttempt {
+            webhook_id: row.webhook_id,
+            delivery: row.delivery,
+            event_id: row.event_id as u64,
+            event: row.event.map(|event| serde_json::from_value(serde_json::Value::String(event))).transpose()?,
+            attempt: row.attempt as u32,
+            attempted_at: row.attempted_at,
+            status_code: row.status_code.map(|code| code as u16),
+            error: row.error,
+            succeeded: row.succeeded,
+        })
+    }
+}
+
+fn internal(action: &str) -> impl FnOnce(sqlx::Error) -> RepositoryError + '_ {
+    move |e| {
+        eprintln!("Ошибка при {}: {:?}", action, e);
+        RepositoryError::InternalError
+    }
+}
+
+#[async_trait::async_trait]
+impl WebhookRepository for SqliteWebhookRepository {
+    async fn list(&self) -> Result<Vec<Webhook>, RepositoryError> {
+        let rows = sqlx::query_as!(
+            WebhookRow,
+            r#"SELECT id as "id!", url, events as "events!: Json<Vec<ChangeKind>>", secret, created_at as "created_at!: DateTime<Utc>" FROM webhooks ORDER BY id"#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(internal("получении подписок"))?;
+        Ok(rows.into_iter().map(Webhook::from).collect())
+    }
+
+    async fn get(&self, id: WebhookId) -> Result<Option<Webhook>, RepositoryError> {
+        let row = sqlx::query_as!(
+            WebhookRow,
+            r#"SELECT id as "id!", url, events as "events!: Json<Vec<ChangeKind>>", secret, created_at as "created_at!: DateTime<Utc>" FROM webhooks WHERE id = ?"#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(internal("получении подписки"))?;
+        Ok(row.map(Webhook::from))
+    }
+
+    async fn create(&self, webhook: NewWebhook) -> Result<Webhook, RepositoryError> {
+        let created_at = Utc::now();
+        let events = Json(&webhook.events);
+        let id = sqlx::query_scalar!(
+            r#"INSERT INTO webhooks (url, events, secret, created_at) VALUES (?, ?, ?, ?) RETURNING id as "id!""#,
+            webhook.url, events, webhook.secret, created_at
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(internal("создании подписки"))?;
+        Ok(Webhook { id, url: webhook.url, events: webhook.events, secret: webhook.secret, created_at })
+    }
+
+    async fn delete(&self, id: WebhookId) -> Result<bool, RepositoryError> {
+        let affected_rows = sqlx::query!(r#"DELETE FROM webhooks WHERE id = ?"#, id)
+            .execute(&self.pool)
+            .await
+            .map_err(internal("удалении подписки"))?
+            .rows_affected();
+        Ok(affected_rows > 0)
+    }
+
+    async fn record(&self, attempt: &DeliveryAttempt) -> Result<(), RepositoryError> {
+        let (event_id, event, number) = (attempt.event_id as i64, attempt.event.map(|event| event.as_str()), attempt.attempt as i64);
+        let status_code = attempt.status_code.map(i64::from);
+        sqlx::query!(
+            r#"INSERT INTO webhook_deliveries (webhook_id, delivery, event_id, event, attempt, attempted_at, status_code, error, succeeded)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)"#,
+            attempt.webhook_id, attempt.delivery, event_id, event, number, attempt.attempted_at, status_code, attempt.error, attempt.succeeded
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(internal("записи попытки доставки"))?;
+        Ok(())
+    }
+
+    async fn deliveries(&self, id: WebhookId) -> Result<Vec<DeliveryAttempt>, RepositoryError> {
+        let rows = sqlx::query_as!(
+            DeliveryRow,
+            r#"SELECT webhook_id, delivery, event_id, event, attempt, attempted_at as "attempted_at!: DateTime<Utc>", status_code, error, succeeded as "succeeded!"
+            FROM webhook_deliveries WHERE webhook_id = ? ORDER BY id"#,
+            id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(internal("получении журнала доставки"))?;
+        rows.into_iter().map(DeliveryAttempt::try_from).collect::<Result<_, _>>().map_err(|e| {
+            eprintln!("Ошибка при чтении журнала доставки: {:?}", e);
+            RepositoryError::InternalError
+        })
+    }
+}
+
+// Проверяем хранение подписок и журнала доставки в SQLite
+#[cfg(test)]
+mod sqlite_webhook_repository_tests {
+    use chrono::Utc;
+    use sqlx::SqlitePool;
+
+    use crate::domain::events::ChangeKind;
+    use crate::domain::webhooks::{DeliveryAttempt, NewWebhook, WebhookRepository};
+    use crate::infrastructure::schema::MIGRATOR;
+    use crate::infrastructure::sqlite_webhooks::SqliteWebhookRepository;
+
+    async fn repository() -> SqliteWebhookRepository {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        SqliteWebhookRepository::new(pool)
+    }
+
+    fn webhook(url: &str, events: Vec<ChangeKind>) -> NewWebhook {
+        NewWebhook { url: url.to_string(), events, secret: "secret".to_string() }
+    }
+
+    #[tokio::test]
+    async fn create_get_list_and_delete() {
+        // Проверяем, что подписка сохраняется со всеми полями и удаляется
+        let repository = repository().await;
+        let first = repository.create(webhook("https://chat.example/hook", vec![ChangeKind::Created, ChangeKind::Deleted])).await.unwrap();
+        let second = repository.create(webhook("https://ci.example/hook", Vec::new())).await.unwrap();
+
+        let stored = repository.get(first.id).await.unwrap().unwrap();
+        assert_eq!(stored.events, vec![ChangeKind::Created, ChangeKind::Deleted]);
+        assert_eq!((stored.url.as_str(), stored.secret.as_str()), ("https://chat.example/hook", "secret"));
+        assert_eq!(repository.list().await.unwrap().iter().map(|webhook| webhook.id).collect::<Vec<_>>(), vec![first.id, second.id]);
+
+        assert!(repository.delete(first.id).await.unwrap());
+        assert!(!repository.delete(first.id).await.unwrap());
+        assert_eq!(repository.get(first.id).await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn delivery_log_is_kept_per_webhook() {
+        // Проверяем запись попыток доставки и их удаление вместе с подпиской
+        let repository = repository().await;
+        let webhook = repository.create(webhook("https://ci.example/hook", Vec::new())).await.unwrap();
+        let attempt = DeliveryAttempt {
+            webhook_id: webhook.id,
+            delivery: "d1".to_string(),
+            event_id: 7,
+            event: Some(ChangeKind::Toggled),
+            attempt: 1,
+            attempted_at: Utc::now(),
+            status_code: Some(500),
+            error: Some("500 Internal Server Error".to_string()),
+            succeeded: false,
+        };
+        repository.record(&attempt).await.unwrap();
+        let retry = DeliveryAttempt { attempt: 2, status_code: Some(204), error: None, succeeded: true, ..attempt.clone() };
+        repository.record(&retry).await.unwrap();
+
+        assert_eq!(repository.deliveries(webhook.id).await.unwrap(), vec![attempt.clone(), retry]);
+        repository.delete(webhook.id).await.unwrap();
+        assert!(repository.deliveries(webhook.id).await.unwrap().is_empty());
+        // Попытка для удаленной подписки не записывается
+        assert!(repository.record(&attempt).await.is_err());
+    }
+}
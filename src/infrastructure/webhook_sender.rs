@@ -0,0 +1,197 @@
+use std::future::Future;
+use std::net::{IpAddr, SocketAddr};
+use std::pin::Pin;
+use std::time::Duration;
+
+use actix_tls::connect::{Connector as TcpConnector, Resolve, Resolver};
+use actix_web::http::header::CONTENT_TYPE;
+
+use crate::application::webhooks::{WebhookSender, host_of, is_private_address};
+
+// Сколько ждать ответа получателя, прежде чем считать попытку неудачной
+const SEND_TIMEOUT: Duration = Duration::from_secs(10);
+
+// Отправляет уведомления POST-запросом с JSON-телом через клиент actix.
+// Перенаправления не выполняются: иначе внешний получатель мог бы переслать запрос
+// на внутренний адрес, минуя проверку.
+pub struct HttpWebhookSender {
+    client: awc::Client,
+    allow_private_hosts: bool, // Можно ли слать уведомления на адреса этого компьютера и локальной сети
+}
+
+impl HttpWebhookSender {
+    pub fn new(allow_private_hosts: bool) -> HttpWebhookSender {
+        let builder = awc::Client::builder().timeout(SEND_TIMEOUT).disable_redirects();
+        let client = if allow_private_hosts {
+            builder.finish()
+        } else {
+            let resolver = TcpConnector::new(Resolver::custom(PublicResolver)).service();
+            builder.connector(awc::Connector::new().connector(resolver)).finish()
+        };
+        HttpWebhookSender { client, allow_private_hosts }
+    }
+}
+
+// Разрешает имена узлов для соединений клиента и отказывает, если среди адресов есть внутренний.
+// Соединение устанавливается именно с проверенными адресами, поэтому смена записи DNS
+// между проверкой и запросом (DNS rebinding) не помогает обойти запрет.
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn lookup<'a>(&'a self, host: &'a str, port: u16) -> Pin<Box<dyn Future<Output = Result<Vec<SocketAddr>, Box<dyn std::error::Error>>> + 'a>> {
+        Box::pin(async move {
+            let addresses: Vec<SocketAddr> = tokio::net::lookup_host((host, port)).await?.collect();
+            match addresses.iter().find(|address| is_private_address(address.ip())) {
+                Some(address) => Err(format!("{} resolves to the private address {}", host, address.ip()).into()),
+                None => Ok(addresses),
+            }
+        })
+    }
+}
+
+#[async_trait::async_trait(?Send)]
+impl WebhookSender for HttpWebhookSender {
+    async fn send(&self, url: &str, headers: &[(&'static str, String)], body: String) -> Result<u16, String> {
+        // IP-адрес в URL соединитель не разрешает, поэтому он проверяется здесь
+        let literal = host_of(url).and_then(|host| host.parse::<IpAddr>().ok());
+        if let Some(ip) = literal.filter(|ip| !self.allow_private_hosts && is_private_address(*ip)) {
+            return Err(format!("{} is a private address", ip));
+        }
+        let mut request = self.client.post(url).insert_header((CONTENT_TYPE, "application/json"));
+        for (name, value) in headers {
+            request = request.insert_header((*name, value.as_str()));
+        }
+        match request.send_body(body).await {
+            Ok(response) => Ok(response.status().as_u16()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+}
+
+// Проверяем доставку уведомлений настоящему HTTP-серверу
+#[cfg(test)]
+mod webhook_sender_tests {
+    use std::rc::Rc;
+    use std::sync::{Arc, Mutex};
+    use std::time::Duration;
+
+    use actix_web::{App, HttpRequest, HttpResponse, HttpServer, web};
+    use sqlx::SqlitePool;
+
+    use crate::application::webhooks::{
+        DELIVERY_HEADER, EVENT_HEADER, RetryPolicy, SIGNATURE_HEADER, TIMESTAMP_HEADER, WebhookDispatcher, WebhookSender, sign,
+    };
+    use crate::domain::events::ChangeKind;
+    use crate::domain::webhooks::{NewWebhook, WebhookRepository};
+    use crate::infrastructure::schema::MIGRATOR;
+    use crate::infrastructure::sqlite_webhooks::SqliteWebhookRepository;
+    use crate::infrastructure::webhook_sender::HttpWebhookSender;
+
+    // Запрос, полученный подставным сервером: заголовки уведомления и тело
+    type Received = Mutex<Vec<(Vec<String>, String)>>;
+
+    // Подставной получатель: первую попытку отклоняет, следующие принимает
+    async fn receiver(req: HttpRequest, body: String, received: web::Data<Received>) -> HttpResponse {
+        let headers = [EVENT_HEADER, DELIVERY_HEADER, TIMESTAMP_HEADER, SIGNATURE_HEADER]
+            .iter()
+            .map(|name| req.headers().get(*name).and_then(|value| value.to_str().ok()).unwrap_or_default().to_string())
+            .collect();
+        let mut received = received.lock().unwrap();
+        received.push((headers, body));
+        if received.len() == 1 { HttpResponse::ServiceUnavailable().finish() } else { HttpResponse::NoContent().finish() }
+    }
+
+    #[actix_web::test]
+    async fn signed_delivery_is_retried_until_accepted() {
+        // Проверяем подпись уведомления, повтор после ответа 503 и журнал обеих попыток
+        let received = web::Data::new(Received::default());
+        let data = received.clone();
+        let server = HttpServer::new(move || App::new().app_data(data.clone()).route("/hook", web::post().to(receiver)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let address = server.addrs()[0];
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        let repository = Arc::new(SqliteWebhookRepository::new(pool));
+        let url = format!("http://{}/hook", address);
+        let webhook = repository.create(NewWebhook { url, events: Vec::new(), secret: "s3cr3t".to_string() }).await.unwrap();
+        let policy = RetryPolicy { attempts: 3, initial_delay: Duration::from_millis(10), max_delay: Duration::from_millis(10) };
+        let dispatcher = WebhookDispatcher::new(repository.clone(), Rc::new(HttpWebhookSender::new(true)), policy);
+
+        let body = r#"{"id":5,"type":"toggled"}"#.to_string();
+        assert!(dispatcher.deliver(webhook.clone(), 5, ChangeKind::Toggled, body.clone()).await);
+        handle.stop(true).await;
+
+        let received = std::mem::take(&mut *received.lock().unwrap());
+        assert_eq!(received.len(), 2);
+        for (headers, received_body) in received.iter() {
+            let timestamp: i64 = headers[2].parse().unwrap();
+            assert_eq!(headers[0], "toggled");
+            assert_eq!(headers[3], format!("sha256={}", sign("s3cr3t", timestamp, &body)));
+            assert_eq!(received_body, &body);
+        }
+        // Повтор приходит с тем же идентификатором доставки
+        assert_eq!(received[0].0[1], received[1].0[1]);
+
+        let log = repository.deliveries(webhook.id).await.unwrap();
+        let outcomes: Vec<_> = log.iter().map(|attempt| (attempt.attempt, attempt.status_code, attempt.succeeded)).collect();
+        assert_eq!(outcomes, vec![(1, Some(503), false), (2, Some(204), true)]);
+        assert_eq!(log[0].delivery, received[0].0[1]);
+    }
+
+    #[actix_web::test]
+    async fn private_addresses_are_refused_before_sending() {
+        // Проверяем, что внутренний адрес в URL и имя, разрешающееся во внутренний адрес, отклоняются без запроса
+        let sender = HttpWebhookSender::new(false);
+        let error = sender.send("http://localhost:9/hook", &[], String::new()).await.unwrap_err();
+        assert!(error.contains("localhost resolves to the private address"), "{}", error);
+        let error = sender.send("http://[::ffff:127.0.0.1]:9/hook", &[], String::new()).await.unwrap_err();
+        assert_eq!(error, "::ffff:127.0.0.1 is a private address");
+    }
+
+    // Подставной получатель, который перенаправляет уведомление на внутренний адрес
+    async fn redirect(internal: web::Data<String>) -> HttpResponse {
+        HttpResponse::TemporaryRedirect().insert_header(("location", internal.as_str())).finish()
+    }
+
+    async fn internal(hits: web::Data<Mutex<usize>>) -> HttpResponse {
+        *hits.lock().unwrap() += 1;
+        HttpResponse::Ok().finish()
+    }
+
+    #[actix_web::test]
+    async fn redirects_are_not_followed() {
+        // Проверяем, что ответ 307 со ссылкой на внутренний адрес считается ответом получателя,
+        // а перенаправление не выполняется. Подставной получатель сам слушает loopback,
+        // поэтому отправитель создается с разрешением внутренних адресов.
+        let hits = web::Data::new(Mutex::new(0));
+        let data = hits.clone();
+        let server = HttpServer::new(move || App::new().app_data(data.clone()).route("/internal", web::post().to(internal)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let internal_url = web::Data::new(format!("http://{}/internal", server.addrs()[0]));
+        let server = server.run();
+        let internal_handle = server.handle();
+        actix_web::rt::spawn(server);
+        let server = HttpServer::new(move || App::new().app_data(internal_url.clone()).route("/hook", web::post().to(redirect)))
+            .workers(1)
+            .bind(("127.0.0.1", 0))
+            .unwrap();
+        let url = format!("http://{}/hook", server.addrs()[0]);
+        let server = server.run();
+        let handle = server.handle();
+        actix_web::rt::spawn(server);
+
+        let status = HttpWebhookSender::new(true).send(&url, &[], String::new()).await;
+        handle.stop(true).await;
+        internal_handle.stop(true).await;
+        assert_eq!(status, Ok(307));
+        assert_eq!(*hits.lock().unwrap(), 0);
+    }
+}
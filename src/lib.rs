@@ -0,0 +1,6 @@
+// Слои приложения. Бинарник собирает из них сервер и команды обслуживания,
+// бенчмарки подключают их напрямую.
+pub mod application;
+pub mod domain;
+pub mod infrastructure;
+pub mod presentation;
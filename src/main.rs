@@ -1,68 +1,577 @@
-mod application;
-mod domain;
-mod infrastructure;
-mod presentation;
-
-use std::sync::Mutex;
+use std::num::NonZeroUsize;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+use std::sync::Arc;
+use std::time::Duration;
 
 use actix_web::{App, HttpServer, web};
 use clap::Parser;
-use sqlx::SqlitePool;
+use sqlx::{SqlitePool, sqlite::SqlitePoolOptions};
+use tokio::sync::Mutex;
 
-use crate::{
-    application::services::TaskService,
-    infrastructure::{in_memory::InMemoryTaskRepository, sqlite::SqliteTaskRepository},
-    presentation::web_controller,
+use clean_todo_demo::{
+    application::{
+        export::{self, ExportFormat},
+        feed::FeedItem,
+        import::{self, ConflictMode, ImportFormat, RowStatus},
+        services::TaskService,
+        validation::{DEFAULT_MAX_DESCRIPTION_LENGTH, DEFAULT_MAX_TITLE_LENGTH, ValidationPolicy},
+        webhooks::{RetryPolicy, WebhookDispatcher, WebhookService},
+    },
+    domain::{queries::TaskFilter, repositories::TaskRepository},
+    infrastructure::{
+        caching::CachingTaskRepository,
+        chaos::{ChaosConfig, ChaosOperation, ChaosTaskRepository},
+        event_sourced::EventSourcedTaskRepository,
+        in_memory::InMemoryTaskRepository,
+        maintenance,
+        schema::{self, ConnectionSettings, JournalMode, SchemaError, Synchronous},
+        sqlite::SqliteTaskRepository,
+        sqlite_webhooks::SqliteWebhookRepository,
+        webhook_sender::HttpWebhookSender,
+    },
+    presentation::{caldav, feed, idempotency::{self, IdempotencyStore}, openapi, web_controller},
 };
 
 #[derive(clap::Parser)]
 struct Cli {
     #[command(subcommand)]
-    repository: Repository,
+    command: Command,
+    // Включает кэш чтения задач указанной емкости
+    #[arg(long, global = true)]
+    cache_capacity: Option<NonZeroUsize>,
+    // Сколько секунд хранятся ответы на запросы с Idempotency-Key
+    #[arg(long, global = true, default_value_t = idempotency::DEFAULT_WINDOW.as_secs())]
+    idempotency_window_secs: u64,
+    #[command(flatten)]
+    chaos: ChaosArgs,
+    #[command(flatten)]
+    sqlite: SqliteArgs,
+    #[command(flatten)]
+    validation: ValidationArgs,
+    #[command(flatten)]
+    webhooks: WebhookArgs,
+}
+
+// Настройки соединений с SQLite
+#[derive(clap::Args)]
+struct SqliteArgs {
+    // Режим журнала
+    #[arg(long, global = true, value_enum, default_value = "wal")]
+    sqlite_journal_mode: JournalMode,
+    // Уровень синхронизации с диском
+    #[arg(long, global = true, value_enum, default_value = "normal")]
+    sqlite_synchronous: Synchronous,
+    // Ожидание освобождения блокировки, мс
+    #[arg(long, global = true, default_value_t = 5000)]
+    sqlite_busy_timeout_ms: u64,
+    // Размер пула соединений
+    #[arg(long, global = true, default_value_t = 10)]
+    sqlite_max_connections: u32,
+    // Емкость кэша подготовленных запросов на соединение
+    #[arg(long, global = true, default_value_t = 100)]
+    sqlite_statement_cache: usize,
+}
+
+impl SqliteArgs {
+    fn settings(&self) -> ConnectionSettings {
+        ConnectionSettings {
+            journal_mode: self.sqlite_journal_mode,
+            synchronous: self.sqlite_synchronous,
+            busy_timeout: Duration::from_millis(self.sqlite_busy_timeout_ms),
+            max_connections: self.sqlite_max_connections,
+            statement_cache_capacity: self.sqlite_statement_cache,
+        }
+    }
+}
+
+// Правила проверки полей задачи; задаются флагами или переменными окружения (в том числе из .env)
+#[derive(clap::Args)]
+struct ValidationArgs {
+    // Сохранять пробельные символы по краям названия и описания
+    #[arg(long, global = true, env = "TASK_NO_TRIM")]
+    no_trim: bool,
+    // Наибольшая длина названия в символах
+    #[arg(long, global = true, env = "TASK_MAX_TITLE_LENGTH", default_value_t = DEFAULT_MAX_TITLE_LENGTH)]
+    max_title_length: usize,
+    // Наибольшая длина описания в символах
+    #[arg(long, global = true, env = "TASK_MAX_DESCRIPTION_LENGTH", default_value_t = DEFAULT_MAX_DESCRIPTION_LENGTH)]
+    max_description_length: usize,
+    // Символы, недопустимые в названии и описании, одной строкой
+    #[arg(long, global = true, env = "TASK_FORBIDDEN_CHARACTERS", default_value = "")]
+    forbidden_characters: String,
+    // Разрешить задачи без описания
+    #[arg(long, global = true, env = "TASK_OPTIONAL_DESCRIPTION")]
+    optional_description: bool,
+}
+
+impl ValidationArgs {
+    fn policy(&self) -> ValidationPolicy {
+        ValidationPolicy {
+            trim: !self.no_trim,
+            max_title_length: self.max_title_length,
+            max_description_length: self.max_description_length,
+            forbidden_characters: self.forbidden_characters.chars().collect(),
+            require_description: !self.optional_description,
+        }
+    }
+}
+
+// Повторы доставки уведомлений подписчикам
+#[derive(clap::Args)]
+struct WebhookArgs {
+    // Сколько всего попыток доставить уведомление
+    #[arg(long, global = true, default_value_t = RetryPolicy::default().attempts)]
+    webhook_attempts: u32,
+    // Пауза перед первым повтором, мс; каждая следующая вдвое длиннее
+    #[arg(long, global = true, default_value_t = RetryPolicy::default().initial_delay.as_millis() as u64)]
+    webhook_initial_delay_ms: u64,
+    // Предел паузы между попытками, мс
+    #[arg(long, global = true, default_value_t = RetryPolicy::default().max_delay.as_millis() as u64)]
+    webhook_max_delay_ms: u64,
+    // Разрешает уведомления на адреса этого компьютера и локальной сети
+    #[arg(long, global = true)]
+    webhook_allow_private_hosts: bool,
+}
+
+impl WebhookArgs {
+    fn policy(&self) -> RetryPolicy {
+        RetryPolicy {
+            attempts: self.webhook_attempts,
+            initial_delay: Duration::from_millis(self.webhook_initial_delay_ms),
+            max_delay: Duration::from_millis(self.webhook_max_delay_ms),
+        }
+    }
+}
+
+// Внедрение сбоев в хранилище для проверки устойчивости
+#[derive(clap::Args)]
+struct ChaosArgs {
+    // Включает внедрение сбоев с указанным зерном генератора
+    #[arg(long, global = true)]
+    chaos_seed: Option<u64>,
+    // Максимальная случайная задержка операции, мс
+    #[arg(long, global = true, default_value_t = 0)]
+    chaos_latency_ms: u64,
+    // Доля операций, завершающихся ошибкой
+    #[arg(long, global = true, default_value_t = 0.0)]
+    chaos_failure_rate: f64,
+    // Доля операций, завершающихся тайм-аутом
+    #[arg(long, global = true, default_value_t = 0.0)]
+    chaos_timeout_rate: f64,
+    // Время до тайм-аута, мс
+    #[arg(long, global = true, default_value_t = 1000)]
+    chaos_timeout_ms: u64,
+    // Затрагиваемые операции через запятую; по умолчанию все
+    #[arg(long, global = true, value_delimiter = ',')]
+    chaos_ops: Vec<ChaosOperation>,
 }
 
 #[derive(clap::Subcommand)]
-enum Repository {
+enum Command {
     InMemory,
-    Sqlite,
+    Sqlite {
+        // Не применять миграции при запуске
+        #[arg(long)]
+        no_migrate: bool,
+    },
+    EventSourced {
+        // Файл журнала событий; без него журнал хранится только в памяти
+        #[arg(long)]
+        log: Option<PathBuf>,
+    },
+    // Управление схемой базы DATABASE_URL без запуска сервера
+    Migrate {
+        #[command(subcommand)]
+        action: MigrateAction,
+    },
+    // Сохранить копию базы DATABASE_URL в файл, не останавливая работу с ней
+    Backup {
+        path: PathBuf,
+    },
+    // Заменить содержимое базы DATABASE_URL резервной копией
+    Restore {
+        path: PathBuf,
+    },
+    // Проверить целостность базы DATABASE_URL
+    Check,
+    // Выгрузить задачи из базы DATABASE_URL или журнала событий
+    Export {
+        #[arg(long, value_enum)]
+        format: ExportFormat,
+        // Файл для выгрузки; по умолчанию стандартный вывод
+        #[arg(long)]
+        output: Option<PathBuf>,
+        // Читать задачи из журнала событий вместо базы
+        #[arg(long)]
+        log: Option<PathBuf>,
+        // Отбор по статусу
+        #[arg(long)]
+        status: Option<bool>,
+        // Отбор по метке
+        #[arg(long)]
+        tag: Option<String>,
+        // Отбор по шаблону названия
+        #[arg(long)]
+        title: Option<String>,
+    },
+    // Загрузить задачи из файла в базу DATABASE_URL или журнал событий
+    Import {
+        path: PathBuf,
+        #[arg(long, value_enum)]
+        format: ImportFormat,
+        // Что делать с задачами, ID которых уже занят
+        #[arg(long, value_enum, default_value = "skip")]
+        conflict: ConflictMode,
+        // Только проверить записи, ничего не загружая
+        #[arg(long)]
+        dry_run: bool,
+        // Загружать в журнал событий вместо базы
+        #[arg(long)]
+        log: Option<PathBuf>,
+    },
+}
+
+#[derive(clap::Subcommand)]
+enum MigrateAction {
+    // Показать примененные и ожидающие миграции
+    Status,
+    // Применить все ожидающие миграции
+    Up,
+    // Откатить последнюю миграцию или все миграции новее указанной версии
+    Down {
+        #[arg(long)]
+        target: Option<i64>,
+    },
+}
+
+// Подключается к DATABASE_URL и проверяет, что схема базы не новее бинарника.
+// Завершает процесс, если работать с базой нельзя.
+async fn open_database(settings: &ConnectionSettings) -> SqlitePool {
+    let url = std::env::var("DATABASE_URL").unwrap_or_default();
+    let pool = match schema::connect(&url, settings).await {
+        Ok(pool) => pool,
+        Err(e) => {
+            eprintln!("Error connecting to {:?}: {}", url, e);
+            std::process::exit(1);
+        }
+    };
+    match schema::check_version(&pool).await {
+        Ok(()) => pool,
+        Err(e @ SchemaError::NewerThanBinary { .. }) => {
+            eprintln!("Refusing to start: {}", e);
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error checking database schema: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+async fn migrate(action: MigrateAction, settings: &ConnectionSettings) {
+    let pool = open_database(settings).await;
+    let result = match action {
+        MigrateAction::Status => schema::status(&pool).await.map(|migrations| {
+            for migration in migrations {
+                let state = if migration.applied { "applied" } else { "pending" };
+                println!("{} {:<8} {}", migration.version, state, migration.description);
+            }
+        }),
+        MigrateAction::Up => schema::up(&pool).await.map(|_| {
+            println!("Schema is at version {}", schema::latest_version());
+        }),
+        MigrateAction::Down { target } => schema::down(&pool, target).await.map(|version| {
+            println!("Schema is at version {}", version);
+        }),
+    };
+    if let Err(e) = result {
+        eprintln!("Migration failed: {}", e);
+        std::process::exit(1);
+    }
+}
+
+// Открывает журнал событий из файла. Завершает процесс, если файл не читается или поврежден.
+fn open_log(path: &Path) -> EventSourcedTaskRepository {
+    match EventSourcedTaskRepository::open(path) {
+        Ok(repository) => repository,
+        Err(e) => {
+            eprintln!("Error opening event log {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    }
+}
+
+// Открывает постоянное хранилище для команд выгрузки и загрузки:
+// журнал событий, если он указан, иначе базу DATABASE_URL. Для чтения (`read_only`)
+// хранилище не меняется: журнал должен существовать, а схема базы - быть актуальной.
+async fn open_repository(log: Option<PathBuf>, settings: &ConnectionSettings, read_only: bool) -> Box<dyn TaskRepository> {
+    match log {
+        Some(path) if read_only && !path.exists() => {
+            eprintln!("Event log {} does not exist", path.display());
+            std::process::exit(1);
+        }
+        Some(path) => Box::new(open_log(&path)),
+        None => {
+            let pool = open_database(settings).await;
+            let result = if read_only {
+                schema::status(&pool).await.map(|migrations| migrations.iter().filter(|migration| !migration.applied).count())
+            } else {
+                schema::up(&pool).await.map(|_| 0)
+            };
+            match result {
+                Ok(0) => {}
+                Ok(pending) => {
+                    eprintln!("Database schema is outdated: {} migrations are not applied, run `migrate up` first", pending);
+                    std::process::exit(1);
+                }
+                Err(e) => {
+                    eprintln!("Migration failed: {}", e);
+                    std::process::exit(1);
+                }
+            }
+            Box::new(SqliteTaskRepository::new(pool).await)
+        }
+    }
+}
+
+async fn import_tasks(mut task_service: TaskService, path: &Path, format: ImportFormat, conflict: ConflictMode, dry_run: bool) {
+    let records = match std::fs::read(path).map_err(|e| e.to_string()).and_then(|input| import::parse(format, &input)) {
+        Ok(records) => records,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", path.display(), e);
+            std::process::exit(1);
+        }
+    };
+    let report = match task_service.import("cli", records, conflict, dry_run).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Import failed: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+    for row in &report.rows {
+        match (&row.id, &row.error, &row.reason) {
+            (_, Some(e), _) => println!("row {}: {:?} ({:?})", row.row, row.status, e),
+            (_, None, Some(reason)) => println!("row {}: {:?} ({})", row.row, row.status, reason),
+            (Some(id), None, None) => println!("row {}: {:?} task {}", row.row, row.status, id),
+            (None, None, None) => println!("row {}: {:?}", row.row, row.status),
+        }
+        if !row.unmapped.is_empty() {
+            println!("row {}: unmapped fields: {}", row.row, row.unmapped.join(", "));
+        }
+    }
+    println!(
+        "{}created: {}, updated: {}, skipped: {}, rejected: {}{}",
+        if dry_run { "dry run, " } else { "" },
+        report.count(RowStatus::Created),
+        report.count(RowStatus::Updated),
+        report.count(RowStatus::Skipped),
+        report.count(RowStatus::Rejected),
+        if report.aborted { ", aborted because of conflicts" } else { "" },
+    );
+    let unmapped = report.unmapped();
+    if !unmapped.is_empty() {
+        let fields: Vec<String> = unmapped.iter().map(|(field, rows)| format!("{} ({})", field, rows)).collect();
+        println!("unmapped fields: {}", fields.join(", "));
+    }
+    if report.aborted || report.count(RowStatus::Rejected) > 0 {
+        std::process::exit(1);
+    }
+}
+
+async fn export_tasks(task_service: TaskService, format: ExportFormat, output: Option<PathBuf>, filter: TaskFilter) {
+    let tasks = match task_service.find(&filter).await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error reading tasks: {:?}", e);
+            std::process::exit(1);
+        }
+    };
+    let result = match &output {
+        Some(path) => std::fs::File::create(path).and_then(|file| export::export(&tasks, format, std::io::BufWriter::new(file))),
+        None => export::export(&tasks, format, std::io::stdout().lock()),
+    };
+    match result {
+        Ok(()) => {
+            if let Some(path) = output {
+                eprintln!("Exported {} tasks to {}", tasks.len(), path.display());
+            }
+        }
+        Err(e) => {
+            eprintln!("Export failed: {}", e);
+            std::process::exit(1);
+        }
+    }
 }
 
 #[actix_web::main]
 async fn main() {
     dotenv::dotenv().ok();
     let cli = Cli::parse();
+    let settings = cli.sqlite.settings();
+    let policy = cli.validation.policy();
+    // Подписки хранятся в той же базе, что и задачи; без базы - в памяти до остановки сервера
+    let mut webhook_pool = None;
 
-    let task_service = match cli.repository {
-            Repository::InMemory => {
+    let repository: Box<dyn TaskRepository> = match cli.command {
+            Command::InMemory => {
                 println!("Using in-memory repository");
-                TaskService::new(Box::new(InMemoryTaskRepository::new()))
+                Box::new(InMemoryTaskRepository::new())
             }
-            Repository::Sqlite => {
+            Command::Sqlite { no_migrate } => {
                 println!("Using sqlite repository");
-                TaskService::new(Box::new(
-                    SqliteTaskRepository::new(
-                        SqlitePool::connect(&std::env::var("DATABASE_URL").unwrap())
-                            .await
-                            .unwrap(),
-                    )
-                    .await,
-                ))
+                let pool = open_database(&settings).await;
+                if no_migrate {
+                    match schema::status(&pool).await {
+                        Ok(migrations) => {
+                            let pending = migrations.iter().filter(|migration| !migration.applied).count();
+                            if pending > 0 {
+                                eprintln!("Warning: {} migrations are not applied", pending);
+                            }
+                        }
+                        Err(e) => {
+                            eprintln!("Error checking database schema: {}", e);
+                            std::process::exit(1);
+                        }
+                    }
+                } else if let Err(e) = schema::up(&pool).await {
+                    eprintln!("Migration failed: {}", e);
+                    std::process::exit(1);
+                }
+                webhook_pool = Some(pool.clone());
+                Box::new(SqliteTaskRepository::new(pool).await)
+            }
+            Command::EventSourced { log } => {
+                println!("Using event-sourced repository");
+                match log {
+                    Some(path) => Box::new(open_log(&path)),
+                    None => Box::new(EventSourcedTaskRepository::new()),
+                }
+            }
+            Command::Migrate { action } => {
+                migrate(action, &settings).await;
+                return;
+            }
+            Command::Backup { path } => {
+                let pool = open_database(&settings).await;
+                match maintenance::backup(&pool, &path).await {
+                    Ok(()) => println!("Backup saved to {}", path.display()),
+                    Err(e) => {
+                        eprintln!("Backup failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+            Command::Restore { path } => {
+                let pool = open_database(&settings).await;
+                match maintenance::restore(&pool, &path).await {
+                    Ok(version) => println!("Restored backup with schema version {}", version),
+                    Err(e) => {
+                        eprintln!("Restore failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
+            }
+            Command::Export { format, output, log, status, tag, title } => {
+                let repository = open_repository(log, &settings, true).await;
+                let filter = TaskFilter { status, tag, title };
+                export_tasks(TaskService::new(repository), format, output, filter).await;
+                return;
+            }
+            Command::Import { path, format, conflict, dry_run, log } => {
+                let repository = open_repository(log, &settings, false).await;
+                import_tasks(TaskService::new(repository).with_validation_policy(policy), &path, format, conflict, dry_run).await;
+                return;
+            }
+            Command::Check => {
+                let pool = open_database(&settings).await;
+                match maintenance::check_integrity(&pool, &policy).await {
+                    Ok(problems) if problems.is_empty() => println!("No problems found"),
+                    Ok(problems) => {
+                        for problem in &problems {
+                            println!("{}", problem);
+                        }
+                        eprintln!("Found {} problems", problems.len());
+                        std::process::exit(1);
+                    }
+                    Err(e) => {
+                        eprintln!("Integrity check failed: {}", e);
+                        std::process::exit(1);
+                    }
+                }
+                return;
             }
     };
+    let repository: Box<dyn TaskRepository> = match cli.chaos.chaos_seed {
+        Some(seed) => {
+            println!("Injecting storage faults with seed {}", seed);
+            let config = ChaosConfig {
+                seed,
+                max_latency: Duration::from_millis(cli.chaos.chaos_latency_ms),
+                failure_rate: cli.chaos.chaos_failure_rate,
+                timeout_rate: cli.chaos.chaos_timeout_rate,
+                timeout: Duration::from_millis(cli.chaos.chaos_timeout_ms),
+                operations: cli.chaos.chaos_ops,
+            };
+            Box::new(ChaosTaskRepository::new(repository, config))
+        }
+        None => repository,
+    };
+    let (task_service, cache_stats) = match cli.cache_capacity {
+        Some(capacity) => {
+            println!("Caching up to {} tasks", capacity);
+            let repository = CachingTaskRepository::new(repository, capacity);
+            let stats = repository.stats();
+            (TaskService::new(Box::new(repository)), Some(stats))
+        }
+        None => (TaskService::new(repository), None),
+    };
+    let task_service = task_service.with_validation_policy(policy);
+
+    let webhook_pool = match webhook_pool {
+        Some(pool) => pool,
+        None => {
+            // Одно соединение без ограничения простоя: с его закрытием база в памяти пропадет
+            let pool = SqlitePoolOptions::new().max_connections(1).idle_timeout(None).max_lifetime(None);
+            let pool = pool.connect("sqlite::memory:").await.unwrap();
+            schema::up(&pool).await.unwrap();
+            pool
+        }
+    };
+    let webhook_repository = Arc::new(SqliteWebhookRepository::new(webhook_pool));
+    let allow_private_hosts = cli.webhooks.webhook_allow_private_hosts;
+    let sender = Rc::new(HttpWebhookSender::new(allow_private_hosts));
+    let dispatcher = WebhookDispatcher::new(webhook_repository.clone(), sender, cli.webhooks.policy());
+    let change_feed = task_service.feed();
+    let subscription = change_feed.subscribe(None, Default::default());
+    actix_web::rt::spawn(dispatcher.run(subscription, |event| feed::message(FeedItem::Event(event))));
+    let webhook_service = web::Data::new(WebhookService::new(webhook_repository).with_private_hosts(allow_private_hosts));
+
     let task_service = web::Data::new(Mutex::new(task_service));
+    // Подписчики SSE и WebSocket берут ленту напрямую, не блокируя сервис задач
+    let change_feed = web::Data::new(change_feed);
+    let idempotency_store = web::Data::new(IdempotencyStore::new(Duration::from_secs(cli.idempotency_window_secs)));
 
     HttpServer::new(move || {
         App::new()
-            .service(web_controller::get_all_tasks)
-            .service(web_controller::get_task_by_id)
-            .service(web_controller::create_task)
-            .service(web_controller::toggle_task)
-            .service(web_controller::delete_task)
+            .configure(web_controller::configure)
+            .configure(openapi::configure)
+            .configure(caldav::configure)
             .app_data(task_service.clone())
+            .app_data(change_feed.clone())
+            .app_data(idempotency_store.clone())
+            .app_data(webhook_service.clone())
     })
     .bind(("127.0.0.1", 8080))
     .unwrap()
     .run()
     .await
     .unwrap();
+
+    if let Some(stats) = cache_stats {
+        println!("Cache hits: {}, misses: {}", stats.hits(), stats.misses());
+    }
 }
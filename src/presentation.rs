@@ -1,2 +1,9 @@
+pub mod caldav;
+pub mod openapi;
 pub mod web_controller;
-pub mod dto;
\ No newline at end of file
+pub mod dto;
+pub mod feed;
+pub mod idempotency;
+pub mod negotiation;
+pub mod session;
+pub mod webhooks;
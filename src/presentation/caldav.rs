@@ -0,0 +1,661 @@
+pub mod xml;
+
+use actix_web::{HttpRequest, HttpResponse, http::{Method, StatusCode, header}, web};
+use chrono::{DateTime, Utc};
+use sha2::{Digest, Sha256};
+use tokio::sync::Mutex;
+
+use crate::application::icalendar;
+use crate::application::import::{ConflictMode, RecordError, RowStatus};
+use crate::application::services::{TaskService, TaskServiceError};
+use crate::domain::entities::{Task, TaskId};
+use crate::presentation::caldav::xml::{CALDAV, CALENDAR_SERVER, DAV, DavRequest, Multistatus, Name};
+use crate::presentation::session::SessionId;
+
+// CalDAV (RFC 4791) поверх TaskService: все задачи образуют один календарь /dav/tasks/,
+// каждая задача - ресурс с одним VTODO. Корень /dav/ служит и принципалом, и домашним
+// каталогом календарей, авторизации нет.
+
+const ROOT: &str = "/dav/";
+const COLLECTION: &str = "/dav/tasks/";
+// Расширение задачи с именем ресурса, под которым ее создал клиент;
+// без него ресурс называется `<id>.ics`
+const RESOURCE_NAME: &str = "caldav-name";
+const CALENDAR_CONTENT_TYPE: &str = "text/calendar; charset=utf-8; component=VTODO";
+
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    let propfind = Method::from_bytes(b"PROPFIND").unwrap();
+    let report = Method::from_bytes(b"REPORT").unwrap();
+    cfg.route("/.well-known/caldav", web::to(well_known))
+        .service(
+            web::resource(["/dav", ROOT])
+                .route(web::method(Method::OPTIONS).to(options))
+                .route(web::method(propfind.clone()).to(propfind_root)),
+        )
+        .service(
+            web::resource(["/dav/tasks", COLLECTION])
+                .route(web::method(Method::OPTIONS).to(options))
+                .route(web::method(propfind.clone()).to(propfind_collection))
+                .route(web::method(report).to(report_collection)),
+        )
+        .service(
+            web::resource("/dav/tasks/{name}")
+                .route(web::method(Method::OPTIONS).to(options))
+                .route(web::method(propfind).to(propfind_task))
+                .route(web::get().to(get_task))
+                .route(web::put().to(put_task))
+                .route(web::delete().to(delete_task)),
+        );
+}
+
+// Ресурс CalDAV и его представление для ответа
+enum Resource<'a> {
+    Root,
+    Collection { ctag: String },
+    Task { task: &'a Task, data: String, etag: String },
+}
+
+impl Resource<'_> {
+    fn task(task: &Task) -> Resource<'_> {
+        let data = calendar_data(task);
+        let etag = etag(&data);
+        Resource::Task { task, data, etag }
+    }
+
+    fn href(&self) -> String {
+        match self {
+            Resource::Root => ROOT.to_string(),
+            Resource::Collection { .. } => COLLECTION.to_string(),
+            Resource::Task { task, .. } => format!("{}{}", COLLECTION, encode_segment(&resource_name(task))),
+        }
+    }
+
+    // Свойства, которые отдаются на allprop
+    fn all_props(&self) -> Vec<Name> {
+        let names: &[(&str, &str)] = match self {
+            Resource::Root => &[(DAV, "resourcetype"), (DAV, "displayname"), (DAV, "current-user-principal"), (CALDAV, "calendar-home-set")],
+            Resource::Collection { .. } => &[
+                (DAV, "resourcetype"),
+                (DAV, "displayname"),
+                (DAV, "current-user-privilege-set"),
+                (CALDAV, "supported-calendar-component-set"),
+                (CALENDAR_SERVER, "getctag"),
+            ],
+            Resource::Task { .. } => &[(DAV, "resourcetype"), (DAV, "getetag"), (DAV, "getcontenttype")],
+        };
+        names.iter().map(|(namespace, local)| Name::new(namespace, local)).collect()
+    }
+
+    // Содержимое свойства в виде XML или None, если у ресурса такого свойства нет
+    fn property(&self, name: &Name) -> Option<String> {
+        let principal = format!("<d:href>{}</d:href>", ROOT);
+        match (self, name.namespace.as_str(), name.local.as_str()) {
+            (Resource::Root, DAV, "resourcetype") => Some("<d:collection/><d:principal/>".to_string()),
+            (Resource::Root, DAV, "displayname") => Some("clean-todo-demo".to_string()),
+            (Resource::Root, DAV, "principal-URL") => Some(principal),
+            (Resource::Root, CALDAV, "calendar-home-set") => Some(principal),
+            (Resource::Collection { .. }, DAV, "resourcetype") => Some("<d:collection/><c:calendar/>".to_string()),
+            (Resource::Collection { .. }, DAV, "displayname") => Some("Tasks".to_string()),
+            (Resource::Collection { .. }, DAV, "current-user-privilege-set") => Some(
+                "<d:privilege><d:read/></d:privilege><d:privilege><d:write/></d:privilege><d:privilege><d:write-content/></d:privilege>\
+                 <d:privilege><d:bind/></d:privilege><d:privilege><d:unbind/></d:privilege>"
+                    .to_string(),
+            ),
+            (Resource::Collection { .. }, DAV, "supported-report-set") => Some(
+                "<d:supported-report><d:report><c:calendar-query/></d:report></d:supported-report>\
+                 <d:supported-report><d:report><c:calendar-multiget/></d:report></d:supported-report>"
+                    .to_string(),
+            ),
+            (Resource::Collection { .. }, CALDAV, "supported-calendar-component-set") => Some("<c:comp name=\"VTODO\"/>".to_string()),
+            (Resource::Collection { ctag }, CALENDAR_SERVER, "getctag") => Some(xml::escape(ctag)),
+            (_, DAV, "current-user-principal") => Some(principal),
+            (Resource::Task { .. }, DAV, "resourcetype") => Some(String::new()),
+            (Resource::Task { etag, .. }, DAV, "getetag") => Some(xml::escape(etag)),
+            (Resource::Task { .. }, DAV, "getcontenttype") => Some(CALENDAR_CONTENT_TYPE.to_string()),
+            (Resource::Task { data, .. }, CALDAV, "calendar-data") => Some(xml::escape(data)),
+            _ => None,
+        }
+    }
+
+    // Добавляет ресурс в ответ с запрошенными свойствами; пустой список означает allprop
+    fn describe(&self, multistatus: &mut Multistatus, requested: &[Name]) {
+        let requested = if requested.is_empty() { self.all_props() } else { requested.to_vec() };
+        let mut found = Vec::new();
+        let mut missing = Vec::new();
+        for name in requested {
+            match self.property(&name) {
+                Some(content) => found.push((name, content)),
+                None => missing.push(name),
+            }
+        }
+        multistatus.response(&self.href(), found, missing);
+    }
+}
+
+async fn well_known() -> HttpResponse {
+    HttpResponse::MovedPermanently().insert_header((header::LOCATION, ROOT)).finish()
+}
+
+async fn options() -> HttpResponse {
+    HttpResponse::Ok()
+        .insert_header(("DAV", "1, 3, calendar-access"))
+        .insert_header((header::ALLOW, "OPTIONS, GET, PUT, DELETE, PROPFIND, REPORT"))
+        .finish()
+}
+
+async fn propfind_root(task_service: web::Data<Mutex<TaskService>>, req: HttpRequest, body: web::Bytes) -> HttpResponse {
+    println!("caldav propfind {}", ROOT);
+    let request = match parse_body(&body) {
+        Ok(request) => request,
+        Err(e) => return HttpResponse::from(e),
+    };
+    let mut multistatus = Multistatus::new();
+    Resource::Root.describe(&mut multistatus, &request.props);
+    if depth(&req) > 0 {
+        let tasks = match task_service.lock().await.get_all().await {
+            Ok(tasks) => tasks,
+            Err(e) => {
+                eprintln!("Error getting tasks for CalDAV");
+                return HttpResponse::from(e);
+            }
+        };
+        let resources: Vec<Resource> = tasks.iter().map(Resource::task).collect();
+        Resource::Collection { ctag: ctag(&resources) }.describe(&mut multistatus, &request.props);
+    }
+    multi_status(multistatus)
+}
+
+async fn propfind_collection(task_service: web::Data<Mutex<TaskService>>, req: HttpRequest, body: web::Bytes) -> HttpResponse {
+    println!("caldav propfind {}", COLLECTION);
+    let request = match parse_body(&body) {
+        Ok(request) => request,
+        Err(e) => return HttpResponse::from(e),
+    };
+    let tasks = match task_service.lock().await.get_all().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error getting tasks for CalDAV");
+            return HttpResponse::from(e);
+        }
+    };
+    let resources: Vec<Resource> = tasks.iter().map(Resource::task).collect();
+    let mut multistatus = Multistatus::new();
+    Resource::Collection { ctag: ctag(&resources) }.describe(&mut multistatus, &request.props);
+    if depth(&req) > 0 {
+        for resource in &resources {
+            resource.describe(&mut multistatus, &request.props);
+        }
+    }
+    multi_status(multistatus)
+}
+
+async fn report_collection(task_service: web::Data<Mutex<TaskService>>, body: web::Bytes) -> HttpResponse {
+    let request = match parse_body(&body) {
+        Ok(request) => request,
+        Err(e) => return HttpResponse::from(e),
+    };
+    let report = request.root.as_ref().map(|root| root.local.as_str()).unwrap_or_default();
+    println!("caldav report {}", report);
+    let tasks = match task_service.lock().await.get_all().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error getting tasks for CalDAV");
+            return HttpResponse::from(e);
+        }
+    };
+
+    let mut multistatus = Multistatus::new();
+    match request.root {
+        Some(ref root) if root.is(CALDAV, "calendar-query") => {
+            for task in tasks.iter().filter(|task| !(request.exclude_completed && task.status)) {
+                Resource::task(task).describe(&mut multistatus, &request.props);
+            }
+        }
+        Some(ref root) if root.is(CALDAV, "calendar-multiget") => {
+            for href in &request.hrefs {
+                let task = href
+                    .strip_prefix(COLLECTION)
+                    .and_then(decode_segment)
+                    .and_then(|name| find_task(&tasks, &name));
+                match task {
+                    Some(task) => Resource::task(task).describe(&mut multistatus, &request.props),
+                    None => multistatus.not_found(href),
+                }
+            }
+        }
+        // Отчет не поддерживается (RFC 3253, 3.6)
+        _ => {
+            return HttpResponse::Forbidden()
+                .content_type("application/xml; charset=utf-8")
+                .body("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<d:error xmlns:d=\"DAV:\"><d:supported-report/></d:error>\n");
+        }
+    }
+    multi_status(multistatus)
+}
+
+async fn propfind_task(task_service: web::Data<Mutex<TaskService>>, name: web::Path<String>, body: web::Bytes) -> HttpResponse {
+    println!("caldav propfind {}{}", COLLECTION, name);
+    let request = match parse_body(&body) {
+        Ok(request) => request,
+        Err(e) => return HttpResponse::from(e),
+    };
+    let tasks = match task_service.lock().await.get_all().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error getting tasks for CalDAV");
+            return HttpResponse::from(e);
+        }
+    };
+    let Some(task) = find_task(&tasks, &name) else {
+        return HttpResponse::NotFound().finish();
+    };
+    let mut multistatus = Multistatus::new();
+    Resource::task(task).describe(&mut multistatus, &request.props);
+    multi_status(multistatus)
+}
+
+async fn get_task(task_service: web::Data<Mutex<TaskService>>, name: web::Path<String>) -> HttpResponse {
+    println!("caldav get {}{}", COLLECTION, name);
+    let tasks = match task_service.lock().await.get_all().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error getting tasks for CalDAV");
+            return HttpResponse::from(e);
+        }
+    };
+    match find_task(&tasks, &name).map(Resource::task) {
+        Some(Resource::Task { data, etag, .. }) => HttpResponse::Ok()
+            .content_type(CALENDAR_CONTENT_TYPE)
+            .insert_header((header::ETAG, etag))
+            .body(data),
+        _ => HttpResponse::NotFound().finish(),
+    }
+}
+
+// Создает или заменяет задачу. Поля, которых нет в VTODO (проекты и расширения todo.txt),
+// у существующей задачи сохраняются.
+async fn put_task(
+    task_service: web::Data<Mutex<TaskService>>,
+    session: SessionId,
+    name: web::Path<String>,
+    req: HttpRequest,
+    body: web::Bytes,
+) -> HttpResponse {
+    let name = name.into_inner();
+    println!("caldav put {}{}", COLLECTION, name);
+    let mut records = match std::str::from_utf8(&body).map_err(|e| e.to_string()).and_then(icalendar::parse_calendar) {
+        Ok(records) => records,
+        Err(reason) => return HttpResponse::from(TaskServiceError::Malformed(reason)),
+    };
+    if records.len() != 1 {
+        return HttpResponse::from(TaskServiceError::Malformed("calendar resource must contain exactly one VTODO".to_string()));
+    }
+    let mut record = match records.remove(0) {
+        Ok(record) => record,
+        Err(RecordError::Malformed(reason) | RecordError::Skipped(reason)) => return HttpResponse::from(TaskServiceError::Malformed(reason)),
+    };
+
+    // Блокировка держится до конца, чтобы проверка ETag и запись были атомарны
+    let mut task_service = task_service.lock().await;
+    let tasks = match task_service.get_all().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error getting tasks for CalDAV");
+            return HttpResponse::from(e);
+        }
+    };
+    let existing = find_task(&tasks, &name);
+    if let Some(response) = check_preconditions(&req, existing) {
+        return response;
+    }
+    match existing {
+        Some(task) => {
+            record.id = Some(task.id);
+            record.projects = task.projects.clone();
+            for (key, value) in &task.extensions {
+                record.extensions.entry(key.clone()).or_insert_with(|| value.clone());
+            }
+        }
+        None => {
+            // Имя вида `<id>.ics` со свободным ID задает ID задачи, любое другое запоминается
+            record.id = name
+                .strip_suffix(".ics")
+                .and_then(|id| id.parse::<TaskId>().ok())
+                .filter(|id| tasks.iter().all(|task| task.id != *id));
+            if record.id.is_none() {
+                record.extensions.insert(RESOURCE_NAME.to_string(), name.clone());
+            }
+        }
+    }
+
+    let report = match task_service.import(&session.0, vec![Ok(record)], ConflictMode::Upsert, false).await {
+        Ok(report) => report,
+        Err(e) => {
+            eprintln!("Error saving task from CalDAV");
+            return HttpResponse::from(e);
+        }
+    };
+    let row = report.rows.into_iter().next().unwrap();
+    if let Some(e) = row.error {
+        eprintln!("Error saving task from CalDAV: {:?}", e);
+        return HttpResponse::from(e);
+    }
+    let etag = match task_service.get_by_id(row.id.unwrap()).await {
+        Ok(task) => etag(&calendar_data(&task)),
+        Err(e) => return HttpResponse::from(e),
+    };
+    let status = if row.status == RowStatus::Created { StatusCode::CREATED } else { StatusCode::NO_CONTENT };
+    HttpResponse::build(status).insert_header((header::ETAG, etag)).finish()
+}
+
+async fn delete_task(
+    task_service: web::Data<Mutex<TaskService>>,
+    session: SessionId,
+    name: web::Path<String>,
+    req: HttpRequest,
+) -> HttpResponse {
+    println!("caldav delete {}{}", COLLECTION, name);
+    let mut task_service = task_service.lock().await;
+    let tasks = match task_service.get_all().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error getting tasks for CalDAV");
+            return HttpResponse::from(e);
+        }
+    };
+    let Some(task) = find_task(&tasks, &name) else {
+        return HttpResponse::NotFound().finish();
+    };
+    if let Some(response) = check_preconditions(&req, Some(task)) {
+        return response;
+    }
+    match task_service.delete(&session.0, task.id).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            eprintln!("Error deleting task from CalDAV");
+            HttpResponse::from(e)
+        }
+    }
+}
+
+// Проверяет If-Match и If-None-Match; возвращает 412, если условие не выполнено
+fn check_preconditions(req: &HttpRequest, existing: Option<&Task>) -> Option<HttpResponse> {
+    let current = existing.map(|task| etag(&calendar_data(task)));
+    let matches = |header: header::HeaderName| {
+        req.headers().get(header).and_then(|value| value.to_str().ok()).map(|value| {
+            value.split(',').map(str::trim).any(|tag| (tag == "*" && current.is_some()) || Some(tag) == current.as_deref())
+        })
+    };
+    match (matches(header::IF_MATCH), matches(header::IF_NONE_MATCH)) {
+        (Some(false), _) | (_, Some(true)) => Some(HttpResponse::PreconditionFailed().finish()),
+        _ => None,
+    }
+}
+
+fn parse_body(body: &[u8]) -> Result<DavRequest, TaskServiceError> {
+    let body = std::str::from_utf8(body).map_err(|e| TaskServiceError::Malformed(e.to_string()))?;
+    if body.trim().is_empty() {
+        return Ok(DavRequest::default());
+    }
+    xml::parse_request(body).map_err(|reason| {
+        eprintln!("Error parsing CalDAV request: {reason}");
+        TaskServiceError::Malformed(reason)
+    })
+}
+
+fn multi_status(multistatus: Multistatus) -> HttpResponse {
+    HttpResponse::build(StatusCode::MULTI_STATUS)
+        .content_type("application/xml; charset=utf-8")
+        .body(multistatus.finish())
+}
+
+// Глубина PROPFIND: 0 - только сам ресурс, иначе еще и вложенные (infinity сводится к 1)
+fn depth(req: &HttpRequest) -> u8 {
+    match req.headers().get("Depth").and_then(|value| value.to_str().ok()) {
+        Some("0") => 0,
+        _ => 1,
+    }
+}
+
+fn resource_name(task: &Task) -> String {
+    match task.extensions.get(RESOURCE_NAME) {
+        Some(name) => name.clone(),
+        None => format!("{}.ics", task.id),
+    }
+}
+
+fn find_task<'a>(tasks: &'a [Task], name: &str) -> Option<&'a Task> {
+    tasks.iter().find(|task| resource_name(task) == name)
+}
+
+// Представление задачи в календаре. DTSTAMP берется из даты создания, чтобы
+// представление и его ETag не менялись, пока не изменится задача.
+fn calendar_data(task: &Task) -> String {
+    let stamp = task
+        .created
+        .and_then(|created| created.and_hms_opt(0, 0, 0))
+        .map(|created| created.and_utc())
+        .unwrap_or(DateTime::<Utc>::UNIX_EPOCH);
+    let mut data = Vec::new();
+    icalendar::write_calendar(std::slice::from_ref(task), stamp, &mut data).unwrap();
+    String::from_utf8(data).unwrap()
+}
+
+fn etag(data: &str) -> String {
+    format!("\"{:x}\"", Sha256::digest(data.as_bytes()))
+}
+
+// ctag меняется при любом изменении в коллекции
+fn ctag(resources: &[Resource]) -> String {
+    let mut hasher = Sha256::new();
+    for resource in resources {
+        if let Resource::Task { etag, .. } = resource {
+            hasher.update(etag.as_bytes());
+        }
+    }
+    format!("{:x}", hasher.finalize())
+}
+
+// Кодирует имя ресурса для href: все, кроме незарезервированных символов, в виде %XX
+fn encode_segment(name: &str) -> String {
+    name.bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'.' | b'_' | b'~' | b'@' => (byte as char).to_string(),
+            byte => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn decode_segment(segment: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(segment.len());
+    let mut rest = segment.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+// Проверяем CalDAV на запросах в том виде, в каком их отправляют Thunderbird, DAVx5 и Apple
+#[cfg(test)]
+mod caldav_tests {
+    use actix_web::{App, http::{Method, StatusCode}, test, web};
+    use tokio::sync::Mutex;
+
+    use crate::application::import::{ConflictMode, ImportRecord};
+    use crate::application::services::TaskService;
+    use crate::domain::entities::Task;
+    use crate::domain::repositories::TaskRepository;
+    use crate::infrastructure::in_memory::InMemoryTaskRepository;
+    use crate::presentation::caldav;
+
+    const THUNDERBIRD_PROPFIND_CALENDAR: &str = include_str!("../../tests/fixtures/caldav/thunderbird_propfind_calendar.xml");
+    const THUNDERBIRD_CALENDAR_QUERY: &str = include_str!("../../tests/fixtures/caldav/thunderbird_calendar_query.xml");
+    const DAVX5_PROPFIND_PRINCIPAL: &str = include_str!("../../tests/fixtures/caldav/davx5_propfind_principal.xml");
+    const DAVX5_PROPFIND_MEMBERS: &str = include_str!("../../tests/fixtures/caldav/davx5_propfind_members.xml");
+    const DAVX5_PUT_TODO: &str = include_str!("../../tests/fixtures/caldav/davx5_put_todo.ics");
+    const APPLE_CALENDAR_MULTIGET: &str = include_str!("../../tests/fixtures/caldav/apple_calendar_multiget.xml");
+
+    // Сервис с открытой задачей 1 и выполненной задачей 2
+    async fn service() -> web::Data<Mutex<TaskService>> {
+        let mut repository = InMemoryTaskRepository::new();
+        for (title, status) in [("Open", false), ("Done", true)] {
+            let id = repository.next_id().await;
+            let task = Task { id, title: title.to_string(), description: "Desc".to_string(), status, ..Default::default() };
+            repository.create(task).await.unwrap();
+        }
+        web::Data::new(Mutex::new(TaskService::new(Box::new(repository))))
+    }
+
+    fn dav(method: &str, uri: &str, depth: &str, body: &str) -> test::TestRequest {
+        test::TestRequest::default()
+            .method(Method::from_bytes(method.as_bytes()).unwrap())
+            .uri(uri)
+            .insert_header(("Depth", depth))
+            .set_payload(body.to_string())
+    }
+
+    async fn body_text(response: actix_web::dev::ServiceResponse) -> String {
+        String::from_utf8(test::read_body(response).await.to_vec()).unwrap()
+    }
+
+    #[tokio::test]
+    async fn discovery_via_well_known_and_principal() {
+        // Проверяем путь DAVx5: /.well-known/caldav, затем принципал и домашний каталог
+        let app = test::init_service(App::new().app_data(service().await).configure(caldav::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/.well-known/caldav").to_request()).await;
+        assert_eq!(response.status(), StatusCode::MOVED_PERMANENTLY);
+        assert_eq!(response.headers().get("location").unwrap(), "/dav/");
+
+        let response = test::call_service(&app, test::TestRequest::default().method(Method::OPTIONS).uri("/dav/").to_request()).await;
+        assert!(response.headers().get("dav").unwrap().to_str().unwrap().contains("calendar-access"));
+
+        let response = test::call_service(&app, dav("PROPFIND", "/dav/", "0", DAVX5_PROPFIND_PRINCIPAL).to_request()).await;
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = body_text(response).await;
+        assert!(body.contains("<d:current-user-principal><d:href>/dav/</d:href></d:current-user-principal>"));
+        assert!(body.contains("<c:calendar-home-set><d:href>/dav/</d:href></c:calendar-home-set>"));
+        // Адресные книги не поддерживаются
+        assert!(body.contains("<x:addressbook-home-set xmlns:x=\"urn:ietf:params:xml:ns:carddav\"/></d:prop><d:status>HTTP/1.1 404 Not Found"));
+    }
+
+    #[tokio::test]
+    async fn propfind_describes_calendar_and_members() {
+        // Проверяем свойства календаря для Thunderbird и список задач с ETag для DAVx5
+        let app = test::init_service(App::new().app_data(service().await).configure(caldav::configure)).await;
+
+        let response = test::call_service(&app, dav("PROPFIND", "/dav/tasks/", "0", THUNDERBIRD_PROPFIND_CALENDAR).to_request()).await;
+        let body = body_text(response).await;
+        assert!(body.contains("<d:resourcetype><d:collection/><c:calendar/></d:resourcetype>"));
+        assert!(body.contains("<c:supported-calendar-component-set><c:comp name=\"VTODO\"/></c:supported-calendar-component-set>"));
+        assert!(body.contains("<cs:getctag>"));
+        assert!(body.contains("<d:owner/></d:prop><d:status>HTTP/1.1 404 Not Found"));
+        assert!(!body.contains("/dav/tasks/1.ics"));
+
+        let response = test::call_service(&app, dav("PROPFIND", "/dav/tasks/", "1", DAVX5_PROPFIND_MEMBERS).to_request()).await;
+        let body = body_text(response).await;
+        assert_eq!(body.matches("<d:response>").count(), 3);
+        assert!(body.contains("<d:href>/dav/tasks/1.ics</d:href>"));
+        assert!(body.contains("<d:getcontenttype>text/calendar; charset=utf-8; component=VTODO</d:getcontenttype>"));
+        assert_eq!(body.matches("<d:getetag>&quot;").count(), 2);
+    }
+
+    #[tokio::test]
+    async fn calendar_query_skips_completed_tasks() {
+        // Проверяем фильтр Thunderbird, скрывающий выполненные задачи
+        let app = test::init_service(App::new().app_data(service().await).configure(caldav::configure)).await;
+
+        let response = test::call_service(&app, dav("REPORT", "/dav/tasks/", "1", THUNDERBIRD_CALENDAR_QUERY).to_request()).await;
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body = body_text(response).await;
+        assert!(body.contains("<d:href>/dav/tasks/1.ics</d:href>"));
+        assert!(!body.contains("/dav/tasks/2.ics"));
+        assert!(!body.contains("calendar-data"));
+    }
+
+    #[tokio::test]
+    async fn multiget_returns_calendar_data() {
+        // Проверяем, что Apple получает данные запрошенных задач и 404 для неизвестного адреса
+        let app = test::init_service(App::new().app_data(service().await).configure(caldav::configure)).await;
+
+        let response = test::call_service(&app, dav("REPORT", "/dav/tasks/", "1", APPLE_CALENDAR_MULTIGET).to_request()).await;
+        let body = body_text(response).await;
+        // CR экранируется, иначе XML-парсер клиента превратит CRLF в LF
+        assert!(body.contains("<c:calendar-data>BEGIN:VCALENDAR&#13;\n"));
+        assert!(body.contains("SUMMARY:Open&#13;\n"));
+        assert!(body.contains("<d:href>/dav/tasks/missing%20task.ics</d:href><d:status>HTTP/1.1 404 Not Found</d:status>"));
+        assert!(body.contains("<cs:updated-by/></d:prop><d:status>HTTP/1.1 404 Not Found"));
+    }
+
+    #[tokio::test]
+    async fn put_get_and_delete_with_etags() {
+        // Проверяем создание задачи клиентом, ее чтение, условное изменение и удаление
+        let service = service().await;
+        let app = test::init_service(App::new().app_data(service.clone()).configure(caldav::configure)).await;
+        let uri = "/dav/tasks/7e1c9f0a-3b2d-4c5e-9f6a-1b2c3d4e5f60.ics";
+
+        let request = test::TestRequest::put().uri(uri).insert_header(("If-None-Match", "*")).set_payload(DAVX5_PUT_TODO);
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        let etag = response.headers().get("etag").unwrap().to_str().unwrap().to_string();
+
+        let task = service.lock().await.get_by_id(3).await.unwrap();
+        assert_eq!(task.title, "Renew passport");
+        assert_eq!(task.description, "Bring two photos, old passport\nand the form");
+        assert_eq!(task.tags, vec!["errands", "documents"]);
+        assert_eq!(task.priority, Some('A'));
+
+        let response = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+        assert_eq!(response.headers().get("etag").unwrap().to_str().unwrap(), etag);
+        assert!(body_text(response).await.contains("UID:7e1c9f0a-3b2d-4c5e-9f6a-1b2c3d4e5f60\r\n"));
+
+        // Повторное создание и изменение по устаревшему ETag отклоняются
+        let request = test::TestRequest::put().uri(uri).insert_header(("If-None-Match", "*")).set_payload(DAVX5_PUT_TODO);
+        assert_eq!(test::call_service(&app, request.to_request()).await.status(), StatusCode::PRECONDITION_FAILED);
+        let request = test::TestRequest::put().uri(uri).insert_header(("If-Match", "\"stale\"")).set_payload(DAVX5_PUT_TODO);
+        assert_eq!(test::call_service(&app, request.to_request()).await.status(), StatusCode::PRECONDITION_FAILED);
+
+        let updated = DAVX5_PUT_TODO.replace("STATUS:NEEDS-ACTION", "STATUS:COMPLETED");
+        let request = test::TestRequest::put().uri(uri).insert_header(("If-Match", etag.as_str())).set_payload(updated);
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_ne!(response.headers().get("etag").unwrap().to_str().unwrap(), etag);
+        assert!(service.lock().await.get_by_id(3).await.unwrap().status);
+
+        let response = test::call_service(&app, test::TestRequest::delete().uri(uri).to_request()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert_eq!(service.lock().await.get_all().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn put_keeps_fields_missing_from_vtodo() {
+        // Проверяем, что изменение через CalDAV не стирает проекты и расширения todo.txt
+        let service = service().await;
+        let record = ImportRecord {
+            id: Some(1),
+            title: "Open".to_string(),
+            description: "Desc".to_string(),
+            projects: vec!["Home".to_string()],
+            extensions: [("note".to_string(), "keep".to_string())].into(),
+            ..Default::default()
+        };
+        service.lock().await.import("test", vec![Ok(record)], ConflictMode::Upsert, false).await.unwrap();
+        let app = test::init_service(App::new().app_data(service.clone()).configure(caldav::configure)).await;
+
+        let vtodo = "BEGIN:VCALENDAR\r\nBEGIN:VTODO\r\nUID:task-1@clean-todo-demo\r\nSUMMARY:Renamed\r\nEND:VTODO\r\nEND:VCALENDAR\r\n";
+        let response = test::call_service(&app, test::TestRequest::put().uri("/dav/tasks/1.ics").set_payload(vtodo).to_request()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+
+        let task = service.lock().await.get_by_id(1).await.unwrap();
+        assert_eq!(task.title, "Renamed");
+        assert_eq!(task.projects, vec!["Home"]);
+        assert_eq!(task.extensions["note"], "keep");
+    }
+}
@@ -0,0 +1,176 @@
+use quick_xml::XmlVersion;
+use quick_xml::events::Event;
+use quick_xml::name::ResolveResult;
+use quick_xml::NsReader;
+
+// Пространства имен WebDAV, CalDAV и расширений Apple CalendarServer
+pub const DAV: &str = "DAV:";
+pub const CALDAV: &str = "urn:ietf:params:xml:ns:caldav";
+pub const CALENDAR_SERVER: &str = "http://calendarserver.org/ns/";
+
+// Имя элемента XML вместе с пространством имен
+#[derive(Clone, Debug, PartialEq)]
+pub struct Name {
+    pub namespace: String,
+    pub local: String,
+}
+
+impl Name {
+    pub fn new(namespace: &str, local: &str) -> Name {
+        Name { namespace: namespace.to_string(), local: local.to_string() }
+    }
+
+    pub fn is(&self, namespace: &str, local: &str) -> bool {
+        self.namespace == namespace && self.local == local
+    }
+}
+
+// Разобранное тело запроса PROPFIND или REPORT
+#[derive(Debug, Default)]
+pub struct DavRequest {
+    pub root: Option<Name>, // Корневой элемент: propfind, calendar-query, calendar-multiget
+    pub props: Vec<Name>, // Запрошенные свойства; пустой список означает allprop
+    pub hrefs: Vec<String>, // Адреса ресурсов для calendar-multiget
+    pub exclude_completed: bool, // Фильтр отсекает выполненные задачи
+}
+
+// Разбирает тело запроса. Пустое тело PROPFIND по RFC 4918 означает allprop.
+pub fn parse_request(body: &str) -> Result<DavRequest, String> {
+    let mut request = DavRequest::default();
+    let mut reader = NsReader::from_str(body);
+    let mut stack: Vec<Name> = Vec::new();
+    let mut text = String::new();
+    let mut prop_filter: Option<String> = None; // Свойство текущего prop-filter
+    let mut negated = false; // Текущий text-match с negate-condition="yes"
+
+    loop {
+        let (namespace, event) = reader.read_resolved_event().map_err(|e| e.to_string())?;
+        let namespace = match namespace {
+            ResolveResult::Bound(namespace) => namespace.0.to_string(),
+            _ => String::new(),
+        };
+        match event {
+            Event::Start(ref element) | Event::Empty(ref element) => {
+                let name = Name { namespace, local: element.local_name().as_ref().to_string() };
+                let attribute = |key: &str| {
+                    element
+                        .try_get_attribute(key)
+                        .ok()
+                        .flatten()
+                        .and_then(|attribute| attribute.normalized_value(XmlVersion::Implicit1_0).ok().map(|value| value.into_owned()))
+                };
+                if stack.is_empty() {
+                    request.root = Some(name.clone());
+                } else if stack.len() == 2 && stack[1].is(DAV, "prop") {
+                    request.props.push(name.clone());
+                }
+                if name.is(CALDAV, "prop-filter") {
+                    prop_filter = attribute("name").map(|value| value.to_ascii_uppercase());
+                } else if name.is(CALDAV, "is-not-defined") && prop_filter.as_deref() == Some("COMPLETED") {
+                    request.exclude_completed = true;
+                } else if name.is(CALDAV, "text-match") {
+                    negated = attribute("negate-condition").as_deref() == Some("yes");
+                }
+                text.clear();
+                if matches!(event, Event::Start(_)) {
+                    stack.push(name);
+                }
+            }
+            Event::Text(content) => text.push_str(&content.xml10_content()),
+            Event::CData(content) => text.push_str(&content.xml10_content()),
+            Event::GeneralRef(reference) => {
+                let reference = format!("&{};", &*reference);
+                text.push_str(&quick_xml::escape::unescape(&reference).map_err(|e| e.to_string())?);
+            }
+            Event::End(_) => {
+                let name = stack.pop().ok_or("unexpected closing tag")?;
+                if name.is(DAV, "href") {
+                    request.hrefs.push(text.trim().to_string());
+                } else if name.is(CALDAV, "text-match") && negated && prop_filter.as_deref() == Some("STATUS") && text.trim().eq_ignore_ascii_case("COMPLETED") {
+                    request.exclude_completed = true;
+                } else if name.is(CALDAV, "prop-filter") {
+                    prop_filter = None;
+                }
+                text.clear();
+            }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+    Ok(request)
+}
+
+// Ответ 207 Multi-Status
+pub struct Multistatus {
+    body: String,
+}
+
+impl Multistatus {
+    pub fn new() -> Multistatus {
+        Multistatus {
+            body: format!(
+                "<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<d:multistatus xmlns:d=\"{}\" xmlns:c=\"{}\" xmlns:cs=\"{}\">\n",
+                DAV, CALDAV, CALENDAR_SERVER
+            ),
+        }
+    }
+
+    // Ресурс с найденными свойствами (имя и содержимое в виде XML) и свойствами, которых у него нет
+    pub fn response(&mut self, href: &str, found: Vec<(Name, String)>, missing: Vec<Name>) {
+        self.body.push_str(&format!("<d:response><d:href>{}</d:href>", escape(href)));
+        if !found.is_empty() {
+            self.body.push_str("<d:propstat><d:prop>");
+            for (name, content) in &found {
+                self.body.push_str(&element(name, content));
+            }
+            self.body.push_str("</d:prop><d:status>HTTP/1.1 200 OK</d:status></d:propstat>");
+        }
+        if !missing.is_empty() {
+            self.body.push_str("<d:propstat><d:prop>");
+            for name in &missing {
+                self.body.push_str(&element(name, ""));
+            }
+            self.body.push_str("</d:prop><d:status>HTTP/1.1 404 Not Found</d:status></d:propstat>");
+        }
+        self.body.push_str("</d:response>\n");
+    }
+
+    // Ресурс, которого нет
+    pub fn not_found(&mut self, href: &str) {
+        self.body.push_str(&format!(
+            "<d:response><d:href>{}</d:href><d:status>HTTP/1.1 404 Not Found</d:status></d:response>\n",
+            escape(href)
+        ));
+    }
+
+    pub fn finish(mut self) -> String {
+        self.body.push_str("</d:multistatus>\n");
+        self.body
+    }
+}
+
+impl Default for Multistatus {
+    fn default() -> Self {
+        Multistatus::new()
+    }
+}
+
+// Элемент с префиксом известного пространства имен; для остальных пространство объявляется на месте
+pub fn element(name: &Name, content: &str) -> String {
+    let (tag, declaration) = match name.namespace.as_str() {
+        DAV => (format!("d:{}", name.local), String::new()),
+        CALDAV => (format!("c:{}", name.local), String::new()),
+        CALENDAR_SERVER => (format!("cs:{}", name.local), String::new()),
+        "" => (name.local.clone(), String::new()),
+        namespace => (format!("x:{}", name.local), format!(" xmlns:x=\"{}\"", escape(namespace))),
+    };
+    if content.is_empty() {
+        format!("<{}{}/>", tag, declaration)
+    } else {
+        format!("<{}{}>{}</{}>", tag, declaration, content, tag)
+    }
+}
+
+pub fn escape(value: &str) -> String {
+    quick_xml::escape::escape(value).into_owned()
+}
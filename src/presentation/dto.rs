@@ -1,19 +1,251 @@
-use actix_web::HttpResponse;
+use std::collections::BTreeMap;
 
-use crate::{application::services::TaskServiceError, domain::entities::{Task, TaskId}};
+use actix_web::{HttpResponse, http::StatusCode};
+use chrono::{DateTime, NaiveDate, Utc};
 
-#[derive(serde::Deserialize)]
+use crate::{
+    application::{
+        export::ExportFormat,
+        feed::{FeedEvent, FeedFilter},
+        import::{ConflictMode, ImportFormat, ImportReport, RowStatus},
+        patch::PatchError,
+        services::{BatchOperation, TaskDraft, TaskServiceError},
+        validation::{TaskField, ValidationError},
+        webhooks::WebhookError,
+    },
+    domain::{
+        entities::{Task, TaskId},
+        events::ChangeKind,
+        queries::{TaskFilter, TaskMutation},
+        repositories::BatchMode,
+        webhooks::{DeliveryAttempt, NewWebhook, Webhook, WebhookId},
+    },
+};
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
 pub struct CreateTaskRequest {
     pub title: String, // Название задачи
     pub description: String, // Описание задачи
+    #[serde(default)]
+    pub due: Option<String>, // Срок в формате ГГГГ-ММ-ДД
+}
+
+impl From<CreateTaskRequest> for TaskDraft {
+    fn from(request: CreateTaskRequest) -> Self {
+        TaskDraft { title: request.title, description: request.description, due: request.due }
+    }
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct UpdateTaskRequest {
+    pub title: String, // Новое название задачи
+    pub description: String, // Новое описание задачи
+    #[serde(default)]
+    pub due: Option<String>, // Новый срок; если не указан, остается прежним
+}
+
+impl From<UpdateTaskRequest> for TaskDraft {
+    fn from(request: UpdateTaskRequest) -> Self {
+        TaskDraft { title: request.title, description: request.description, due: request.due }
+    }
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct BatchRequest {
+    pub mode: BatchMode, // Режим выполнения: all_or_nothing или best_effort
+    pub operations: Vec<BatchOperationRequest>, // Операции пакета
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+#[serde(tag = "op", rename_all = "snake_case")]
+pub enum BatchOperationRequest {
+    Create {
+        title: String,
+        description: String,
+        #[serde(default)]
+        due: Option<String>,
+    },
+    Update {
+        #[schema(value_type = i64)]
+        id: TaskId,
+        title: String,
+        description: String,
+        #[serde(default)]
+        due: Option<String>,
+    },
+    Toggle {
+        #[schema(value_type = i64)]
+        id: TaskId,
+    },
+    Delete {
+        #[schema(value_type = i64)]
+        id: TaskId,
+    },
+}
+
+impl From<BatchOperationRequest> for BatchOperation {
+    fn from(request: BatchOperationRequest) -> Self {
+        match request {
+            BatchOperationRequest::Create { title, description, due } => BatchOperation::Create { draft: TaskDraft { title, description, due } },
+            BatchOperationRequest::Update { id, title, description, due } => BatchOperation::Update { id, draft: TaskDraft { title, description, due } },
+            BatchOperationRequest::Toggle { id } => BatchOperation::Toggle { id },
+            BatchOperationRequest::Delete { id } => BatchOperation::Delete { id },
+        }
+    }
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct BatchResponse {
+    pub results: Vec<BatchItemResponse>, // Результаты в порядке операций запроса
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct BatchItemResponse {
+    pub index: usize, // Номер операции в запросе
+    pub status: BatchItemStatus, // Итог операции
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<i64>)]
+    pub id: Option<TaskId>, // Идентификатор затронутой задачи
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiErrorResponse>, // Ошибка, если операция не выполнена
+}
+
+#[derive(serde::Serialize, PartialEq, utoipa::ToSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchItemStatus {
+    Ok, // Операция выполнена
+    Error, // Операция не выполнена
+}
+
+impl BatchItemResponse {
+    pub fn new(index: usize, result: Result<TaskId, TaskServiceError>) -> Self {
+        match result {
+            Ok(id) => BatchItemResponse { index, status: BatchItemStatus::Ok, id: Some(id), error: None },
+            Err(e) => BatchItemResponse { index, status: BatchItemStatus::Error, id: None, error: Some(ApiErrorResponse::from(&e)) },
+        }
+    }
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct BulkUpdateRequest {
+    pub filter: TaskFilter, // Условие отбора задач
+    pub mutation: TaskMutation, // Изменение, применяемое к отобранным задачам
+    #[serde(default)]
+    pub dry_run: bool, // Только показать, какие задачи будут изменены
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct BulkUpdateResponse {
+    pub dry_run: bool, // Был ли это пробный запуск
+    #[schema(value_type = Vec<i64>)]
+    pub affected: Vec<TaskId>, // Идентификаторы измененных (или подходящих) задач
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct TasksQuery {
+    pub as_of: Option<DateTime<Utc>>, // Момент времени, на который нужно состояние задач
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ExportQuery {
+    pub format: ExportFormat, // Формат выгрузки
+    pub status: Option<bool>, // Отбор по статусу
+    pub tag: Option<String>, // Отбор по метке
+    pub title: Option<String>, // Отбор по шаблону названия
+}
+
+impl ExportQuery {
+    pub fn filter(&self) -> TaskFilter {
+        TaskFilter { status: self.status, tag: self.tag.clone(), title: self.title.clone() }
+    }
+}
+
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct ImportQuery {
+    pub format: ImportFormat, // Формат тела запроса
+    #[serde(default)]
+    pub conflict: ConflictMode, // Что делать с уже существующими задачами
+    #[serde(default)]
+    pub dry_run: bool, // Только проверить записи, ничего не загружая
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct ImportResponse {
+    pub dry_run: bool, // Был ли это пробный запуск
+    pub aborted: bool, // Загрузка отменена из-за конфликта
+    pub created: usize, // Количество созданных задач
+    pub updated: usize, // Количество замененных задач
+    pub skipped: usize, // Количество пропущенных записей: существующих задач и записей, которые не описывают задачу
+    pub rejected: usize, // Количество отклоненных записей
+    pub rows: Vec<ImportRowResponse>, // Отчет по каждой записи
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub unmapped: BTreeMap<String, usize>, // Не перенесенные в задачи поля и число записей с ними
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct ImportRowResponse {
+    pub row: usize, // Номер записи в файле, начиная с 1
+    pub status: RowStatus, // Итог обработки записи
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schema(value_type = Option<i64>)]
+    pub id: Option<TaskId>, // ID задачи
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<ApiErrorResponse>, // Причина отклонения
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>, // Почему пропущена запись, которая не описывает задачу
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub unmapped: Vec<String>, // Поля записи, которые не перенесены в задачу
+}
+
+impl From<ImportReport> for ImportResponse {
+    fn from(report: ImportReport) -> Self {
+        ImportResponse {
+            dry_run: report.dry_run,
+            aborted: report.aborted,
+            created: report.count(RowStatus::Created),
+            updated: report.count(RowStatus::Updated),
+            skipped: report.count(RowStatus::Skipped),
+            rejected: report.count(RowStatus::Rejected),
+            unmapped: report.unmapped(),
+            rows: report
+                .rows
+                .into_iter()
+                .map(|row| ImportRowResponse {
+                    row: row.row,
+                    status: row.status,
+                    id: row.id,
+                    error: row.error.as_ref().map(ApiErrorResponse::from),
+                    reason: row.reason,
+                    unmapped: row.unmapped,
+                })
+                .collect(),
+        }
+    }
+}
+
+#[derive(serde::Serialize, utoipa::ToSchema)]
 pub struct TaskResponse {
+    #[schema(value_type = i64)]
     pub id: TaskId, // Идентификатор задачи
     pub title: String, // Название задачи
     pub description: String, // Описание задачи
     pub status: bool, // Статус задачи
+    pub tags: Vec<String>, // Метки задачи
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub priority: Option<char>, // Приоритет от A до Z
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<NaiveDate>, // Дата создания
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub completed: Option<NaiveDate>, // Дата выполнения
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub due: Option<NaiveDate>, // Срок выполнения
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub projects: Vec<String>, // Проекты
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub extensions: BTreeMap<String, String>, // Прочие расширения todo.txt
 }
 
 impl From<Task> for TaskResponse {
@@ -23,61 +255,258 @@ impl From<Task> for TaskResponse {
             title: task.title,
             description: task.description,
             status: task.status,
+            tags: task.tags,
+            priority: task.priority,
+            created: task.created,
+            completed: task.completed,
+            due: task.due,
+            projects: task.projects,
+            extensions: task.extensions,
         }
     }
 }
 
-#[derive(serde::Serialize)]
+#[derive(serde::Deserialize, utoipa::IntoParams)]
+#[into_params(parameter_in = Query)]
+pub struct FeedQuery {
+    pub tag: Option<String>, // Только задачи с этой меткой до или после изменения
+    pub project: Option<String>, // Только задачи этого проекта до или после изменения
+    pub after: Option<u64>, // Продолжить после события с этим номером; для SSE то же делает Last-Event-ID
+}
+
+impl From<&FeedQuery> for FeedFilter {
+    fn from(query: &FeedQuery) -> Self {
+        FeedFilter { tag: query.tag.clone(), project: query.project.clone() }
+    }
+}
+
+// Событие ленты изменений. Если часть событий потеряна, вместо события приходит
+// `{"type": "reset"}`, и список задач нужно прочитать заново.
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct EventResponse {
+    pub id: u64, // Номер события
+    #[serde(rename = "type")]
+    pub kind: ChangeKind, // Что произошло с задачей
+    pub task: TaskResponse, // Задача после изменения; для удаленной - последнее ее состояние
+}
+
+impl From<FeedEvent> for EventResponse {
+    fn from(event: FeedEvent) -> Self {
+        EventResponse { id: event.id, kind: event.kind, task: event.task.into() }
+    }
+}
+
+#[derive(serde::Deserialize, utoipa::ToSchema)]
+pub struct CreateWebhookRequest {
+    pub url: String, // Адрес http или https, на который отправляются уведомления
+    #[serde(default)]
+    pub events: Vec<ChangeKind>, // Интересующие виды изменений; пустой список - все
+    pub secret: String, // Ключ, которым подписывается каждое уведомление
+}
+
+impl From<CreateWebhookRequest> for NewWebhook {
+    fn from(request: CreateWebhookRequest) -> Self {
+        NewWebhook { url: request.url, events: request.events, secret: request.secret }
+    }
+}
+
+// Подписка без секрета: он известен только создавшему ее клиенту
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct WebhookResponse {
+    #[schema(value_type = i64)]
+    pub id: WebhookId, // Идентификатор подписки
+    pub url: String, // Адрес получателя
+    pub events: Vec<ChangeKind>, // Интересующие виды изменений; пустой список - все
+    pub created_at: DateTime<Utc>, // Время создания
+}
+
+impl From<Webhook> for WebhookResponse {
+    fn from(webhook: Webhook) -> Self {
+        WebhookResponse { id: webhook.id, url: webhook.url, events: webhook.events, created_at: webhook.created_at }
+    }
+}
+
+// Попытка доставить уведомление
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct DeliveryResponse {
+    pub delivery: String, // Идентификатор доставки из заголовка X-Webhook-Delivery
+    pub event_id: u64, // Номер события в ленте изменений
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub event: Option<ChangeKind>, // Вид изменения; нет у событий, потерянных до доставки
+    pub attempt: u32, // Номер попытки, начиная с 1; 0 - уведомление не отправлялось
+    pub attempted_at: DateTime<Utc>, // Время попытки
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status_code: Option<u16>, // Код ответа получателя
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>, // Почему попытка не удалась
+    pub succeeded: bool, // Получатель принял уведомление
+}
+
+impl From<DeliveryAttempt> for DeliveryResponse {
+    fn from(attempt: DeliveryAttempt) -> Self {
+        DeliveryResponse {
+            delivery: attempt.delivery,
+            event_id: attempt.event_id,
+            event: attempt.event,
+            attempt: attempt.attempt,
+            attempted_at: attempt.attempted_at,
+            status_code: attempt.status_code,
+            error: attempt.error,
+            succeeded: attempt.succeeded,
+        }
+    }
+}
+
+// Тип содержимого ответов с ошибкой
+pub const PROBLEM_CONTENT_TYPE: &str = "application/problem+json";
+
+// Ошибка в формате Problem Details (RFC 7807). Тип about:blank означает, что смысл ошибки
+// определяется кодом ответа, а title - его стандартное название. Машиночитаемый код ошибки
+// и ошибки отдельных полей передаются расширениями code и errors.
+#[derive(serde::Serialize, utoipa::ToSchema)]
 pub struct ApiErrorResponse {
+    #[serde(rename = "type")]
+    pub problem_type: String, // URI типа проблемы
+    pub title: String, // Краткое название проблемы
+    pub status: u16, // Код ответа HTTP
+    pub detail: String, // Описание именно этого случая
     pub code: String, // Код ошибки
-    pub message: String, // Сообщение об ошибке
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub details: Option<serde_json::Value>, // Дополнительные детали об ошибке
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub errors: Vec<FieldErrorResponse>, // Ошибки отдельных полей
 }
 
-impl From<TaskServiceError> for HttpResponse {
-    fn from(e: TaskServiceError) -> Self {
-        let response = match e {
-            TaskServiceError::MissingTitle => ApiErrorResponse {
-                code: "validation_failed".to_string(),
-                message: "Title is required".to_string(),
-                details: None,
-            },
-            TaskServiceError::MissingDescription => ApiErrorResponse {
-                code: "validation_failed".to_string(),
-                message: "Description is required".to_string(),
-                details: None,
-            },
-            TaskServiceError::TaskNotFound => ApiErrorResponse {
-                code: "not_found".to_string(),
-                message: "Task not found".to_string(),
-                details: None,
-            },
-            TaskServiceError::TaskAlreadyExists => ApiErrorResponse {
-                code: "conflict".to_string(),
-                message: "Task already exists".to_string(),
-                details: None,
-            },
-            TaskServiceError::UnexpectedError => ApiErrorResponse {
-                code: "unexpected_error".to_string(),
-                message: "An unexpected error occurred".to_string(),
-                details: None,
+#[derive(serde::Serialize, utoipa::ToSchema)]
+pub struct FieldErrorResponse {
+    pub field: String, // Поле запроса
+    pub code: String, // Код ошибки: required, too_long, unknown_field, invalid_value и т. п.
+    pub message: String, // Описание ошибки
+}
+
+impl ApiErrorResponse {
+    pub fn new(status: StatusCode, code: &str, detail: String) -> Self {
+        ApiErrorResponse {
+            problem_type: "about:blank".to_string(),
+            title: status.canonical_reason().unwrap_or_default().to_string(),
+            status: status.as_u16(),
+            detail,
+            code: code.to_string(),
+            errors: Vec::new(),
+        }
+    }
+}
+
+impl From<&ValidationError> for FieldErrorResponse {
+    fn from(e: &ValidationError) -> Self {
+        let (field, code, message) = match e {
+            ValidationError::MissingTitle => ("title", "required", "Title is required".to_string()),
+            ValidationError::MissingDescription => ("description", "required", "Description is required".to_string()),
+            ValidationError::TitleTooLong { max } => ("title", "too_long", format!("Title must be at most {} characters long", max)),
+            ValidationError::DescriptionTooLong { max } => {
+                ("description", "too_long", format!("Description must be at most {} characters long", max))
+            }
+            ValidationError::ForbiddenCharacter { field: TaskField::Title, character } => {
+                ("title", "forbidden_character", format!("Title must not contain {:?}", character))
             }
+            ValidationError::ForbiddenCharacter { field: TaskField::Description, character } => {
+                ("description", "forbidden_character", format!("Description must not contain {:?}", character))
+            }
+            ValidationError::InvalidDue(due) => ("due", "invalid_date", format!("{:?} is not a date in YYYY-MM-DD format", due)),
         };
+        FieldErrorResponse { field: field.to_string(), code: code.to_string(), message }
+    }
+}
 
-        match e {
-            TaskServiceError::MissingTitle | TaskServiceError::MissingDescription => {
-                HttpResponse::BadRequest().json(response)
-            },
-            TaskServiceError::TaskNotFound => {
-                HttpResponse::NotFound().json(response)
-            },
-            TaskServiceError::TaskAlreadyExists => {
-                HttpResponse::Conflict().json(response)
-            },
-            TaskServiceError::UnexpectedError => {
-                HttpResponse::InternalServerError().json(response)
+// Поле ошибки изменения - поле задачи или, если ошибка в пути, JSON Pointer операции
+impl From<&PatchError> for FieldErrorResponse {
+    fn from(e: &PatchError) -> Self {
+        let (field, code, message) = match e {
+            PatchError::UnknownField(field) => (field.clone(), "unknown_field", format!("Task has no field {:?}", field)),
+            PatchError::ImmutableField(field) => (field.clone(), "immutable_field", format!("Field {:?} cannot be changed", field)),
+            PatchError::InvalidPath(path) => (path.clone(), "invalid_path", format!("Path {:?} does not exist or cannot be changed", path)),
+            PatchError::TestFailed(path) => (path.clone(), "test_failed", format!("Value at {:?} does not match", path)),
+            PatchError::InvalidValue { field, reason } => (field.clone(), "invalid_value", reason.clone()),
+        };
+        FieldErrorResponse { field, code: code.to_string(), message }
+    }
+}
+
+impl From<&TaskServiceError> for ApiErrorResponse {
+    fn from(e: &TaskServiceError) -> Self {
+        let (status, code, detail) = match e {
+            TaskServiceError::Validation(_) => (StatusCode::BAD_REQUEST, "validation_failed", "Task fields are invalid".to_string()),
+            TaskServiceError::InvalidPatch(PatchError::TestFailed(_)) => {
+                (StatusCode::CONFLICT, "patch_test_failed", "Task does not match the test operation of the patch".to_string())
             }
+            TaskServiceError::InvalidPatch(_) => (StatusCode::UNPROCESSABLE_ENTITY, "invalid_patch", "Patch cannot be applied to the task".to_string()),
+            TaskServiceError::TaskNotFound => (StatusCode::NOT_FOUND, "not_found", "Task not found".to_string()),
+            TaskServiceError::TaskAlreadyExists => (StatusCode::CONFLICT, "conflict", "Task already exists".to_string()),
+            TaskServiceError::NothingToUndo => (StatusCode::CONFLICT, "nothing_to_undo", "There is no operation to undo".to_string()),
+            TaskServiceError::NothingToRedo => (StatusCode::CONFLICT, "nothing_to_redo", "There is no operation to redo".to_string()),
+            TaskServiceError::UndoConflict => (StatusCode::CONFLICT, "conflict", "Task was modified after the operation".to_string()),
+            TaskServiceError::Aborted => (
+                StatusCode::CONFLICT,
+                "aborted",
+                "Operation was rolled back because another operation in the batch failed".to_string(),
+            ),
+            TaskServiceError::MissingFilter => (StatusCode::BAD_REQUEST, "validation_failed", "Filter must contain at least one condition".to_string()),
+            TaskServiceError::Unsupported => (
+                StatusCode::NOT_IMPLEMENTED,
+                "not_supported",
+                "Operation is not supported by the configured repository".to_string(),
+            ),
+            TaskServiceError::Timeout => (StatusCode::SERVICE_UNAVAILABLE, "timeout", "Storage did not respond in time".to_string()),
+            TaskServiceError::Malformed(reason) => (StatusCode::BAD_REQUEST, "malformed_input", format!("Input could not be parsed: {}", reason)),
+            TaskServiceError::UnexpectedError => (StatusCode::INTERNAL_SERVER_ERROR, "unexpected_error", "An unexpected error occurred".to_string()),
+        };
+        let mut response = ApiErrorResponse::new(status, code, detail);
+        match e {
+            TaskServiceError::Validation(errors) => response.errors = errors.iter().map(FieldErrorResponse::from).collect(),
+            TaskServiceError::InvalidPatch(error) => response.errors = vec![FieldErrorResponse::from(error)],
+            _ => {}
         }
+        response
+    }
+}
+
+impl From<ApiErrorResponse> for HttpResponse {
+    fn from(response: ApiErrorResponse) -> Self {
+        let status = StatusCode::from_u16(response.status).unwrap_or(StatusCode::INTERNAL_SERVER_ERROR);
+        HttpResponse::build(status).content_type(PROBLEM_CONTENT_TYPE).json(response)
+    }
+}
+
+impl From<TaskServiceError> for HttpResponse {
+    fn from(e: TaskServiceError) -> Self {
+        HttpResponse::from(ApiErrorResponse::from(&e))
+    }
+}
+
+impl From<WebhookError> for HttpResponse {
+    fn from(e: WebhookError) -> Self {
+        let response = match e {
+            WebhookError::InvalidUrl(url) => {
+                let mut response = ApiErrorResponse::new(StatusCode::BAD_REQUEST, "validation_failed", "Webhook fields are invalid".to_string());
+                let message = format!("{:?} is not an http or https URL", url);
+                response.errors = vec![FieldErrorResponse { field: "url".to_string(), code: "invalid_value".to_string(), message }];
+                response
+            }
+            WebhookError::PrivateHost(url) => {
+                let mut response = ApiErrorResponse::new(StatusCode::BAD_REQUEST, "validation_failed", "Webhook fields are invalid".to_string());
+                let message = format!("{:?} points to a loopback, link-local or private address", url);
+                response.errors = vec![FieldErrorResponse { field: "url".to_string(), code: "private_host".to_string(), message }];
+                response
+            }
+            WebhookError::MissingSecret => {
+                let mut response = ApiErrorResponse::new(StatusCode::BAD_REQUEST, "validation_failed", "Webhook fields are invalid".to_string());
+                let message = "Secret is required".to_string();
+                response.errors = vec![FieldErrorResponse { field: "secret".to_string(), code: "required".to_string(), message }];
+                response
+            }
+            WebhookError::NotFound => ApiErrorResponse::new(StatusCode::NOT_FOUND, "not_found", "Webhook not found".to_string()),
+            WebhookError::UnexpectedError => {
+                ApiErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "unexpected_error", "An unexpected error occurred".to_string())
+            }
+        };
+        HttpResponse::from(response)
     }
 }
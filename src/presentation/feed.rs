@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use actix_web::{HttpRequest, HttpResponse, Responder, http::{StatusCode, header}, web};
+use actix_ws::Message;
+use futures_util::StreamExt;
+use tokio::time::{Instant, interval_at};
+
+use crate::application::feed::{ChangeFeed, FeedFilter, FeedItem};
+use crate::presentation::dto::{ApiErrorResponse, EventResponse, FeedQuery};
+
+// Заголовок, с которым браузер переподключается к потоку SSE
+const LAST_EVENT_ID: &str = "Last-Event-ID";
+// Как часто в тихий поток SSE отправляется комментарий, чтобы прокси не закрывали соединение
+const KEEP_ALIVE: Duration = Duration::from_secs(15);
+const RESET: &str = r#"{"type":"reset"}"#;
+
+// Событие ленты в JSON; то же тело получают подписчики webhooks
+pub fn message(item: FeedItem) -> String {
+    match item {
+        FeedItem::Event(event) => serde_json::to_string(&EventResponse::from(event)).unwrap_or_default(),
+        FeedItem::Reset => RESET.to_string(),
+    }
+}
+
+// Событие ленты в формате text/event-stream: имя события - вид изменения
+fn frame(item: FeedItem) -> web::Bytes {
+    let frame = match &item {
+        FeedItem::Event(event) => format!("id: {}\nevent: {}\n", event.id, event.kind.as_str()),
+        FeedItem::Reset => "event: reset\n".to_string(),
+    };
+    web::Bytes::from(frame + "data: " + &message(item) + "\n\n")
+}
+
+#[utoipa::path(
+    get,
+    path = "/events",
+    tag = "events",
+    summary = "Stream task changes as Server-Sent Events",
+    description = "Each change is an SSE event named after its `type`, with the event number as `id` and \
+        an `EventResponse` as `data`. A reconnecting client sends `Last-Event-ID` (or `after`) and receives \
+        the changes it missed; if they are no longer kept, a `reset` event tells it to reload the task list.",
+    params(
+        FeedQuery,
+        ("Last-Event-ID" = Option<u64>, Header, description = "Number of the last event the client received"),
+    ),
+    responses(
+        (status = 200, description = "Endless stream of events", body = EventResponse, content_type = "text/event-stream"),
+        (status = 400, description = "Malformed query", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn events(req: HttpRequest, change_feed: web::Data<ChangeFeed>, query: web::Query<FeedQuery>) -> impl Responder {
+    let after = req
+        .headers()
+        .get(LAST_EVENT_ID)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.trim().parse().ok())
+        .or(query.after);
+    println!("events after: {:?}, tag: {:?}, project: {:?}", after, query.tag, query.project);
+    let subscription = change_feed.subscribe(after, FeedFilter::from(&*query));
+    let keep_alive = interval_at(Instant::now() + KEEP_ALIVE, KEEP_ALIVE);
+    let stream = futures_util::stream::unfold((subscription, keep_alive), |(mut subscription, mut keep_alive)| async move {
+        let chunk = tokio::select! {
+            item = subscription.next() => frame(item?),
+            _ = keep_alive.tick() => web::Bytes::from_static(b": keep-alive\n\n"),
+        };
+        Some((Ok::<_, actix_web::Error>(chunk), (subscription, keep_alive)))
+    });
+    HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header((header::CACHE_CONTROL, "no-cache"))
+        .streaming(stream)
+}
+
+#[utoipa::path(
+    get,
+    path = "/events/ws",
+    tag = "events",
+    summary = "Stream task changes over a WebSocket",
+    description = "After the upgrade every change arrives as a text message with an `EventResponse`, \
+        or `{\"type\": \"reset\"}` if some changes were lost. Messages from the client are ignored.",
+    params(FeedQuery),
+    responses(
+        (status = 101, description = "Switched to the WebSocket protocol"),
+        (status = 400, description = "Not a WebSocket handshake, or malformed query", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn events_ws(
+    req: HttpRequest,
+    body: web::Payload,
+    change_feed: web::Data<ChangeFeed>,
+    query: web::Query<FeedQuery>,
+) -> impl Responder {
+    let (response, mut session, mut messages) = match actix_ws::handle(&req, body) {
+        Ok(parts) => parts,
+        Err(e) => {
+            eprintln!("Error opening WebSocket: {}", e);
+            return HttpResponse::from(ApiErrorResponse::new(StatusCode::BAD_REQUEST, "websocket_required", e.to_string()));
+        }
+    };
+    println!("events_ws after: {:?}, tag: {:?}, project: {:?}", query.after, query.tag, query.project);
+    let mut subscription = change_feed.subscribe(query.after, FeedFilter::from(&*query));
+    actix_web::rt::spawn(async move {
+        loop {
+            tokio::select! {
+                received = messages.next() => match received {
+                    Some(Ok(Message::Ping(bytes))) => {
+                        if session.pong(&bytes).await.is_err() {
+                            return;
+                        }
+                    }
+                    Some(Ok(Message::Close(_)) | Err(_)) | None => break,
+                    Some(Ok(_)) => {}
+                },
+                item = subscription.next() => {
+                    let Some(item) = item else { break };
+                    if session.text(message(item)).await.is_err() {
+                        return;
+                    }
+                }
+            }
+        }
+        let _ = session.close(None).await;
+    });
+    response
+}
+
+// Проверяем доставку изменений через SSE и WebSocket
+#[cfg(test)]
+mod feed_tests {
+    use std::future::poll_fn;
+    use std::pin::Pin;
+
+    use actix_web::body::MessageBody;
+    use actix_web::dev::Payload;
+    use actix_web::{App, http::StatusCode, test, web};
+    use serde_json::{Value, json};
+    use tokio::sync::Mutex;
+
+    use crate::application::feed::ChangeFeed;
+    use crate::application::services::{TaskDraft, TaskService};
+    use crate::domain::queries::{TaskFilter, TaskMutation};
+    use crate::infrastructure::in_memory::InMemoryTaskRepository;
+    use crate::presentation::web_controller;
+
+    // Сервис задач и его лента изменений, как их регистрирует main
+    fn service() -> (web::Data<Mutex<TaskService>>, web::Data<ChangeFeed>) {
+        let service = TaskService::new(Box::new(InMemoryTaskRepository::new()));
+        let feed = web::Data::new(service.feed());
+        (web::Data::new(Mutex::new(service)), feed)
+    }
+
+    fn draft(title: &str) -> TaskDraft {
+        TaskDraft { title: title.to_string(), description: "Desc".to_string(), ..Default::default() }
+    }
+
+    // Следующий кусок бесконечного тела ответа
+    async fn next_chunk(body: &mut (impl MessageBody + Unpin)) -> String {
+        let chunk = poll_fn(|cx| Pin::new(&mut *body).poll_next(cx)).await.unwrap().ok().unwrap();
+        String::from_utf8_lossy(&chunk).into_owned()
+    }
+
+    #[tokio::test]
+    async fn sse_resumes_after_last_event_id() {
+        // Проверяем, что после переподключения приходят пропущенные события, а затем новые
+        let (service, feed) = service();
+        let app = test::init_service(App::new().app_data(service.clone()).app_data(feed).configure(web_controller::configure)).await;
+        for title in ["First", "Second"] {
+            service.lock().await.create("test", draft(title)).await.unwrap();
+        }
+
+        let request = test::TestRequest::get().uri("/api/v1/events").insert_header(("Last-Event-ID", "1")).to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "text/event-stream");
+        let mut body = response.into_body();
+        let chunk = next_chunk(&mut body).await;
+        let (head, data) = chunk.split_once("data: ").unwrap();
+        assert_eq!(head, "id: 2\nevent: created\n");
+        let event: Value = serde_json::from_str(data.trim_end()).unwrap();
+        assert_eq!(event, json!({ "id": 2, "type": "created", "task": { "id": 2, "title": "Second", "description": "Desc", "status": false, "tags": [] } }));
+
+        service.lock().await.toggle("test", 2).await.unwrap();
+        assert!(next_chunk(&mut body).await.starts_with("id: 3\nevent: toggled\n"));
+
+        // Номер из прошлого запуска сервера
+        let request = test::TestRequest::get().uri("/api/v1/events?after=10").to_request();
+        let mut body = test::call_service(&app, request).await.into_body();
+        assert_eq!(next_chunk(&mut body).await, "event: reset\ndata: {\"type\":\"reset\"}\n\n");
+    }
+
+    #[actix_web::test]
+    async fn websocket_filters_by_tag() {
+        // Проверяем, что подписчик WebSocket получает только изменения задач с нужной меткой
+        let (service, feed) = service();
+        let app = test::init_service(App::new().app_data(service.clone()).app_data(feed).configure(web_controller::configure)).await;
+
+        let request = test::TestRequest::get()
+            .uri("/api/v1/events/ws?tag=work")
+            .insert_header(("connection", "upgrade"))
+            .insert_header(("upgrade", "websocket"))
+            .insert_header(("sec-websocket-version", "13"))
+            .insert_header(("sec-websocket-key", "dGhlIHNhbXBsZSBub25jZQ=="))
+            .to_request();
+        // Клиент держит соединение открытым и ничего не присылает
+        let silent: Payload = Payload::Stream { payload: Box::pin(futures_util::stream::pending()) };
+        let (request, _) = request.replace_payload(silent);
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::SWITCHING_PROTOCOLS);
+        let mut body = response.into_body();
+
+        let task = service.lock().await.create("test", draft("Plain")).await.unwrap();
+        let mutation = TaskMutation::AddTag("work".to_string());
+        let filter = TaskFilter { status: Some(false), ..Default::default() };
+        service.lock().await.bulk_update("test", filter, mutation, false).await.unwrap();
+
+        // Кадр сервера без маски: байт FIN и кода, длина, затем текст
+        let frame = next_chunk(&mut body).await;
+        let event: Value = serde_json::from_str(&frame[frame.find('{').unwrap()..]).unwrap();
+        assert_eq!((event["id"].as_u64(), event["type"].as_str()), (Some(2), Some("updated")));
+        assert_eq!(event["task"]["id"], task.id);
+        assert_eq!(event["task"]["tags"], json!(["work"]));
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/events/ws").to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+}
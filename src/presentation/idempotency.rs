@@ -0,0 +1,369 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use actix_web::body::{BoxBody, MessageBody, to_bytes};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::header::{HeaderMap, HeaderName, HeaderValue};
+use actix_web::http::{StatusCode, header};
+use actix_web::middleware::Next;
+use actix_web::{HttpResponse, web};
+use sha2::{Digest, Sha256};
+
+use crate::presentation::dto::ApiErrorResponse;
+use crate::presentation::session::SESSION_HEADER;
+
+// Заголовок, которым клиент помечает повторы одного и того же запроса
+pub const IDEMPOTENCY_HEADER: &str = "Idempotency-Key";
+// Заголовок повторно отданного сохраненного ответа
+const REPLAYED_HEADER: HeaderName = HeaderName::from_static("idempotent-replayed");
+const MAX_KEY_LENGTH: usize = 255;
+// Сколько хранится ответ на запрос с ключом по умолчанию
+pub const DEFAULT_WINDOW: Duration = Duration::from_secs(24 * 60 * 60);
+// Как часто из хранилища удаляются все устаревшие ключи
+const SWEEP_INTERVAL: Duration = Duration::from_secs(60);
+
+// Ответ, сохраненный для повтора
+#[derive(Clone)]
+struct StoredResponse {
+    status: StatusCode,
+    headers: HeaderMap,
+    body: web::Bytes,
+}
+
+impl StoredResponse {
+    fn replay(&self) -> HttpResponse {
+        let mut response = HttpResponse::build(self.status).body(self.body.clone());
+        for (name, value) in &self.headers {
+            response.headers_mut().append(name.clone(), value.clone());
+        }
+        response.headers_mut().insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+struct Entry {
+    fingerprint: Vec<u8>, // Хеш метода, пути, типа и тела запроса
+    created: Instant,
+    response: Option<StoredResponse>, // Пусто, пока запрос выполняется
+}
+
+// Чем закончилась попытка начать запрос с ключом
+enum Attempt {
+    Started,
+    Replay(StoredResponse),
+    InProgress,
+    Mismatch,
+}
+
+struct Entries {
+    map: HashMap<(String, String), Entry>,
+    swept: Instant, // Когда устаревшие ключи удалялись в последний раз
+}
+
+// Ответы на POST-запросы с заголовком Idempotency-Key. Ключ действует в пределах сессии
+// из заголовка X-Session-Id (или общего пространства, если его нет) в течение окна хранения: повтор с тем же телом получает сохраненный ответ,
+// не выполняясь заново, а другое тело с тем же ключом отклоняется.
+pub struct IdempotencyStore {
+    window: Duration,
+    entries: Mutex<Entries>,
+}
+
+impl IdempotencyStore {
+    pub fn new(window: Duration) -> Self {
+        IdempotencyStore { window, entries: Mutex::new(Entries { map: HashMap::new(), swept: Instant::now() }) }
+    }
+
+    fn begin(&self, scope: &(String, String), fingerprint: Vec<u8>) -> Attempt {
+        let mut entries = self.entries.lock().unwrap();
+        // Устаревший ключ запроса забывается сразу, остальные - не чаще раза в SWEEP_INTERVAL,
+        // чтобы не перебирать все ключи на каждом запросе
+        if entries.swept.elapsed() >= SWEEP_INTERVAL {
+            entries.map.retain(|_, entry| entry.created.elapsed() < self.window);
+            entries.swept = Instant::now();
+        } else if entries.map.get(scope).is_some_and(|entry| entry.created.elapsed() >= self.window) {
+            entries.map.remove(scope);
+        }
+        let entries = &mut entries.map;
+        match entries.get(scope) {
+            Some(entry) if entry.fingerprint != fingerprint => Attempt::Mismatch,
+            Some(Entry { response: Some(response), .. }) => Attempt::Replay(response.clone()),
+            Some(_) => Attempt::InProgress,
+            None => {
+                entries.insert(scope.clone(), Entry { fingerprint, created: Instant::now(), response: None });
+                Attempt::Started
+            }
+        }
+    }
+
+    // Сохраняет ответ; без ответа ключ освобождается и следующий повтор выполнится заново
+    fn finish(&self, scope: &(String, String), response: Option<StoredResponse>) {
+        let entries = &mut self.entries.lock().unwrap().map;
+        match response {
+            Some(response) => {
+                if let Some(entry) = entries.get_mut(scope) {
+                    entry.response = Some(response);
+                }
+            }
+            None => {
+                entries.remove(scope);
+            }
+        }
+    }
+}
+
+// Ключ запроса, который сейчас выполняется. Если обработчик не дошел до ответа - ошибка,
+// отключение клиента и отмена future запроса, - ключ освобождается при удалении, иначе все
+// повторы получали бы 409 до конца окна хранения.
+struct Pending {
+    store: web::Data<IdempotencyStore>,
+    scope: (String, String),
+    finished: bool,
+}
+
+impl Pending {
+    fn finish(mut self, response: Option<StoredResponse>) {
+        self.finished = true;
+        self.store.finish(&self.scope, response);
+    }
+}
+
+impl Drop for Pending {
+    fn drop(&mut self) {
+        if !self.finished {
+            self.store.finish(&self.scope, None);
+        }
+    }
+}
+
+fn fingerprint(req: &ServiceRequest, body: &[u8]) -> Vec<u8> {
+    let mut hasher = Sha256::new();
+    hasher.update(req.method().as_str());
+    hasher.update(b"\n");
+    hasher.update(req.path());
+    hasher.update(b"\n");
+    hasher.update(req.headers().get(header::CONTENT_TYPE).map(HeaderValue::as_bytes).unwrap_or_default());
+    hasher.update(b"\n");
+    hasher.update(body);
+    hasher.finalize().to_vec()
+}
+
+fn reject(req: ServiceRequest, status: StatusCode, code: &str, detail: &str) -> ServiceResponse<BoxBody> {
+    eprintln!("Rejecting idempotent request: {detail}");
+    req.into_response(HttpResponse::from(ApiErrorResponse::new(status, code, detail.to_string())))
+}
+
+// Промежуточный обработчик POST-маршрутов, создающих задачи. Без заголовка Idempotency-Key
+// или без IdempotencyStore в данных приложения запрос проходит как есть. Ответы 5xx
+// не сохраняются: после сбоя или тайм-аута хранилища повтор должен выполниться заново.
+pub async fn idempotent(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, actix_web::Error> {
+    let key = req.headers().get(IDEMPOTENCY_HEADER).cloned();
+    let store = req.app_data::<web::Data<IdempotencyStore>>().cloned();
+    let (Some(key), Some(store)) = (key, store) else {
+        return next.call(req).await.map(ServiceResponse::map_into_boxed_body);
+    };
+    let key = match key.to_str() {
+        Ok(key) if !key.is_empty() && key.len() <= MAX_KEY_LENGTH => key.to_string(),
+        _ => {
+            let detail = format!("{} must be 1 to {} visible ASCII characters", IDEMPOTENCY_HEADER, MAX_KEY_LENGTH);
+            return Ok(reject(req, StatusCode::BAD_REQUEST, "invalid_idempotency_key", &detail));
+        }
+    };
+    // Адрес клиента в область ключа не входит: повтор после смены сети должен найти свой ответ
+    let session = req.headers().get(SESSION_HEADER).and_then(|value| value.to_str().ok()).unwrap_or_default().to_string();
+    let body = match req.extract::<web::Bytes>().await {
+        Ok(body) => body,
+        Err(e) => return Ok(reject(req, StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large", &e.to_string())),
+    };
+    let scope = (session, key);
+    match store.begin(&scope, fingerprint(&req, &body)) {
+        Attempt::Started => {}
+        Attempt::Replay(response) => {
+            println!("Replaying response for {} {}", IDEMPOTENCY_HEADER, scope.1);
+            return Ok(req.into_response(response.replay()));
+        }
+        Attempt::InProgress => {
+            let detail = format!("Request with {} {} is still being processed", IDEMPOTENCY_HEADER, scope.1);
+            return Ok(reject(req, StatusCode::CONFLICT, "idempotency_key_in_use", &detail));
+        }
+        Attempt::Mismatch => {
+            let detail = format!("{} {} was already used for a different request", IDEMPOTENCY_HEADER, scope.1);
+            return Ok(reject(req, StatusCode::UNPROCESSABLE_ENTITY, "idempotency_key_reused", &detail));
+        }
+    }
+    let pending = Pending { store, scope, finished: false };
+    // Тело прочитано для отпечатка, обработчику отдается его копия
+    req.set_payload(Payload::from(body));
+
+    let response = next.call(req).await?;
+    let (request, response) = response.into_parts();
+    let (head, body) = response.into_parts();
+    let Ok(body) = to_bytes(body).await else {
+        let error = ApiErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "unexpected_error", "Response could not be stored".to_string());
+        return Ok(ServiceResponse::new(request, HttpResponse::from(error)));
+    };
+    let stored = StoredResponse { status: head.status(), headers: head.headers().clone(), body: body.clone() };
+    pending.finish(Some(stored).filter(|stored| !stored.status.is_server_error()));
+    Ok(ServiceResponse::new(request, head.set_body(BoxBody::new(body))))
+}
+
+// Проверяем повтор ответов по ключу идемпотентности на создании задач и пакетах
+#[cfg(test)]
+mod idempotency_tests {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    use actix_web::middleware::from_fn;
+    use actix_web::{App, HttpResponse, http::StatusCode, test, web};
+    use serde_json::{Value, json};
+    use tokio::sync::Mutex;
+
+    use crate::application::services::TaskService;
+    use crate::infrastructure::chaos::{ChaosConfig, ChaosOperation, ChaosTaskRepository};
+    use crate::infrastructure::in_memory::InMemoryTaskRepository;
+    use crate::presentation::idempotency::{IDEMPOTENCY_HEADER, IdempotencyStore, idempotent};
+    use crate::presentation::web_controller;
+
+    fn service(config: ChaosConfig) -> web::Data<Mutex<TaskService>> {
+        let repository = ChaosTaskRepository::new(InMemoryTaskRepository::new(), config);
+        web::Data::new(Mutex::new(TaskService::new(Box::new(repository))))
+    }
+
+    fn create(key: &str, title: &str) -> test::TestRequest {
+        test::TestRequest::post()
+            .uri("/api/v1/tasks")
+            .insert_header((IDEMPOTENCY_HEADER, key))
+            .set_json(json!({ "title": title, "description": "Desc" }))
+    }
+
+    #[tokio::test]
+    async fn retry_replays_stored_response() {
+        // Проверяем, что повтор с тем же ключом отдает тот же ответ, не создавая вторую задачу,
+        // даже если адрес клиента сменился
+        let store = web::Data::new(IdempotencyStore::new(Duration::from_secs(60)));
+        let app = test::init_service(App::new().app_data(service(ChaosConfig::default())).app_data(store).configure(web_controller::configure)).await;
+        let count = || async {
+            let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks").to_request()).await;
+            let body: Value = test::read_body_json(response).await;
+            body.as_array().unwrap().len()
+        };
+
+        let first = test::call_service(&app, create("retry-1", "New").peer_addr("10.0.0.2:40000".parse().unwrap()).to_request()).await;
+        assert_eq!(first.status(), StatusCode::CREATED);
+        let location = first.headers().get("location").cloned();
+        let first = test::read_body(first).await;
+
+        // Повтор приходит с другого адреса, как после переключения на мобильную сеть
+        let retry = test::call_service(&app, create("retry-1", "New").peer_addr("172.16.5.9:51000".parse().unwrap()).to_request()).await;
+        assert_eq!(retry.status(), StatusCode::CREATED);
+        assert_eq!(retry.headers().get("location").cloned(), location);
+        assert_eq!(retry.headers().get("idempotent-replayed").unwrap(), "true");
+        assert_eq!(test::read_body(retry).await, first);
+        assert_eq!(count().await, 1);
+
+        // Другой ключ - другой запрос
+        let other = test::call_service(&app, create("retry-2", "New").to_request()).await;
+        assert_eq!(other.status(), StatusCode::CREATED);
+        assert!(other.headers().get("idempotent-replayed").is_none());
+        assert_eq!(count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn reused_key_with_other_body_is_rejected() {
+        // Проверяем 422 для того же ключа с другим телом и отдельные ключи у разных сессий
+        let store = web::Data::new(IdempotencyStore::new(Duration::from_secs(60)));
+        let app = test::init_service(App::new().app_data(service(ChaosConfig::default())).app_data(store).configure(web_controller::configure)).await;
+        let count = || async {
+            let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks").to_request()).await;
+            let body: Value = test::read_body_json(response).await;
+            body.as_array().unwrap().len()
+        };
+
+        test::call_service(&app, create("key", "First").to_request()).await;
+        let response = test::call_service(&app, create("key", "Second").to_request()).await;
+        assert_eq!(response.status(), StatusCode::UNPROCESSABLE_ENTITY);
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/problem+json");
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body["code"], "idempotency_key_reused");
+
+        let batch = json!({ "mode": "best_effort", "operations": [{ "op": "toggle", "id": 1 }] });
+        let request = test::TestRequest::post().uri("/api/v1/tasks/batch").insert_header((IDEMPOTENCY_HEADER, "key")).set_json(batch);
+        assert_eq!(test::call_service(&app, request.to_request()).await.status(), StatusCode::UNPROCESSABLE_ENTITY);
+
+        let response = test::call_service(&app, create("key", "Second").insert_header(("X-Session-Id", "phone")).to_request()).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+
+        let response = test::call_service(&app, create("", "Third").to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(count().await, 2);
+    }
+
+    #[tokio::test]
+    async fn batch_is_replayed_and_key_expires() {
+        // Проверяем, что пакет не выполняется повторно, пока ключ не устарел
+        let store = web::Data::new(IdempotencyStore::new(Duration::from_millis(100)));
+        let app = test::init_service(App::new().app_data(service(ChaosConfig::default())).app_data(store).configure(web_controller::configure)).await;
+        test::call_service(&app, create("task", "Task").to_request()).await;
+
+        let batch = || {
+            let body = json!({ "mode": "best_effort", "operations": [{ "op": "toggle", "id": 1 }] });
+            test::TestRequest::post().uri("/api/v1/tasks/batch").insert_header((IDEMPOTENCY_HEADER, "toggle")).set_json(body).to_request()
+        };
+        let status = || async {
+            let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/1").to_request()).await;
+            let body: Value = test::read_body_json(response).await;
+            body["status"].as_bool().unwrap()
+        };
+        assert_eq!(test::call_service(&app, batch()).await.status(), StatusCode::OK);
+        assert!(status().await);
+        assert_eq!(test::call_service(&app, batch()).await.status(), StatusCode::OK);
+        assert!(status().await);
+
+        tokio::time::sleep(Duration::from_millis(150)).await;
+        assert_eq!(test::call_service(&app, batch()).await.status(), StatusCode::OK);
+        assert!(!status().await);
+    }
+
+    #[tokio::test]
+    async fn server_errors_are_not_stored() {
+        // Проверяем, что после сбоя хранилища повтор с тем же ключом выполняется заново
+        let config = ChaosConfig { seed: 1, failure_rate: 1.0, operations: vec![ChaosOperation::Create], ..Default::default() };
+        let store = web::Data::new(IdempotencyStore::new(Duration::from_secs(60)));
+        let failing = test::init_service(App::new().app_data(service(config)).app_data(store.clone()).configure(web_controller::configure)).await;
+        let response = test::call_service(&failing, create("flaky", "New").to_request()).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+
+        let app = test::init_service(App::new().app_data(service(ChaosConfig::default())).app_data(store).configure(web_controller::configure)).await;
+        let response = test::call_service(&app, create("flaky", "New").to_request()).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(response.headers().get("idempotent-replayed").is_none());
+    }
+
+    // Первый запрос не получает ответа никогда, следующие создают задачу
+    async fn stalls_once(calls: web::Data<AtomicUsize>) -> HttpResponse {
+        if calls.fetch_add(1, Ordering::SeqCst) == 0 {
+            std::future::pending::<()>().await;
+        }
+        HttpResponse::Created().finish()
+    }
+
+    #[tokio::test]
+    async fn abandoned_request_releases_key() {
+        // Проверяем, что ключ освобождается, если клиент отключился, не дождавшись ответа
+        let store = web::Data::new(IdempotencyStore::new(Duration::from_secs(60)));
+        let calls = web::Data::new(AtomicUsize::new(0));
+        let route = web::post().to(stalls_once).wrap(from_fn(idempotent));
+        let app = test::init_service(App::new().app_data(store).app_data(calls.clone()).route("/slow", route)).await;
+        let request = || test::TestRequest::post().uri("/slow").insert_header((IDEMPOTENCY_HEADER, "slow")).set_payload("body").to_request();
+
+        // При отключении клиента actix удаляет future запроса, не дождавшись ответа
+        let abandoned = tokio::time::timeout(Duration::from_millis(50), test::call_service(&app, request())).await;
+        assert!(abandoned.is_err());
+        let response = test::call_service(&app, request()).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(calls.load(Ordering::SeqCst), 2);
+    }
+}
@@ -0,0 +1,344 @@
+use std::cmp::Reverse;
+use std::future::Future;
+use std::pin::Pin;
+
+use actix_web::error::InternalError;
+use actix_web::http::header::{self, Accept, Header, HeaderValue, Quality, QualityItem};
+use actix_web::http::StatusCode;
+use actix_web::{FromRequest, HttpRequest, HttpResponse, dev::Payload, mime::Mime, web};
+use serde::Serialize;
+use serde::de::DeserializeOwned;
+use ciborium::Value as CborValue;
+use serde_json::Value;
+
+use crate::presentation::dto::ApiErrorResponse;
+
+// Согласование формата тел запросов и ответов задач: формат ответа выбирается по Accept,
+// формат тела запроса - по Content-Type. Ошибки всегда отдаются как problem+json.
+
+// Формат тела запроса или ответа
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MediaFormat {
+    Json, // JSON с отступами; формат по умолчанию
+    MessagePack, // MessagePack, объекты - словари с именами полей
+    Cbor, // CBOR (RFC 8949)
+    Yaml, // YAML
+    Csv, // Таблица с заголовком; списки в ячейке через `;`, словари - парами `key:value`
+}
+
+// Поддерживаемые форматы в порядке предпочтения сервера
+const FORMATS: [MediaFormat; 5] = [MediaFormat::Json, MediaFormat::MessagePack, MediaFormat::Cbor, MediaFormat::Yaml, MediaFormat::Csv];
+
+// Поддерживаемые форматы для ответов 406 и 415
+const SUPPORTED: &str = "application/json, application/msgpack, application/cbor, application/yaml, text/csv";
+
+impl MediaFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            MediaFormat::Json => "application/json",
+            MediaFormat::MessagePack => "application/msgpack",
+            MediaFormat::Cbor => "application/cbor",
+            MediaFormat::Yaml => "application/yaml",
+            MediaFormat::Csv => "text/csv; charset=utf-8",
+        }
+    }
+
+    // Имена типа содержимого без параметров, включая распространенные нестандартные
+    fn essences(&self) -> &'static [&'static str] {
+        match self {
+            MediaFormat::Json => &["application/json"],
+            MediaFormat::MessagePack => &["application/msgpack", "application/x-msgpack", "application/vnd.msgpack"],
+            MediaFormat::Cbor => &["application/cbor"],
+            MediaFormat::Yaml => &["application/yaml", "application/x-yaml", "text/yaml", "text/x-yaml"],
+            MediaFormat::Csv => &["text/csv"],
+        }
+    }
+
+    // Формат по типу содержимого без параметров; шаблоны не подходят ни одному формату
+    fn from_mime(essence: &str) -> Option<MediaFormat> {
+        FORMATS.into_iter().find(|format| format.essences().contains(&essence))
+    }
+
+    // Вес формата и номер диапазона Accept, который его задает. Из подходящих диапазонов
+    // берется самый точный: имя формата, затем `<тип>/*`, затем `*/*`.
+    fn quality(&self, accept: &[QualityItem<Mime>]) -> Option<(Quality, usize)> {
+        let main = self.content_type().split('/').next().unwrap_or_default();
+        accept
+            .iter()
+            .enumerate()
+            .filter_map(|(index, range)| {
+                let essence = range.item.essence_str();
+                let precision = if self.essences().contains(&essence) {
+                    2
+                } else if essence.strip_suffix("/*") == Some(main) {
+                    1
+                } else if essence == "*/*" {
+                    0
+                } else {
+                    return None;
+                };
+                Some((precision, Reverse(index), range.quality))
+            })
+            .max_by_key(|(precision, index, _)| (*precision, *index))
+            .map(|(_, Reverse(index), quality)| (quality, index))
+    }
+
+    // Самый предпочтительный для клиента из поддерживаемых форматов; None, если клиент
+    // не принимает ни один из них. Без заголовка Accept ответ отдается в JSON.
+    // При равных весах выигрывает диапазон, указанный клиентом раньше, а под одним
+    // шаблоном - формат, стоящий раньше в FORMATS.
+    fn negotiate(req: &HttpRequest) -> Option<MediaFormat> {
+        let Ok(accept) = Accept::parse(req) else {
+            return Some(MediaFormat::Json);
+        };
+        if accept.is_empty() {
+            return Some(MediaFormat::Json);
+        }
+        FORMATS
+            .into_iter()
+            .filter_map(|format| format.quality(&accept).map(|(quality, index)| (format, quality, index)))
+            .filter(|(_, quality, _)| *quality > Quality::ZERO)
+            .min_by_key(|(_, quality, index)| (Reverse(*quality), *index))
+            .map(|(format, _, _)| format)
+    }
+
+    pub fn serialize(&self, value: &impl Serialize) -> Result<Vec<u8>, String> {
+        match self {
+            MediaFormat::Json => serde_json::to_vec_pretty(value).map_err(|e| e.to_string()),
+            MediaFormat::MessagePack => rmp_serde::to_vec_named(value).map_err(|e| e.to_string()),
+            MediaFormat::Cbor => {
+                let mut body = Vec::new();
+                ciborium::into_writer(value, &mut body).map_err(|e| e.to_string())?;
+                Ok(body)
+            }
+            MediaFormat::Yaml => serde_yaml_ng::to_string(value).map(String::into_bytes).map_err(|e| e.to_string()),
+            MediaFormat::Csv => to_csv(value),
+        }
+    }
+
+    pub fn deserialize<T: DeserializeOwned>(&self, body: &[u8]) -> Result<T, String> {
+        match self {
+            MediaFormat::Json => serde_json::from_slice(body).map_err(|e| e.to_string()),
+            MediaFormat::MessagePack => rmp_serde::from_slice(body).map_err(|e| e.to_string()),
+            MediaFormat::Cbor => ciborium::from_reader(body).map_err(|e| e.to_string()),
+            MediaFormat::Yaml => serde_yaml_ng::from_slice(body).map_err(|e| e.to_string()),
+            MediaFormat::Csv => serde_json::from_value(from_csv(body)?).map_err(|e| e.to_string()),
+        }
+    }
+}
+
+// Таблица из объекта или массива объектов. Столбцы - все поля в порядке появления,
+// так что необязательные поля, заполненные не у всех задач, тоже попадают в заголовок.
+// Значение проходит через словари CBOR: они, в отличие от serde_json, хранят поля
+// в порядке сериализации, и столбцы идут в порядке полей DTO.
+fn to_csv(value: &impl Serialize) -> Result<Vec<u8>, String> {
+    let value = CborValue::serialized(value).map_err(|e| e.to_string())?;
+    let rows: Vec<&Vec<(CborValue, CborValue)>> = match &value {
+        CborValue::Array(items) => items.iter().filter_map(CborValue::as_map).collect(),
+        CborValue::Map(entries) => vec![entries],
+        _ => return Err("only objects can be written as CSV".to_string()),
+    };
+    let mut columns: Vec<String> = Vec::new();
+    for row in &rows {
+        for (key, _) in row.iter() {
+            let key = cell(key);
+            if !columns.contains(&key) {
+                columns.push(key);
+            }
+        }
+    }
+    let mut csv = csv::Writer::from_writer(Vec::new());
+    csv.write_record(&columns).map_err(|e| e.to_string())?;
+    for row in rows {
+        let cells = columns.iter().map(|column| {
+            row.iter().find(|(key, _)| key.as_text() == Some(column.as_str())).map(|(_, value)| cell(value)).unwrap_or_default()
+        });
+        csv.write_record(cells).map_err(|e| e.to_string())?;
+    }
+    csv.into_inner().map_err(|e| e.to_string())
+}
+
+fn cell(value: &CborValue) -> String {
+    match value {
+        CborValue::Null => String::new(),
+        CborValue::Text(value) => value.clone(),
+        CborValue::Bool(value) => value.to_string(),
+        CborValue::Integer(value) => i128::from(*value).to_string(),
+        CborValue::Float(value) => value.to_string(),
+        CborValue::Bytes(bytes) => bytes.iter().map(|byte| format!("{:02x}", byte)).collect(),
+        CborValue::Tag(_, value) => cell(value),
+        CborValue::Array(items) => items.iter().map(cell).collect::<Vec<_>>().join(";"),
+        CborValue::Map(entries) => entries.iter().map(|(key, value)| format!("{}:{}", cell(key), cell(value))).collect::<Vec<_>>().join(";"),
+        _ => String::new(),
+    }
+}
+
+// Объект из таблицы с заголовком и ровно одной строкой. Значения остаются строками,
+// пустые ячейки пропускаются, как будто поле не указано.
+fn from_csv(body: &[u8]) -> Result<Value, String> {
+    let mut csv = csv::Reader::from_reader(body);
+    let headers = csv.headers().map_err(|e| e.to_string())?.clone();
+    let mut records = csv.records();
+    let record = records.next().ok_or("CSV body has no rows")?.map_err(|e| e.to_string())?;
+    if records.next().is_some() {
+        return Err("CSV body must contain exactly one row".to_string());
+    }
+    let object = headers
+        .iter()
+        .zip(record.iter())
+        .filter(|(_, value)| !value.is_empty())
+        .map(|(column, value)| (column.to_string(), Value::String(value.to_string())))
+        .collect();
+    Ok(Value::Object(object))
+}
+
+fn problem(reason: String, status: StatusCode, code: &str) -> actix_web::Error {
+    eprintln!("Error negotiating format: {reason}");
+    let mut response = HttpResponse::from(ApiErrorResponse::new(status, code, reason.clone()));
+    // Ответ 415 перечисляет допустимые типы в Accept (RFC 9110), ответ 406 - в detail
+    if status == StatusCode::UNSUPPORTED_MEDIA_TYPE {
+        response.headers_mut().insert(header::ACCEPT, HeaderValue::from_static(SUPPORTED));
+    }
+    InternalError::from_response(reason, response).into()
+}
+
+// Формат ответа, выбранный по заголовку Accept. Если клиент не принимает ни один
+// поддерживаемый формат, запрос отклоняется с 406 до вызова обработчика.
+pub struct ResponseFormat(pub MediaFormat);
+
+impl FromRequest for ResponseFormat {
+    type Error = actix_web::Error;
+    type Future = std::future::Ready<Result<ResponseFormat, actix_web::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        std::future::ready(match MediaFormat::negotiate(req) {
+            Some(format) => Ok(ResponseFormat(format)),
+            None => Err(problem(format!("Response can be produced only as {}", SUPPORTED), StatusCode::NOT_ACCEPTABLE, "not_acceptable")),
+        })
+    }
+}
+
+impl ResponseFormat {
+    // Ответ с телом в выбранном формате
+    pub fn respond(&self, status: StatusCode, value: &impl Serialize) -> HttpResponse {
+        match self.0.serialize(value) {
+            Ok(body) => HttpResponse::build(status)
+                .content_type(self.0.content_type())
+                .insert_header((header::VARY, "Accept"))
+                .body(body),
+            Err(e) => {
+                eprintln!("Error serializing response: {e}");
+                HttpResponse::from(ApiErrorResponse::new(StatusCode::INTERNAL_SERVER_ERROR, "unexpected_error", e))
+            }
+        }
+    }
+}
+
+// Тело запроса в формате из Content-Type; аналог web::Json для всех поддерживаемых форматов
+pub struct Body<T>(pub T);
+
+impl<T> Body<T> {
+    pub fn into_inner(self) -> T {
+        self.0
+    }
+}
+
+impl<T> std::ops::Deref for Body<T> {
+    type Target = T;
+
+    fn deref(&self) -> &T {
+        &self.0
+    }
+}
+
+impl<T: DeserializeOwned + 'static> FromRequest for Body<T> {
+    type Error = actix_web::Error;
+    type Future = Pin<Box<dyn Future<Output = Result<Body<T>, actix_web::Error>>>>;
+
+    fn from_request(req: &HttpRequest, payload: &mut Payload) -> Self::Future {
+        let content_type = req
+            .headers()
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.split(';').next())
+            .map(|value| value.trim().to_ascii_lowercase());
+        let format = content_type.as_deref().and_then(MediaFormat::from_mime);
+        let bytes = web::Bytes::from_request(req, payload);
+        Box::pin(async move {
+            let Some(format) = format else {
+                let reason = format!("Body must be one of {}, not {}", SUPPORTED, content_type.as_deref().unwrap_or("untyped"));
+                return Err(problem(reason, StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported_media_type"));
+            };
+            let bytes = bytes.await.map_err(|e| problem(e.to_string(), StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large"))?;
+            format.deserialize(&bytes).map(Body).map_err(|e| problem(e, StatusCode::BAD_REQUEST, "malformed_input"))
+        })
+    }
+}
+
+// Проверяем выбор формата и преобразование значений в каждый формат и обратно
+#[cfg(test)]
+mod negotiation_tests {
+    use std::collections::HashMap;
+
+    use actix_web::test::TestRequest;
+    use serde::Serialize;
+    use serde_json::{Value, json};
+
+    use crate::presentation::negotiation::MediaFormat;
+
+    // Строка таблицы с полями не по алфавиту и необязательным полем
+    #[derive(Serialize)]
+    struct Row {
+        id: u64,
+        title: &'static str,
+        tags: Vec<&'static str>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        extensions: Option<HashMap<&'static str, &'static str>>,
+    }
+
+    fn negotiate(accept: &str) -> Option<MediaFormat> {
+        MediaFormat::negotiate(&TestRequest::default().insert_header(("accept", accept)).to_http_request())
+    }
+
+    #[tokio::test]
+    async fn accept_header_is_ranked() {
+        // Проверяем веса, шаблоны, запрет через q=0 и отсутствие заголовка
+        assert_eq!(MediaFormat::negotiate(&TestRequest::default().to_http_request()), Some(MediaFormat::Json));
+        assert_eq!(negotiate("application/cbor;q=0.5, application/msgpack"), Some(MediaFormat::MessagePack));
+        assert_eq!(negotiate("text/html, application/yaml;q=0.1"), Some(MediaFormat::Yaml));
+        assert_eq!(negotiate("text/*"), Some(MediaFormat::Csv));
+        assert_eq!(negotiate("application/*"), Some(MediaFormat::Json));
+        assert_eq!(negotiate("application/yaml, application/json"), Some(MediaFormat::Yaml));
+        // Шаблон разрешает только те форматы, которые клиент не исключил
+        assert_eq!(negotiate("application/json;q=0, */*;q=0.1"), Some(MediaFormat::MessagePack));
+        assert_eq!(negotiate("application/json;q=0, application/*"), Some(MediaFormat::MessagePack));
+        assert_eq!(negotiate("text/csv;q=0, text/*"), None);
+        assert_eq!(negotiate("text/csv;q=0.2, */*;q=0.5"), Some(MediaFormat::Json));
+        assert_eq!(negotiate("application/msgpack;q=0, text/html"), None);
+    }
+
+    #[tokio::test]
+    async fn formats_roundtrip() {
+        // Проверяем, что каждый формат читает то, что записал
+        let value = json!({ "title": "Task", "status": true, "tags": ["a"] });
+        for format in [MediaFormat::Json, MediaFormat::MessagePack, MediaFormat::Cbor, MediaFormat::Yaml] {
+            let body = format.serialize(&value).unwrap();
+            assert_eq!(format.deserialize::<Value>(&body).unwrap(), value, "{:?}", format);
+        }
+    }
+
+    #[tokio::test]
+    async fn csv_flattens_and_reads_one_row() {
+        // Проверяем столбцы по всем строкам в порядке полей, склейку списков и словарей и чтение тела из одной строки
+        let value = [
+            Row { id: 1, title: "A, B", tags: vec!["x", "y"], extensions: None },
+            Row { id: 2, title: "C", tags: Vec::new(), extensions: Some(HashMap::from([("k", "v")])) },
+        ];
+        let body = MediaFormat::Csv.serialize(&value).unwrap();
+        assert_eq!(String::from_utf8(body).unwrap(), "id,title,tags,extensions\n1,\"A, B\",x;y,\n2,C,,k:v\n");
+
+        let parsed: Value = MediaFormat::Csv.deserialize(b"title,description,due\nNew,Desc,\n").unwrap();
+        assert_eq!(parsed, json!({ "title": "New", "description": "Desc" }));
+        assert!(MediaFormat::Csv.deserialize::<Value>(b"title\nA\nB\n").is_err());
+    }
+}
@@ -0,0 +1,288 @@
+use actix_web::{HttpResponse, Responder, get, web};
+use utoipa::{Modify, OpenApi};
+use utoipa_redoc::{Redoc, Servable};
+
+use crate::application::export::ExportFormat;
+use crate::application::import::{ConflictMode, ImportFormat};
+use crate::presentation::{feed, web_controller, webhooks};
+
+// Спецификация OpenAPI 3 REST API задач. Строится из аннотаций обработчиков web_controller
+// и DTO; каждая версия API описывается отдельно и вкладывается под своим префиксом.
+// CalDAV сюда не входит - методы WebDAV в OpenAPI не описываются.
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "Clean todo demo",
+        description = "Task list with import, export, batches, undo history and webhooks. \
+            Unversioned paths (`/tasks`, `/task/{id}`, `/undo`, ...) are deprecated aliases of `/api/v1` \
+            and answer with `Deprecation` and `Sunset` headers."
+    ),
+    nest((path = "/api/v1", api = V1Api)),
+    // Схемы параметров запроса сами в спецификацию не попадают
+    components(schemas(ExportFormat, ImportFormat, ConflictMode)),
+    modifiers(&WithoutLicense),
+    tags(
+        (name = "tasks", description = "Tasks and their files"),
+        (name = "history", description = "Undo and redo within a client session"),
+        (name = "events", description = "Real-time feed of task changes"),
+        (name = "webhooks", description = "Signed notifications about task changes sent to subscribed URLs"),
+    )
+)]
+pub struct ApiDoc;
+
+// Версия /api/v1
+#[derive(OpenApi)]
+#[openapi(paths(
+    web_controller::get_all_tasks,
+    web_controller::export_tasks,
+    web_controller::calendar_feed,
+    web_controller::import_tasks,
+    web_controller::get_task_by_id,
+    web_controller::create_task,
+    web_controller::execute_batch,
+    web_controller::bulk_update,
+    web_controller::update_task,
+    web_controller::patch_task,
+    web_controller::delete_task,
+    web_controller::undo,
+    web_controller::redo,
+    feed::events,
+    feed::events_ws,
+    webhooks::create_webhook,
+    webhooks::get_webhooks,
+    webhooks::get_webhook,
+    webhooks::delete_webhook,
+    webhooks::get_deliveries,
+))]
+struct V1Api;
+
+// utoipa берет лицензию из Cargo.toml, а там она не указана
+struct WithoutLicense;
+
+impl Modify for WithoutLicense {
+    fn modify(&self, openapi: &mut utoipa::openapi::OpenApi) {
+        openapi.info.license = None;
+    }
+}
+
+// Спецификация в JSON и ее просмотр в Redoc
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    cfg.service(openapi_json).service(Redoc::with_url("/docs", ApiDoc::openapi()));
+}
+
+#[get("/openapi.json")]
+async fn openapi_json() -> impl Responder {
+    HttpResponse::Ok().json(ApiDoc::openapi())
+}
+
+// Проверяем, что спецификация совпадает с сохраненной в openapi.json и с тем,
+// что на самом деле отвечают обработчики
+#[cfg(test)]
+mod openapi_tests {
+    use std::collections::BTreeSet;
+    use std::sync::Arc;
+
+    use actix_web::http::StatusCode;
+    use actix_web::{App, HttpRequest, HttpResponse, test, web};
+    use serde_json::{Value, json};
+    use sqlx::SqlitePool;
+    use tokio::sync::Mutex;
+    use utoipa::OpenApi;
+    use utoipa::openapi::RefOr;
+
+    use crate::application::services::TaskService;
+    use crate::application::webhooks::WebhookService;
+    use crate::domain::entities::Task;
+    use crate::domain::repositories::TaskRepository;
+    use crate::domain::webhooks::{NewWebhook, WebhookRepository};
+    use crate::infrastructure::in_memory::InMemoryTaskRepository;
+    use crate::infrastructure::schema::MIGRATOR;
+    use crate::infrastructure::sqlite_webhooks::SqliteWebhookRepository;
+    use crate::presentation::openapi::{self, ApiDoc};
+    use crate::presentation::web_controller;
+
+    const SNAPSHOT: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/openapi.json");
+
+    async fn service() -> web::Data<Mutex<TaskService>> {
+        let mut repository = InMemoryTaskRepository::new();
+        let id = repository.next_id().await;
+        repository.create(Task { id, title: "Task".to_string(), description: "Desc".to_string(), ..Default::default() }).await.unwrap();
+        web::Data::new(Mutex::new(TaskService::new(Box::new(repository))))
+    }
+
+    async fn webhook_service() -> web::Data<WebhookService> {
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        let repository = SqliteWebhookRepository::new(pool);
+        let webhook = NewWebhook { url: "https://ci.example/hook".to_string(), events: Vec::new(), secret: "secret".to_string() };
+        repository.create(webhook).await.unwrap();
+        web::Data::new(WebhookService::new(Arc::new(repository)))
+    }
+
+    // Запрос к каждой операции спецификации: метод, путь в спецификации, адрес и тело
+    fn requests() -> Vec<(&'static str, &'static str, &'static str, Option<Value>)> {
+        vec![
+            ("get", "/api/v1/tasks", "/api/v1/tasks", None),
+            ("get", "/api/v1/tasks", "/api/v1/tasks?as_of=yesterday", None),
+            ("get", "/api/v1/tasks/export", "/api/v1/tasks/export?format=json", None),
+            ("get", "/api/v1/tasks/export", "/api/v1/tasks/export?format=xml", None),
+            ("get", "/api/v1/tasks.ics", "/api/v1/tasks.ics", None),
+            ("post", "/api/v1/tasks/import", "/api/v1/tasks/import?format=json", Some(json!([{ "title": "New", "description": "Desc" }]))),
+            ("post", "/api/v1/tasks/import", "/api/v1/tasks/import?format=json&conflict=abort", Some(json!([{ "id": 1, "title": "Task", "description": "Desc" }]))),
+            ("get", "/api/v1/tasks/{id}", "/api/v1/tasks/1", None),
+            ("get", "/api/v1/tasks/{id}", "/api/v1/tasks/2", None),
+            ("post", "/api/v1/tasks", "/api/v1/tasks", Some(json!({ "title": "New", "description": "Desc" }))),
+            ("post", "/api/v1/tasks", "/api/v1/tasks", Some(json!({ "title": "", "description": "Desc" }))),
+            ("post", "/api/v1/tasks/batch", "/api/v1/tasks/batch", Some(json!({ "mode": "best_effort", "operations": [{ "op": "toggle", "id": 1 }] }))),
+            ("post", "/api/v1/tasks/batch", "/api/v1/tasks/batch", Some(json!({ "mode": "best_effort", "operations": [{ "op": "toggle", "id": 2 }] }))),
+            ("post", "/api/v1/tasks/bulk", "/api/v1/tasks/bulk", Some(json!({ "filter": { "status": false }, "mutation": { "add_tag": "work" } }))),
+            ("post", "/api/v1/tasks/bulk", "/api/v1/tasks/bulk", Some(json!({ "filter": {}, "mutation": { "set_status": true } }))),
+            ("put", "/api/v1/tasks/{id}", "/api/v1/tasks/1", Some(json!({ "title": "Changed", "description": "Desc" }))),
+            ("put", "/api/v1/tasks/{id}", "/api/v1/tasks/2", Some(json!({ "title": "Changed", "description": "Desc" }))),
+            ("patch", "/api/v1/tasks/{id}", "/api/v1/tasks/1", None),
+            ("patch", "/api/v1/tasks/{id}", "/api/v1/tasks/1", Some(json!({ "title": "Changed" }))),
+            ("delete", "/api/v1/tasks/{id}", "/api/v1/tasks/1", None),
+            ("delete", "/api/v1/tasks/{id}", "/api/v1/tasks/2", None),
+            ("post", "/api/v1/undo", "/api/v1/undo", None),
+            ("post", "/api/v1/redo", "/api/v1/redo", None),
+            ("get", "/api/v1/events", "/api/v1/events?tag=work", None),
+            ("get", "/api/v1/events", "/api/v1/events?after=first", None),
+            ("get", "/api/v1/events/ws", "/api/v1/events/ws", None),
+            ("post", "/api/v1/webhooks", "/api/v1/webhooks", Some(json!({ "url": "https://chat.example/hook", "events": ["deleted"], "secret": "s3cr3t" }))),
+            ("post", "/api/v1/webhooks", "/api/v1/webhooks", Some(json!({ "url": "chat.example", "secret": "s3cr3t" }))),
+            ("get", "/api/v1/webhooks", "/api/v1/webhooks", None),
+            ("get", "/api/v1/webhooks/{id}", "/api/v1/webhooks/1", None),
+            ("get", "/api/v1/webhooks/{id}", "/api/v1/webhooks/2", None),
+            ("delete", "/api/v1/webhooks/{id}", "/api/v1/webhooks/1", None),
+            ("delete", "/api/v1/webhooks/{id}", "/api/v1/webhooks/2", None),
+            ("get", "/api/v1/webhooks/{id}/deliveries", "/api/v1/webhooks/1/deliveries", None),
+            ("get", "/api/v1/webhooks/{id}/deliveries", "/api/v1/webhooks/2/deliveries", None),
+        ]
+    }
+
+    #[tokio::test]
+    async fn spec_matches_snapshot() {
+        // Проверяем, что изменения API попадают в openapi.json; UPDATE_OPENAPI=1 перезаписывает файл
+        let spec = ApiDoc::openapi().to_pretty_json().unwrap() + "\n";
+        if std::env::var_os("UPDATE_OPENAPI").is_some() {
+            std::fs::write(SNAPSHOT, &spec).unwrap();
+        }
+        let snapshot = std::fs::read_to_string(SNAPSHOT).unwrap_or_default();
+        assert!(spec == snapshot, "openapi.json is out of date, run `UPDATE_OPENAPI=1 cargo test spec_matches_snapshot`");
+    }
+
+    #[tokio::test]
+    async fn handlers_answer_as_documented() {
+        // Проверяем, что каждая операция спецификации проверена и отвечает только документированными кодами
+        let spec = ApiDoc::openapi();
+        let mut operations = Vec::new();
+        for (path, item) in &spec.paths.paths {
+            let methods = [("get", &item.get), ("post", &item.post), ("put", &item.put), ("patch", &item.patch), ("delete", &item.delete)];
+            operations.extend(methods.into_iter().filter_map(|(method, operation)| Some((method, path.as_str(), operation.as_ref()?))));
+        }
+        let requests = requests();
+        for (method, path, _) in &operations {
+            assert!(requests.iter().any(|request| (request.0, request.1) == (*method, *path)), "{} {} is not checked", method, path);
+        }
+
+        for (method, path, uri, body) in requests {
+            let (_, _, operation) = operations.iter().find(|operation| (operation.0, operation.1) == (method, path)).unwrap();
+            let service = service().await;
+            let feed = web::Data::new(service.lock().await.feed());
+            let app = test::init_service(
+                App::new().app_data(service).app_data(feed).app_data(webhook_service().await).configure(web_controller::configure),
+            )
+            .await;
+
+            let mut request = test::TestRequest::default().method(method.to_uppercase().parse().unwrap()).uri(uri);
+            if let Some(body) = body {
+                request = request.set_json(body);
+            }
+            let response = test::call_service(&app, request.to_request()).await;
+            let status = response.status().as_u16().to_string();
+            let Some(RefOr::T(documented)) = operation.responses.responses.get(&status) else {
+                panic!("{} {} answered {} which is not documented", method, uri, status);
+            };
+            let content_type = response.headers().get("content-type").map(|value| value.to_str().unwrap().to_string()).unwrap_or_default();
+            // Ответ без документированного тела не должен иметь и типа содержимого
+            let content_documented = if documented.content.is_empty() {
+                content_type.is_empty()
+            } else {
+                documented.content.keys().any(|documented| content_type.starts_with(documented.as_str()))
+            };
+            assert!(
+                content_documented,
+                "{} {} answered {} with {} which is not documented",
+                method,
+                uri,
+                status,
+                content_type
+            );
+            for header in documented.headers.keys() {
+                assert!(response.headers().contains_key(header.as_str()), "{} {} answered {} without {}", method, uri, status, header);
+            }
+        }
+    }
+
+    #[tokio::test]
+    async fn every_route_is_documented() {
+        // Проверяем, что каждый путь и метод из таблицы маршрутов v1 описан в спецификации и наоборот
+        let spec = ApiDoc::openapi();
+        // Обработчик по умолчанию отдаёт отладочный вид карты маршрутов: другого способа
+        // перечислить зарегистрированные ресурсы actix не даёт
+        let app = test::init_service(App::new().configure(web_controller::v1).default_service(web::to(|request: HttpRequest| async move {
+            HttpResponse::Ok().body(format!("{:#?}", request.resource_map()))
+        })))
+        .await;
+        let map = test::call_and_read_body(&app, test::TestRequest::get().uri("/").to_request()).await;
+        let map = std::str::from_utf8(&map).unwrap();
+        let patterns: BTreeSet<&str> = map
+            .split("patterns: Single(")
+            .skip(1)
+            .filter_map(|rest| rest.split('"').nth(1))
+            .filter(|pattern| !pattern.is_empty())
+            .collect();
+
+        let mut routes = BTreeSet::new();
+        for pattern in &patterns {
+            let uri = pattern.replace("{id}", "1");
+            for method in ["get", "post", "put", "patch", "delete"] {
+                let request = test::TestRequest::default().method(method.to_uppercase().parse().unwrap()).uri(&uri);
+                let response = test::call_service(&app, request.to_request()).await;
+                // Ресурс без маршрута для метода отвечает 405
+                if response.status() != StatusCode::METHOD_NOT_ALLOWED {
+                    routes.insert((method, format!("{}{}", web_controller::V1, pattern)));
+                }
+            }
+        }
+
+        let mut documented = BTreeSet::new();
+        for (path, item) in &spec.paths.paths {
+            let methods = [("get", &item.get), ("post", &item.post), ("put", &item.put), ("patch", &item.patch), ("delete", &item.delete)];
+            documented.extend(methods.into_iter().filter(|(_, operation)| operation.is_some()).map(|(method, _)| (method, path.clone())));
+        }
+        for (method, path) in &routes {
+            assert!(documented.contains(&(*method, path.clone())), "{} {} is registered but not documented", method, path);
+        }
+        for (method, path) in &documented {
+            assert!(routes.contains(&(*method, path.clone())), "{} {} is documented but not registered", method, path);
+        }
+    }
+
+    #[tokio::test]
+    async fn spec_and_docs_are_served() {
+        // Проверяем, что спецификация и страница Redoc доступны по HTTP
+        let app = test::init_service(App::new().configure(openapi::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/openapi.json").to_request()).await;
+        assert!(response.status().is_success());
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body["openapi"].as_str().map(|version| &version[..2]), Some("3."));
+        assert!(body["paths"]["/api/v1/tasks/{id}"]["get"].is_object());
+        assert!(body["components"]["schemas"]["TaskResponse"].is_object());
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/docs").to_request()).await;
+        assert!(response.status().is_success());
+        assert!(std::str::from_utf8(&test::read_body(response).await).unwrap().contains("Redoc"));
+    }
+}
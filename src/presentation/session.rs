@@ -0,0 +1,44 @@
+use std::future::{Ready, ready};
+
+use actix_web::{FromRequest, HttpRequest, dev::Payload};
+use utoipa::openapi::path::{Parameter, ParameterBuilder, ParameterIn};
+use utoipa::openapi::{Object, Required, Type};
+
+// Заголовок, которым клиент обозначает свою сессию
+pub const SESSION_HEADER: &str = "X-Session-Id";
+
+// Идентификатор сессии клиента, по которому ведется история отмены.
+// Берется из заголовка X-Session-Id, а если его нет, из адреса клиента.
+pub struct SessionId(pub String);
+
+impl FromRequest for SessionId {
+    type Error = actix_web::Error;
+    type Future = Ready<Result<SessionId, actix_web::Error>>;
+
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let session = req
+            .headers()
+            .get(SESSION_HEADER)
+            .and_then(|value| value.to_str().ok())
+            .filter(|value| !value.is_empty())
+            .map(str::to_string)
+            .or_else(|| req.connection_info().realip_remote_addr().map(str::to_string))
+            .unwrap_or_else(|| "anonymous".to_string());
+        ready(Ok(SessionId(session)))
+    }
+}
+
+// Описание заголовка сессии в спецификации OpenAPI
+impl utoipa::IntoParams for SessionId {
+    fn into_params(_: impl Fn() -> Option<ParameterIn>) -> Vec<Parameter> {
+        vec![
+            ParameterBuilder::new()
+                .name(SESSION_HEADER)
+                .parameter_in(ParameterIn::Header)
+                .required(Required::False)
+                .description(Some("Client session that owns the undo history; defaults to the client address"))
+                .schema(Some(Object::with_type(Type::String)))
+                .build(),
+        ]
+    }
+}
@@ -1,30 +1,345 @@
-use std::sync::Mutex;
+use actix_web::dev::HttpServiceFactory;
+use actix_web::error::{InternalError, JsonPayloadError};
+use actix_web::middleware::{DefaultHeaders, from_fn};
+use actix_web::{HttpRequest, HttpResponse, Resource, Responder, Route, http::{StatusCode, header}, web};
 
-use actix_web::{HttpResponse, Responder, delete, get, patch, post, web};
+use tokio::sync::Mutex;
 
-use crate::application::services::TaskService;
+use crate::application::export::{self, ExportFormat};
+use crate::application::import;
+use crate::application::patch::{PatchOperation, TaskPatch};
+use crate::application::services::{TaskService, TaskServiceError};
 use crate::domain::entities::TaskId;
-use crate::presentation::dto::{CreateTaskRequest, TaskResponse};
+use crate::presentation::dto::{
+    ApiErrorResponse, BatchItemResponse, BatchItemStatus, BatchRequest, BatchResponse, BulkUpdateRequest,
+    BulkUpdateResponse, CreateTaskRequest, ExportQuery, ImportQuery, ImportResponse, TaskResponse,
+    TasksQuery, UpdateTaskRequest,
+};
+use crate::presentation::feed;
+use crate::presentation::idempotency;
+use crate::presentation::negotiation::{Body, ResponseFormat};
+use crate::presentation::session::SessionId;
+use crate::presentation::webhooks;
 
-#[get("/tasks")]
-pub async fn get_all_tasks(task_service: web::Data<Mutex<TaskService>>) -> impl Responder {
+// Форматы частичного изменения задачи
+const JSON_PATCH: &str = "application/json-patch+json";
+const MERGE_PATCH: &str = "application/merge-patch+json";
+const ACCEPT_PATCH: header::HeaderName = header::HeaderName::from_static("accept-patch");
+const ACCEPTED_PATCHES: &str = "application/json-patch+json, application/merge-patch+json";
+
+// Префикс текущей версии API
+pub const V1: &str = "/api/v1";
+// Старые пути без версии объявлены устаревшими 18.10.2026 (RFC 9745) и перестанут
+// работать 18.04.2027 (RFC 8594)
+const DEPRECATED_SINCE: &str = "@1792281600";
+const SUNSET: &str = "Sun, 18 Apr 2027 00:00:00 GMT";
+
+// Маршруты REST API задач. Каждая версия монтируется в свой scope со своей таблицей
+// маршрутов, поэтому следующая версия может заменить часть обработчиков, не трогая эту.
+pub fn configure(cfg: &mut web::ServiceConfig) {
+    // Ошибки разбора тела, запроса и пути отдаются в том же формате, что и остальные ошибки
+    cfg.app_data(web::JsonConfig::default().error_handler(|err, _| {
+        let (status, code) = match err {
+            JsonPayloadError::ContentType => (StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported_media_type"),
+            JsonPayloadError::Overflow { .. } | JsonPayloadError::OverflowKnownLength { .. } => {
+                (StatusCode::PAYLOAD_TOO_LARGE, "payload_too_large")
+            }
+            _ => (StatusCode::BAD_REQUEST, "malformed_input"),
+        };
+        extractor_error(err, status, code)
+    }))
+    .app_data(web::QueryConfig::default().error_handler(|err, _| extractor_error(err, StatusCode::BAD_REQUEST, "malformed_input")))
+    .app_data(web::PathConfig::default().error_handler(|err, _| extractor_error(err, StatusCode::NOT_FOUND, "not_found")));
+    cfg.service(web::scope(V1).configure(v1));
+    legacy(cfg);
+}
+
+fn extractor_error(err: impl std::fmt::Display + std::fmt::Debug + 'static, status: StatusCode, code: &str) -> actix_web::Error {
+    eprintln!("Error extracting request: {err}");
+    let response = HttpResponse::from(ApiErrorResponse::new(status, code, err.to_string()));
+    InternalError::from_response(err, response).into()
+}
+
+pub fn v1(cfg: &mut web::ServiceConfig) {
+    // Пути с фиксированным сегментом регистрируются раньше /tasks/{id}
+    cfg.service(web::resource("/tasks").route(web::get().to(get_all_tasks)).route(idempotent(web::post().to(create_task))))
+        .service(web::resource("/tasks.ics").route(web::get().to(calendar_feed)))
+        .service(web::resource("/tasks/export").route(web::get().to(export_tasks)))
+        .service(web::resource("/tasks/import").route(web::post().to(import_tasks)))
+        .service(web::resource("/tasks/batch").route(idempotent(web::post().to(execute_batch))))
+        .service(web::resource("/tasks/bulk").route(web::post().to(bulk_update)))
+        .service(
+            web::resource("/tasks/{id}")
+                .name("task")
+                .route(web::get().to(get_task_by_id))
+                .route(web::put().to(update_task))
+                .route(web::patch().to(patch_task))
+                .route(web::delete().to(delete_task)),
+        )
+        .service(web::resource("/undo").route(web::post().to(undo)))
+        .service(web::resource("/redo").route(web::post().to(redo)))
+        .service(web::resource("/events").route(web::get().to(feed::events)))
+        .service(web::resource("/events/ws").route(web::get().to(feed::events_ws)))
+        .service(web::resource("/webhooks").route(web::get().to(webhooks::get_webhooks)).route(web::post().to(webhooks::create_webhook)))
+        .service(
+            web::resource("/webhooks/{id}")
+                .name("webhook")
+                .route(web::get().to(webhooks::get_webhook))
+                .route(web::delete().to(webhooks::delete_webhook)),
+        )
+        .service(web::resource("/webhooks/{id}/deliveries").route(web::get().to(webhooks::get_deliveries)));
+}
+
+// Пути, которыми API пользовалось до появления версий. Отвечают так же, как /api/v1,
+// но с заголовками Deprecation и Sunset и ссылкой на документацию.
+fn legacy(cfg: &mut web::ServiceConfig) {
+    cfg.service(deprecated(web::resource("/tasks").route(web::get().to(get_all_tasks)).route(idempotent(web::post().to(create_task)))))
+        .service(deprecated(web::resource("/tasks.ics").route(web::get().to(calendar_feed))))
+        .service(deprecated(web::resource("/tasks/export").route(web::get().to(export_tasks))))
+        .service(deprecated(web::resource("/tasks/import").route(web::post().to(import_tasks))))
+        .service(deprecated(web::resource("/tasks/batch").route(idempotent(web::post().to(execute_batch)))))
+        .service(deprecated(web::resource("/tasks/bulk").route(web::post().to(bulk_update))))
+        .service(deprecated(web::resource("/task/{id}").route(web::get().to(get_task_by_id))))
+        .service(deprecated(
+            web::resource("/tasks/{id}")
+                .route(web::put().to(update_task))
+                .route(web::patch().to(patch_task))
+                .route(web::delete().to(delete_task)),
+        ))
+        .service(deprecated(web::resource("/undo").route(web::post().to(undo))))
+        .service(deprecated(web::resource("/redo").route(web::post().to(redo))));
+}
+
+// Маршрут, который можно безопасно повторять с заголовком Idempotency-Key
+fn idempotent(route: Route) -> Route {
+    route.wrap(from_fn(idempotency::idempotent))
+}
+
+fn deprecated(resource: Resource) -> impl HttpServiceFactory {
+    resource.wrap(
+        DefaultHeaders::new()
+            .add(("Deprecation", DEPRECATED_SINCE))
+            .add(("Sunset", SUNSET))
+            .add((header::LINK, "</docs>; rel=\"deprecation\"; type=\"text/html\"")),
+    )
+}
+
+#[utoipa::path(
+    get,
+    path = "/tasks",
+    tag = "tasks",
+    summary = "List tasks",
+    params(TasksQuery),
+    responses(
+        (status = 200, description = "All tasks, in the format chosen by `Accept`", content(
+            (Vec<TaskResponse> = "application/json"),
+            (Vec<TaskResponse> = "application/msgpack"),
+            (Vec<TaskResponse> = "application/cbor"),
+            (Vec<TaskResponse> = "application/yaml"),
+            (String = "text/csv"),
+        )),
+        (status = 406, description = "None of the formats in `Accept` is supported", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 400, description = "Malformed query", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 501, description = "Storage does not keep history for `as_of`", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_all_tasks(
+    task_service: web::Data<Mutex<TaskService>>,
+    query: web::Query<TasksQuery>,
+    format: ResponseFormat,
+) -> impl Responder {
     println!("get_all_tasks");
-    let tasks = task_service.lock().unwrap().get_all().await;
+    let tasks = match query.as_of {
+        Some(at) => task_service.lock().await.get_all_as_of(at).await,
+        None => task_service.lock().await.get_all().await,
+    };
+    let tasks = match tasks {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error getting tasks");
+            return HttpResponse::from(e);
+        }
+    };
     let response: Vec<TaskResponse> = tasks.into_iter().map(TaskResponse::from).collect();
-    HttpResponse::Ok().body(serde_json::to_string_pretty(&response).unwrap())
+    format.respond(StatusCode::OK, &response)
+}
+
+#[utoipa::path(
+    get,
+    path = "/tasks/export",
+    tag = "tasks",
+    summary = "Export tasks to a file",
+    params(ExportQuery),
+    responses(
+        (status = 200, description = "Tasks in the requested format, as an attachment", content(
+            (String = "text/csv"),
+            (Vec<TaskResponse> = "application/json"),
+            (String = "application/x-ndjson"),
+            (String = "text/markdown"),
+            (String = "text/plain"),
+            (String = "text/calendar"),
+        )),
+        (status = 400, description = "Malformed query", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn export_tasks(
+    task_service: web::Data<Mutex<TaskService>>,
+    query: web::Query<ExportQuery>,
+) -> impl Responder {
+    println!("export_tasks format: {:?}", query.format);
+    let tasks = match task_service.lock().await.find(&query.filter()).await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error exporting tasks");
+            return HttpResponse::from(e);
+        }
+    };
+    let mut response = HttpResponse::Ok();
+    response.content_type(query.format.content_type()).insert_header((
+        header::CONTENT_DISPOSITION,
+        format!("attachment; filename=\"tasks.{}\"", query.format.extension()),
+    ));
+
+    // NDJSON отдается потоком по одной задаче, остальные форматы - целиком
+    if query.format == ExportFormat::Ndjson {
+        let lines = tasks.into_iter().map(|task| Ok::<_, actix_web::Error>(web::Bytes::from(export::ndjson_line(&task))));
+        return response.streaming(futures_util::stream::iter(lines));
+    }
+    let mut body = Vec::new();
+    match export::export(&tasks, query.format, &mut body) {
+        Ok(()) => response.body(body),
+        Err(e) => {
+            eprintln!("Error exporting tasks: {:?}", e);
+            HttpResponse::from(TaskServiceError::UnexpectedError)
+        }
+    }
+}
+
+// Календарь задач, на который можно подписаться из календарных программ
+#[utoipa::path(
+    get,
+    path = "/tasks.ics",
+    tag = "tasks",
+    summary = "Calendar feed of all tasks",
+    responses(
+        (status = 200, description = "iCalendar with a VTODO per task", body = String, content_type = "text/calendar"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn calendar_feed(task_service: web::Data<Mutex<TaskService>>) -> impl Responder {
+    println!("calendar_feed");
+    let tasks = match task_service.lock().await.get_all().await {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error getting tasks for calendar");
+            return HttpResponse::from(e);
+        }
+    };
+    let mut body = Vec::new();
+    match export::export(&tasks, ExportFormat::Ical, &mut body) {
+        Ok(()) => HttpResponse::Ok().content_type(ExportFormat::Ical.content_type()).body(body),
+        Err(e) => {
+            eprintln!("Error writing calendar: {:?}", e);
+            HttpResponse::from(TaskServiceError::UnexpectedError)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/tasks/import",
+    tag = "tasks",
+    summary = "Import tasks from a file",
+    params(ImportQuery, SessionId),
+    request_body(description = "File in the format given by `format`", content(
+        (String = "text/csv"),
+        (String = "application/json"),
+        (String = "application/x-ndjson"),
+        (String = "text/plain"),
+        (String = "text/calendar"),
+    )),
+    responses(
+        (status = 200, description = "All records imported", body = ImportResponse),
+        (status = 207, description = "Some records were rejected", body = ImportResponse),
+        (status = 409, description = "Import aborted because of a conflict", body = ImportResponse),
+        (status = 400, description = "File or query could not be parsed", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn import_tasks(
+    task_service: web::Data<Mutex<TaskService>>,
+    session: SessionId,
+    query: web::Query<ImportQuery>,
+    body: web::Bytes,
+) -> impl Responder {
+    println!("import_tasks format: {:?}, conflict: {:?}, dry_run: {}", query.format, query.conflict, query.dry_run);
+    let records = match import::parse(query.format, &body) {
+        Ok(records) => records,
+        Err(reason) => {
+            eprintln!("Error parsing import: {reason}");
+            return HttpResponse::from(TaskServiceError::Malformed(reason));
+        }
+    };
+    match task_service
+        .lock()
+        .await
+        .import(&session.0, records, query.conflict, query.dry_run)
+        .await {
+        Ok(report) => {
+            let response = ImportResponse::from(report);
+            println!("Tasks imported: {}, updated: {}, rejected: {}", response.created, response.updated, response.rejected);
+            // 409, если загрузка отменена из-за конфликта; 207, если часть записей отклонена
+            if response.aborted {
+                HttpResponse::Conflict().json(response)
+            } else if response.rejected > 0 {
+                HttpResponse::build(StatusCode::MULTI_STATUS).json(response)
+            } else {
+                HttpResponse::Ok().json(response)
+            }
+        }
+        Err(e) => {
+            eprintln!("Error importing tasks");
+            HttpResponse::from(e)
+        }
+    }
 }
 
-#[get("/task/{id}")]
+#[utoipa::path(
+    get,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    summary = "Get a task",
+    params(("id" = i64, Path, description = "Task ID")),
+    responses(
+        (status = 200, description = "Task, in the format chosen by `Accept`", content(
+            (TaskResponse = "application/json"),
+            (TaskResponse = "application/msgpack"),
+            (TaskResponse = "application/cbor"),
+            (TaskResponse = "application/yaml"),
+            (String = "text/csv"),
+        )),
+        (status = 406, description = "None of the formats in `Accept` is supported", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "Task not found", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn get_task_by_id(
     task_service: web::Data<Mutex<TaskService>>,
     id: web::Path<TaskId>,
+    format: ResponseFormat,
 ) -> impl Responder {
     println!("get_task_by_id/{id}");
-    match task_service.lock().unwrap().get_by_id(*id).await {
+    match task_service.lock().await.get_by_id(*id).await {
         Ok(task) => {
             println!("response: {:?}", task);
-            let response = TaskResponse::from(task);
-            HttpResponse::Ok().body(serde_json::to_string_pretty(&response).unwrap())
+            format.respond(StatusCode::OK, &TaskResponse::from(task))
         }
         Err(e) => {
             eprintln!("Task not found");
@@ -33,10 +348,47 @@ pub async fn get_task_by_id(
     }
 }
 
-#[post("/tasks")]
+#[utoipa::path(
+    post,
+    path = "/tasks",
+    tag = "tasks",
+    summary = "Create a task",
+    params(
+        SessionId,
+        ("Idempotency-Key" = Option<String>, Header, description = "Makes retries safe: a repeated request with the same key and body gets the stored response \
+            (marked with `Idempotent-Replayed: true`) instead of being executed again"),
+    ),
+    request_body(description = "Task in the format given by `Content-Type`", content(
+            (CreateTaskRequest = "application/json"),
+            (CreateTaskRequest = "application/msgpack"),
+            (CreateTaskRequest = "application/cbor"),
+            (CreateTaskRequest = "application/yaml"),
+            (String = "text/csv"),
+        )),
+    responses(
+        (status = 201, description = "Task created, in the format chosen by `Accept`", content(
+            (TaskResponse = "application/json"),
+            (TaskResponse = "application/msgpack"),
+            (TaskResponse = "application/cbor"),
+            (TaskResponse = "application/yaml"),
+            (String = "text/csv"),
+        ),
+            headers(("Location" = String, description = "Address of the created task"))),
+        (status = 406, description = "None of the formats in `Accept` is supported", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 400, description = "Fields are invalid, or the body is malformed", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 409, description = "A request with the same `Idempotency-Key` is still being processed", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 422, description = "`Idempotency-Key` was already used for a different request", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 415, description = "Body is not JSON", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn create_task(
+    req: HttpRequest,
     task_service: web::Data<Mutex<TaskService>>,
-    request: web::Json<CreateTaskRequest>,
+    session: SessionId,
+    request: Body<CreateTaskRequest>,
+    format: ResponseFormat,
 ) -> impl Responder {
     println!(
         "create_task title: {}, description: {}",
@@ -44,12 +396,18 @@ pub async fn create_task(
     );
     match task_service
         .lock()
-        .unwrap()
-        .create(request.title.to_string(), request.description.to_string())
+        .await
+        .create(&session.0, request.into_inner().into())
         .await {
-        Ok(_) => {
-            println!("Task created");
-            HttpResponse::Ok().json("Task created")
+        Ok(task) => {
+            println!("Task created: {}", task.id);
+            let location = req.url_for("task", [task.id.to_string()]);
+            let mut response = format.respond(StatusCode::CREATED, &TaskResponse::from(task));
+            // Новая задача всегда адресуется по пути текущей версии, даже если создана через старый путь
+            if let Some(location) = location.ok().and_then(|url| header::HeaderValue::from_str(url.path()).ok()) {
+                response.headers_mut().insert(header::LOCATION, location);
+            }
+            response
         }
         Err(e) => {
             eprintln!("Error creating task");
@@ -58,34 +416,258 @@ pub async fn create_task(
     }
 }
 
-#[patch("/tasks/{id}")]
-pub async fn toggle_task(
+#[utoipa::path(
+    post,
+    path = "/tasks/batch",
+    tag = "tasks",
+    summary = "Execute several operations at once",
+    params(
+        SessionId,
+        ("Idempotency-Key" = Option<String>, Header, description = "Makes retries safe: a repeated request with the same key and body gets the stored response \
+            (marked with `Idempotent-Replayed: true`) instead of being executed again"),
+    ),
+    request_body = BatchRequest,
+    responses(
+        (status = 200, description = "All operations succeeded", body = BatchResponse),
+        (status = 207, description = "Some operations failed", body = BatchResponse),
+        (status = 400, description = "Malformed request", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 409, description = "A request with the same `Idempotency-Key` is still being processed", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 422, description = "`Idempotency-Key` was already used for a different request", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 415, description = "Body is not JSON", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn execute_batch(
+    task_service: web::Data<Mutex<TaskService>>,
+    session: SessionId,
+    request: web::Json<BatchRequest>,
+) -> impl Responder {
+    let BatchRequest { mode, operations } = request.into_inner();
+    println!("execute_batch mode: {:?}, operations: {}", mode, operations.len());
+    let results = task_service
+        .lock()
+        .await
+        .execute_batch(&session.0, operations.into_iter().map(Into::into).collect(), mode)
+        .await;
+    let results: Vec<BatchItemResponse> = results
+        .into_iter()
+        .enumerate()
+        .map(|(index, result)| BatchItemResponse::new(index, result))
+        .collect();
+
+    // 207, если хотя бы одна операция не выполнена
+    if results.iter().all(|item| item.status == BatchItemStatus::Ok) {
+        HttpResponse::Ok().json(BatchResponse { results })
+    } else {
+        HttpResponse::build(StatusCode::MULTI_STATUS).json(BatchResponse { results })
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/tasks/bulk",
+    tag = "tasks",
+    summary = "Change every task that matches a filter",
+    params(SessionId),
+    request_body = BulkUpdateRequest,
+    responses(
+        (status = 200, description = "IDs of changed (or matching, for a dry run) tasks", body = BulkUpdateResponse),
+        (status = 400, description = "Filter is empty, or the body is malformed", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 415, description = "Body is not JSON", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 501, description = "Storage does not support bulk updates", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn bulk_update(
+    task_service: web::Data<Mutex<TaskService>>,
+    session: SessionId,
+    request: web::Json<BulkUpdateRequest>,
+) -> impl Responder {
+    let BulkUpdateRequest { filter, mutation, dry_run } = request.into_inner();
+    println!("bulk_update filter: {:?}, mutation: {:?}, dry_run: {}", filter, mutation, dry_run);
+    match task_service
+        .lock()
+        .await
+        .bulk_update(&session.0, filter, mutation, dry_run)
+        .await {
+        Ok(affected) => {
+            println!("Tasks affected: {:?}", affected);
+            HttpResponse::Ok().json(BulkUpdateResponse { dry_run, affected })
+        }
+        Err(e) => {
+            eprintln!("Error in bulk update");
+            HttpResponse::from(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    put,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    summary = "Replace title and description of a task",
+    params(("id" = i64, Path, description = "Task ID"), SessionId),
+    request_body(description = "New fields in the format given by `Content-Type`", content(
+            (UpdateTaskRequest = "application/json"),
+            (UpdateTaskRequest = "application/msgpack"),
+            (UpdateTaskRequest = "application/cbor"),
+            (UpdateTaskRequest = "application/yaml"),
+            (String = "text/csv"),
+        )),
+    responses(
+        (status = 200, description = "Updated task, in the format chosen by `Accept`", content(
+            (TaskResponse = "application/json"),
+            (TaskResponse = "application/msgpack"),
+            (TaskResponse = "application/cbor"),
+            (TaskResponse = "application/yaml"),
+            (String = "text/csv"),
+        )),
+        (status = 406, description = "None of the formats in `Accept` is supported", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 400, description = "Fields are invalid, or the body is malformed", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 415, description = "Body is not JSON", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "Task not found", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn update_task(
     task_service: web::Data<Mutex<TaskService>>,
+    session: SessionId,
     id: web::Path<TaskId>,
+    request: Body<UpdateTaskRequest>,
+    format: ResponseFormat,
 ) -> impl Responder {
-    println!("toggle_task/{id}");
-    match task_service.lock().unwrap().toggle(*id).await {
-        Ok(_) => {
-            println!("Task toggled");
-            HttpResponse::Ok().json("Task toggled")
+    println!("update_task/{id} title: {}, description: {}", request.title, request.description);
+    match task_service
+        .lock()
+        .await
+        .update(&session.0, *id, request.into_inner().into())
+        .await {
+        Ok(task) => {
+            println!("Task updated");
+            format.respond(StatusCode::OK, &TaskResponse::from(task))
         }
         Err(e) => {
-            eprintln!("Task not found");
+            eprintln!("Error updating task");
+            HttpResponse::from(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    patch,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    summary = "Change some fields of a task, or toggle its completion",
+    description = "With a JSON Patch (RFC 6902) or JSON Merge Patch (RFC 7396) body changes the given fields. \
+        `id` and `created` cannot be changed. Without a body toggles completion of the task whatever the `Content-Type`.",
+    params(("id" = i64, Path, description = "Task ID"), SessionId),
+    request_body(description = "Patch; omit to toggle completion", content(
+        (Option<Vec<PatchOperation>> = "application/json-patch+json"),
+        (Option<Object> = "application/merge-patch+json"),
+    )),
+    responses(
+        (status = 200, description = "Changed task, in the format chosen by `Accept`", content(
+            (TaskResponse = "application/json"),
+            (TaskResponse = "application/msgpack"),
+            (TaskResponse = "application/cbor"),
+            (TaskResponse = "application/yaml"),
+            (String = "text/csv"),
+        )),
+        (status = 406, description = "None of the formats in `Accept` is supported", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 400, description = "Patch is malformed, or title or description are invalid after it", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 404, description = "Task not found", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 409, description = "A `test` operation failed", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 415, description = "Body is neither a JSON Patch nor a JSON Merge Patch", body = ApiErrorResponse, content_type = "application/problem+json",
+            headers(("Accept-Patch" = String, description = "Supported patch formats"))),
+        (status = 422, description = "Patch targets an unknown or immutable field, a missing path or has a value of a wrong type", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn patch_task(
+    req: HttpRequest,
+    task_service: web::Data<Mutex<TaskService>>,
+    session: SessionId,
+    id: web::Path<TaskId>,
+    body: web::Bytes,
+    format: ResponseFormat,
+) -> impl Responder {
+    let content_type = req
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.split(';').next())
+        .map(|value| value.trim().to_ascii_lowercase());
+    let patch = match content_type.as_deref() {
+        // Запрос без тела, как и до появления частичных изменений, переключает статус, какой бы
+        // Content-Type ни прислал клиент: старые клиенты всегда отправляют application/json
+        _ if body.is_empty() => {
+            println!("toggle_task/{id}");
+            return match task_service.lock().await.toggle(&session.0, *id).await {
+                Ok(task) => {
+                    println!("Task toggled");
+                    format.respond(StatusCode::OK, &TaskResponse::from(task))
+                }
+                Err(e) => {
+                    eprintln!("Task not found");
+                    HttpResponse::from(e)
+                }
+            };
+        }
+        Some(JSON_PATCH) => serde_json::from_slice(&body).map(TaskPatch::Json),
+        Some(MERGE_PATCH) => serde_json::from_slice(&body).map(TaskPatch::Merge),
+        _ => {
+            eprintln!("Unsupported patch format: {:?}", content_type);
+            let detail = format!("Patch must be {} or {}", JSON_PATCH, MERGE_PATCH);
+            let mut response = HttpResponse::from(ApiErrorResponse::new(StatusCode::UNSUPPORTED_MEDIA_TYPE, "unsupported_media_type", detail));
+            response.headers_mut().insert(ACCEPT_PATCH, header::HeaderValue::from_static(ACCEPTED_PATCHES));
+            return response;
+        }
+    };
+    let patch = match patch {
+        Ok(patch) => patch,
+        Err(e) => {
+            eprintln!("Error parsing patch: {e}");
+            return HttpResponse::from(TaskServiceError::Malformed(e.to_string()));
+        }
+    };
+    println!("patch_task/{id} patch: {:?}", patch);
+    match task_service.lock().await.patch(&session.0, *id, &patch).await {
+        Ok(task) => {
+            println!("Task patched");
+            format.respond(StatusCode::OK, &TaskResponse::from(task))
+        }
+        Err(e) => {
+            eprintln!("Error patching task: {:?}", e);
             HttpResponse::from(e)
         }
     }
 }
 
-#[delete("/tasks/{id}")]
+#[utoipa::path(
+    delete,
+    path = "/tasks/{id}",
+    tag = "tasks",
+    summary = "Delete a task",
+    params(("id" = i64, Path, description = "Task ID"), SessionId),
+    responses(
+        (status = 204, description = "Task deleted"),
+        (status = 404, description = "Task not found", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
 pub async fn delete_task(
     task_service: web::Data<Mutex<TaskService>>,
+    session: SessionId,
     id: web::Path<TaskId>,
 ) -> impl Responder {
     println!("delete_task/{id}");
-    match task_service.lock().unwrap().delete(*id).await {
+    match task_service.lock().await.delete(&session.0, *id).await {
         Ok(_) => {
             println!("Task deleted");
-            HttpResponse::Ok().json("Task deleted")
+            HttpResponse::NoContent().finish()
         }
         Err(e) => {
             eprintln!("Task not found");
@@ -93,3 +675,529 @@ pub async fn delete_task(
         }
     }
 }
+
+#[utoipa::path(
+    post,
+    path = "/undo",
+    tag = "history",
+    summary = "Undo the last operation of the session",
+    params(SessionId),
+    responses(
+        (status = 204, description = "Operation undone"),
+        (status = 409, description = "Nothing to undo, or the task was changed since", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn undo(task_service: web::Data<Mutex<TaskService>>, session: SessionId) -> impl Responder {
+    println!("undo session: {}", session.0);
+    match task_service.lock().await.undo(&session.0).await {
+        Ok(_) => {
+            println!("Operation undone");
+            HttpResponse::NoContent().finish()
+        }
+        Err(e) => {
+            eprintln!("Error undoing operation");
+            HttpResponse::from(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    post,
+    path = "/redo",
+    tag = "history",
+    summary = "Redo the last undone operation of the session",
+    params(SessionId),
+    responses(
+        (status = 204, description = "Operation redone"),
+        (status = 409, description = "Nothing to redo, or the task was changed since", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 503, description = "Storage did not respond in time", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn redo(task_service: web::Data<Mutex<TaskService>>, session: SessionId) -> impl Responder {
+    println!("redo session: {}", session.0);
+    match task_service.lock().await.redo(&session.0).await {
+        Ok(_) => {
+            println!("Operation redone");
+            HttpResponse::NoContent().finish()
+        }
+        Err(e) => {
+            eprintln!("Error redoing operation");
+            HttpResponse::from(e)
+        }
+    }
+}
+
+// Проверяем ответы контроллера, в том числе корректность ApiErrorResponse при сбоях
+// хранилища, которые внедряются через ChaosTaskRepository
+#[cfg(test)]
+mod web_controller_tests {
+    use std::time::Duration;
+
+    use actix_web::{App, http::StatusCode, test, web};
+    use serde_json::{Value, json};
+    use tokio::sync::Mutex;
+
+    use crate::application::services::{TaskDraft, TaskService};
+    use crate::domain::entities::Task;
+    use crate::domain::repositories::TaskRepository;
+    use crate::infrastructure::chaos::{ChaosConfig, ChaosOperation, ChaosTaskRepository};
+    use crate::infrastructure::in_memory::InMemoryTaskRepository;
+    use crate::presentation::web_controller;
+
+    async fn service_with(config: ChaosConfig) -> web::Data<Mutex<TaskService>> {
+        let mut inner = InMemoryTaskRepository::new();
+        let id = inner.next_id().await;
+        let task = Task { id, title: "Task".to_string(), description: "Desc".to_string(), ..Default::default() };
+        inner.create(task).await.unwrap();
+        let repository = ChaosTaskRepository::new(inner, config);
+        web::Data::new(Mutex::new(TaskService::new(Box::new(repository))))
+    }
+
+    fn draft(title: &str, description: &str) -> TaskDraft {
+        TaskDraft { title: title.to_string(), description: description.to_string(), ..Default::default() }
+    }
+
+    // Тело ошибки - Problem Details (RFC 7807) с кодом, непустым описанием и ничего лишнего
+    fn assert_api_error(body: &Value, code: &str) {
+        let object = body.as_object().expect("error body must be an object");
+        assert_eq!(object["type"], "about:blank");
+        assert!(!object["title"].as_str().unwrap().is_empty());
+        assert!(object["status"].is_u64());
+        assert_eq!(object["code"], code);
+        assert!(!object["detail"].as_str().unwrap().is_empty());
+        assert!(object.keys().all(|key| ["type", "title", "status", "detail", "code", "errors"].contains(&key.as_str())));
+    }
+
+    #[tokio::test]
+    async fn get_all_failure_returns_internal_error() {
+        // Проверяем ответ на ошибку хранилища при чтении списка задач
+        let service = service_with(ChaosConfig { failure_rate: 1.0, ..Default::default() }).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks").to_request()).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body: Value = test::read_body_json(response).await;
+        assert_api_error(&body, "unexpected_error");
+    }
+
+    #[tokio::test]
+    async fn get_by_id_timeout_returns_service_unavailable() {
+        // Проверяем ответ на тайм-аут хранилища при чтении задачи
+        let config = ChaosConfig { timeout_rate: 1.0, timeout: Duration::from_millis(10), ..Default::default() };
+        let service = service_with(config).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/1").to_request()).await;
+        assert_eq!(response.status(), StatusCode::SERVICE_UNAVAILABLE);
+        let body: Value = test::read_body_json(response).await;
+        assert_api_error(&body, "timeout");
+    }
+
+    #[tokio::test]
+    async fn create_failure_returns_internal_error() {
+        // Проверяем ответ на ошибку хранилища при создании задачи
+        let service = service_with(ChaosConfig { failure_rate: 1.0, ..Default::default() }).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let request = test::TestRequest::post()
+            .uri("/api/v1/tasks")
+            .set_json(json!({ "title": "New", "description": "Desc" }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::INTERNAL_SERVER_ERROR);
+        let body: Value = test::read_body_json(response).await;
+        assert_api_error(&body, "unexpected_error");
+    }
+
+    #[tokio::test]
+    async fn create_returns_created_task_with_location() {
+        // Проверяем код 201, адрес новой задачи и саму задачу в ответе
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let request = test::TestRequest::post()
+            .uri("/api/v1/tasks")
+            .set_json(json!({ "title": "New", "description": "Desc" }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers().get("location").unwrap(), "/api/v1/tasks/2");
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body, json!({ "id": 2, "title": "New", "description": "Desc", "status": false, "tags": [] }));
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/2").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/json");
+    }
+
+    #[tokio::test]
+    async fn create_reports_every_invalid_field() {
+        // Проверяем, что все ошибки полей приходят одним ответом problem+json
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let request = test::TestRequest::post()
+            .uri("/api/v1/tasks")
+            .set_json(json!({ "title": "x".repeat(201), "description": "", "due": "tomorrow" }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/problem+json");
+        let body: Value = test::read_body_json(response).await;
+        assert_api_error(&body, "validation_failed");
+        assert_eq!(body["status"], 400);
+        let fields: Vec<(&str, &str)> = body["errors"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|error| (error["field"].as_str().unwrap(), error["code"].as_str().unwrap()))
+            .collect();
+        assert_eq!(fields, vec![("title", "too_long"), ("description", "required"), ("due", "invalid_date")]);
+    }
+
+    #[tokio::test]
+    async fn extractor_failures_are_problem_details() {
+        // Проверяем, что ошибки разбора тела, запроса и пути отдаются в формате problem+json
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let requests = [
+            (
+                test::TestRequest::post().uri("/api/v1/tasks").insert_header(("content-type", "application/json")).set_payload("{\"title\":"),
+                StatusCode::BAD_REQUEST,
+                "malformed_input",
+            ),
+            (
+                test::TestRequest::post().uri("/api/v1/tasks").insert_header(("content-type", "text/plain")).set_payload("title"),
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                "unsupported_media_type",
+            ),
+            (test::TestRequest::get().uri("/api/v1/tasks?as_of=someday"), StatusCode::BAD_REQUEST, "malformed_input"),
+            (test::TestRequest::get().uri("/api/v1/tasks/first"), StatusCode::NOT_FOUND, "not_found"),
+        ];
+        for (request, status, code) in requests {
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), status);
+            assert_eq!(response.headers().get("content-type").unwrap(), "application/problem+json");
+            let body: Value = test::read_body_json(response).await;
+            assert_api_error(&body, code);
+        }
+    }
+
+    #[tokio::test]
+    async fn tasks_are_negotiated_by_accept_and_content_type() {
+        // Проверяем, что задача отдается в запрошенном формате и создается из тела любого поддерживаемого формата
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let get = |accept: &str| test::TestRequest::get().uri("/api/v1/tasks/1").insert_header(("accept", accept)).to_request();
+        let response = test::call_service(&app, get("application/msgpack")).await;
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/msgpack");
+        assert_eq!(response.headers().get("vary").unwrap(), "Accept");
+        let body: Value = rmp_serde::from_slice(&test::read_body(response).await).unwrap();
+        assert_eq!(body["title"], "Task");
+        let response = test::call_service(&app, get("application/cbor")).await;
+        let body: Value = ciborium::from_reader(&test::read_body(response).await[..]).unwrap();
+        assert_eq!(body["title"], "Task");
+        let response = test::call_service(&app, get("text/html;q=0.9, application/yaml")).await;
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/yaml");
+        let body: Value = serde_yaml_ng::from_slice(&test::read_body(response).await).unwrap();
+        assert_eq!(body["title"], "Task");
+        let response = test::call_service(&app, get("text/csv")).await;
+        assert_eq!(test::read_body(response).await, "id,title,description,status,tags\n1,Task,Desc,false,\n");
+
+        let response = test::call_service(&app, get("text/html")).await;
+        assert_eq!(response.status(), StatusCode::NOT_ACCEPTABLE);
+        let body: Value = test::read_body_json(response).await;
+        assert_api_error(&body, "not_acceptable");
+
+        let new_task = json!({ "title": "From binary", "description": "Desc" });
+        let mut cbor = Vec::new();
+        ciborium::into_writer(&new_task, &mut cbor).unwrap();
+        let bodies = [
+            ("application/yaml", b"title: From YAML\ndescription: Desc\n".to_vec()),
+            ("text/csv", b"title,description,due\nFrom CSV,Desc,\n".to_vec()),
+            ("application/msgpack", rmp_serde::to_vec_named(&new_task).unwrap()),
+            ("application/cbor", cbor),
+        ];
+        for (content_type, body) in bodies {
+            let request = test::TestRequest::post().uri("/api/v1/tasks").insert_header(("content-type", content_type)).set_payload(body);
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), StatusCode::CREATED);
+            let body: Value = test::read_body_json(response).await;
+            assert!(body["title"].as_str().unwrap().starts_with("From "));
+        }
+
+        let request = test::TestRequest::post().uri("/api/v1/tasks").insert_header(("content-type", "application/xml")).set_payload("<task/>");
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert!(response.headers().get("accept").unwrap().to_str().unwrap().contains("application/cbor"));
+    }
+
+    #[tokio::test]
+    async fn toggle_returns_task_and_delete_returns_no_content() {
+        // Проверяем, что переключение отдает задачу с новым статусом, а удаление - пустой ответ 204
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::patch().uri("/api/v1/tasks/1").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body["status"], true);
+
+        // Старые клиенты присылают Content-Type и без тела
+        let request = test::TestRequest::patch().uri("/api/v1/tasks/1").insert_header(("content-type", "application/json"));
+        let response = test::call_service(&app, request.to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body["status"], false);
+
+        let response = test::call_service(&app, test::TestRequest::delete().uri("/api/v1/tasks/1").to_request()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(test::read_body(response).await.is_empty());
+    }
+
+    // Запрос PATCH с телом указанного типа
+    fn patch(uri: &str, content_type: &str, body: Value) -> test::TestRequest {
+        test::TestRequest::patch().uri(uri).insert_header(("content-type", content_type)).set_payload(body.to_string())
+    }
+
+    #[tokio::test]
+    async fn patch_changes_only_given_fields() {
+        // Проверяем JSON Patch и JSON Merge Patch: меняются только указанные поля, название обрезается
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let operations = json!([{ "op": "replace", "path": "/title", "value": " Renamed " }, { "op": "add", "path": "/tags/-", "value": "work" }]);
+        let response = test::call_service(&app, patch("/api/v1/tasks/1", "application/json-patch+json", operations).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body, json!({ "id": 1, "title": "Renamed", "description": "Desc", "status": false, "tags": ["work"] }));
+
+        let merge = json!({ "status": true, "due": "2026-10-25", "tags": null });
+        let response = test::call_service(&app, patch("/api/v1/tasks/1", "application/merge-patch+json; charset=utf-8", merge).to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body, json!({ "id": 1, "title": "Renamed", "description": "Desc", "status": true, "tags": [], "due": "2026-10-25" }));
+    }
+
+    #[tokio::test]
+    async fn patch_reports_precise_errors() {
+        // Проверяем коды и поле ошибки для неизвестного и неизменяемого поля, проваленного test и чужого формата
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let requests = [
+            (patch("/api/v1/tasks/1", "application/merge-patch+json", json!({ "owner": "me" })), StatusCode::UNPROCESSABLE_ENTITY, "owner", "unknown_field"),
+            (
+                patch("/api/v1/tasks/1", "application/json-patch+json", json!([{ "op": "replace", "path": "/id", "value": 2 }])),
+                StatusCode::UNPROCESSABLE_ENTITY,
+                "id",
+                "immutable_field",
+            ),
+            (
+                patch("/api/v1/tasks/1", "application/json-patch+json", json!([{ "op": "test", "path": "/status", "value": true }])),
+                StatusCode::CONFLICT,
+                "/status",
+                "test_failed",
+            ),
+            (patch("/api/v1/tasks/1", "application/merge-patch+json", json!({ "title": "" })), StatusCode::BAD_REQUEST, "title", "required"),
+        ];
+        for (request, status, field, code) in requests {
+            let response = test::call_service(&app, request.to_request()).await;
+            assert_eq!(response.status(), status);
+            let body: Value = test::read_body_json(response).await;
+            assert_eq!(body["errors"].as_array().unwrap().len(), 1);
+            assert_eq!((body["errors"][0]["field"].as_str(), body["errors"][0]["code"].as_str()), (Some(field), Some(code)));
+        }
+
+        let response = test::call_service(&app, patch("/api/v1/tasks/1", "application/json", json!({ "title": "New" })).to_request()).await;
+        assert_eq!(response.status(), StatusCode::UNSUPPORTED_MEDIA_TYPE);
+        assert_eq!(response.headers().get("accept-patch").unwrap(), "application/json-patch+json, application/merge-patch+json");
+        let response = test::call_service(&app, patch("/api/v1/tasks/1", "application/json-patch+json", json!({ "op": "add" })).to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = test::read_body_json(response).await;
+        assert_api_error(&body, "malformed_input");
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/1").to_request()).await;
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!((&body["title"], &body["status"]), (&json!("Task"), &json!(false)));
+    }
+
+    #[tokio::test]
+    async fn legacy_paths_are_deprecated_aliases() {
+        // Проверяем, что старые пути отвечают как /api/v1, но с заголовками Deprecation и Sunset
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/task/1").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("deprecation").unwrap(), "@1792281600");
+        assert_eq!(response.headers().get("sunset").unwrap(), "Sun, 18 Apr 2027 00:00:00 GMT");
+        assert!(response.headers().get("link").unwrap().to_str().unwrap().contains("rel=\"deprecation\""));
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body["title"], "Task");
+
+        let request = test::TestRequest::post()
+            .uri("/tasks")
+            .set_json(json!({ "title": "New", "description": "Desc" }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert!(response.headers().contains_key("deprecation"));
+        assert_eq!(response.headers().get("location").unwrap(), "/api/v1/tasks/2");
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/2").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert!(!response.headers().contains_key("deprecation"));
+        let response = test::call_service(&app, test::TestRequest::get().uri("/tasks/2").to_request()).await;
+        assert_eq!(response.status(), StatusCode::METHOD_NOT_ALLOWED);
+    }
+
+    #[tokio::test]
+    async fn batch_failure_reports_error_per_operation() {
+        // Проверяем, что сбой пакета описан в ответе для каждой операции
+        let config = ChaosConfig { failure_rate: 1.0, operations: vec![ChaosOperation::ExecuteBatch], ..Default::default() };
+        let service = service_with(config).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let request = test::TestRequest::post()
+            .uri("/api/v1/tasks/batch")
+            .set_json(json!({
+                "mode": "best_effort",
+                "operations": [{ "op": "toggle", "id": 1 }, { "op": "delete", "id": 1 }]
+            }))
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body: Value = test::read_body_json(response).await;
+        let results = body["results"].as_array().unwrap();
+        assert_eq!(results.len(), 2);
+        assert_api_error(&results[0]["error"], "unexpected_error");
+        assert_api_error(&results[1]["error"], "aborted");
+    }
+
+    #[tokio::test]
+    async fn undo_and_redo_return_no_content() {
+        // Проверяем, что отмена и повтор отвечают 204 без тела, а пустая история - 409
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+        let session = ("X-Session-Id", "phone");
+        test::call_service(&app, test::TestRequest::delete().uri("/api/v1/tasks/1").insert_header(session).to_request()).await;
+
+        let response = test::call_service(&app, test::TestRequest::post().uri("/api/v1/undo").insert_header(session).to_request()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(test::read_body(response).await.is_empty());
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/1").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+
+        let response = test::call_service(&app, test::TestRequest::post().uri("/api/v1/redo").insert_header(session).to_request()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        assert!(test::read_body(response).await.is_empty());
+        let response = test::call_service(&app, test::TestRequest::post().uri("/api/v1/redo").insert_header(session).to_request()).await;
+        assert_eq!(response.status(), StatusCode::CONFLICT);
+    }
+
+    #[tokio::test]
+    async fn latency_alone_does_not_fail_requests() {
+        // Проверяем, что одна лишь задержка не приводит к ошибке
+        let service = service_with(ChaosConfig { max_latency: Duration::from_millis(5), ..Default::default() }).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+    }
+
+    #[tokio::test]
+    async fn export_sets_content_type_and_filters() {
+        // Проверяем тип содержимого выгрузки и отбор задач по параметрам запроса
+        let service = service_with(ChaosConfig::default()).await;
+        service.lock().await.create("test", draft("Second", "Desc")).await.unwrap();
+        service.lock().await.toggle("test", 1).await.unwrap();
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/export?format=markdown&status=true").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "text/markdown; charset=utf-8");
+        assert_eq!(response.headers().get("content-disposition").unwrap(), "attachment; filename=\"tasks.md\"");
+        assert_eq!(test::read_body(response).await, "- [x] Task\n");
+    }
+
+    #[tokio::test]
+    async fn export_streams_ndjson() {
+        // Проверяем, что NDJSON отдается построчно
+        let service = service_with(ChaosConfig::default()).await;
+        service.lock().await.create("test", draft("Second", "Desc")).await.unwrap();
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/export?format=ndjson").to_request()).await;
+        assert_eq!(response.headers().get("content-type").unwrap(), "application/x-ndjson");
+        let body = test::read_body(response).await;
+        let lines: Vec<Value> = std::str::from_utf8(&body).unwrap().lines().map(|line| serde_json::from_str(line).unwrap()).collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1]["title"], "Second");
+    }
+
+    #[tokio::test]
+    async fn export_rejects_unknown_format() {
+        // Проверяем, что неизвестный формат отклоняется
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks/export?format=xml").to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+    }
+
+    #[tokio::test]
+    async fn calendar_feed_lists_tasks() {
+        // Проверяем тип содержимого календаря и наличие в нем задачи
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let response = test::call_service(&app, test::TestRequest::get().uri("/api/v1/tasks.ics").to_request()).await;
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers().get("content-type").unwrap(), "text/calendar; charset=utf-8");
+        let body = test::read_body(response).await;
+        let body = std::str::from_utf8(&body).unwrap();
+        assert!(body.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(body.contains("\r\nUID:task-1@clean-todo-demo\r\nDTSTAMP:"));
+        assert!(body.contains("\r\nSUMMARY:Task\r\n"));
+    }
+
+    #[tokio::test]
+    async fn import_returns_report() {
+        // Проверяем отчет загрузки и код 207, когда часть записей отклонена
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service.clone()).configure(web_controller::configure)).await;
+
+        let request = test::TestRequest::post()
+            .uri("/api/v1/tasks/import?format=csv&conflict=skip")
+            .set_payload("id,title,description\n1,Task,Desc\n,New,Desc\n,,Desc\n")
+            .to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::MULTI_STATUS);
+        let body: Value = test::read_body_json(response).await;
+        assert_eq!(body["created"], 1);
+        assert_eq!(body["skipped"], 1);
+        assert_eq!(body["rejected"], 1);
+        assert_eq!(body["rows"][1]["id"], 2);
+        assert_api_error(&body["rows"][2]["error"], "validation_failed");
+        assert_eq!(service.lock().await.get_all().await.unwrap().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn import_rejects_unparsable_file() {
+        // Проверяем, что файл, который нельзя разобрать, отклоняется целиком
+        let service = service_with(ChaosConfig::default()).await;
+        let app = test::init_service(App::new().app_data(service).configure(web_controller::configure)).await;
+
+        let request = test::TestRequest::post().uri("/api/v1/tasks/import?format=json").set_payload("[{").to_request();
+        let response = test::call_service(&app, request).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let body: Value = test::read_body_json(response).await;
+        assert_api_error(&body, "malformed_input");
+    }
+}
@@ -0,0 +1,219 @@
+use actix_web::{HttpRequest, HttpResponse, Responder, http::header, web};
+
+use crate::application::webhooks::WebhookService;
+use crate::domain::webhooks::WebhookId;
+use crate::presentation::dto::{ApiErrorResponse, CreateWebhookRequest, DeliveryResponse, WebhookResponse};
+
+#[utoipa::path(
+    post,
+    path = "/webhooks",
+    tag = "webhooks",
+    summary = "Subscribe a URL to task changes",
+    description = "Every matching change is POSTed to `url` with the same JSON body as an `EventResponse` of the \
+        event stream. The request carries `X-Webhook-Event`, `X-Webhook-Delivery`, `X-Webhook-Timestamp` and \
+        `X-Webhook-Signature: sha256=<hex>`, an HMAC-SHA256 of `<timestamp>.<body>` keyed with `secret`. \
+        Deliveries that fail or get a non-2xx response are retried with exponential backoff. \
+        Loopback, link-local and private addresses are refused unless the server runs with \
+        `--webhook-allow-private-hosts`.",
+    request_body = CreateWebhookRequest,
+    responses(
+        (status = 201, description = "Webhook created", body = WebhookResponse,
+            headers(("Location" = String, description = "Address of the created webhook"))),
+        (status = 400, description = "URL or secret is invalid, the URL points to a private address, or the body is malformed", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 415, description = "Body is not JSON", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn create_webhook(
+    req: HttpRequest,
+    webhook_service: web::Data<WebhookService>,
+    request: web::Json<CreateWebhookRequest>,
+) -> impl Responder {
+    println!("create_webhook url: {}, events: {:?}", request.url, request.events);
+    match webhook_service.create(request.into_inner().into()).await {
+        Ok(webhook) => {
+            println!("Webhook created: {}", webhook.id);
+            let mut response = HttpResponse::Created();
+            if let Ok(location) = req.url_for("webhook", [webhook.id.to_string()]) {
+                response.insert_header((header::LOCATION, location.path()));
+            }
+            response.json(WebhookResponse::from(webhook))
+        }
+        Err(e) => {
+            eprintln!("Error creating webhook: {:?}", e);
+            HttpResponse::from(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks",
+    tag = "webhooks",
+    summary = "List webhooks",
+    responses(
+        (status = 200, description = "All webhooks in creation order", body = Vec<WebhookResponse>),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_webhooks(webhook_service: web::Data<WebhookService>) -> impl Responder {
+    println!("get_webhooks");
+    match webhook_service.list().await {
+        Ok(webhooks) => HttpResponse::Ok().json(webhooks.into_iter().map(WebhookResponse::from).collect::<Vec<_>>()),
+        Err(e) => {
+            eprintln!("Error listing webhooks: {:?}", e);
+            HttpResponse::from(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    summary = "Get a webhook",
+    params(("id" = i64, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, description = "Webhook found", body = WebhookResponse),
+        (status = 404, description = "Webhook not found", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_webhook(webhook_service: web::Data<WebhookService>, id: web::Path<WebhookId>) -> impl Responder {
+    println!("get_webhook/{id}");
+    match webhook_service.get(*id).await {
+        Ok(webhook) => HttpResponse::Ok().json(WebhookResponse::from(webhook)),
+        Err(e) => {
+            eprintln!("Error getting webhook: {:?}", e);
+            HttpResponse::from(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    delete,
+    path = "/webhooks/{id}",
+    tag = "webhooks",
+    summary = "Delete a webhook",
+    description = "Removes the webhook together with its delivery log. Pending retries are abandoned.",
+    params(("id" = i64, Path, description = "Webhook ID")),
+    responses(
+        (status = 204, description = "Webhook deleted"),
+        (status = 404, description = "Webhook not found", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn delete_webhook(webhook_service: web::Data<WebhookService>, id: web::Path<WebhookId>) -> impl Responder {
+    println!("delete_webhook/{id}");
+    match webhook_service.delete(*id).await {
+        Ok(()) => HttpResponse::NoContent().finish(),
+        Err(e) => {
+            eprintln!("Error deleting webhook: {:?}", e);
+            HttpResponse::from(e)
+        }
+    }
+}
+
+#[utoipa::path(
+    get,
+    path = "/webhooks/{id}/deliveries",
+    tag = "webhooks",
+    summary = "Get the delivery log of a webhook",
+    description = "Every delivery attempt, oldest first. Retries of one notification share the `delivery` ID.",
+    params(("id" = i64, Path, description = "Webhook ID")),
+    responses(
+        (status = 200, description = "Delivery attempts", body = Vec<DeliveryResponse>),
+        (status = 404, description = "Webhook not found", body = ApiErrorResponse, content_type = "application/problem+json"),
+        (status = 500, description = "Storage failure", body = ApiErrorResponse, content_type = "application/problem+json"),
+    )
+)]
+pub async fn get_deliveries(webhook_service: web::Data<WebhookService>, id: web::Path<WebhookId>) -> impl Responder {
+    println!("get_deliveries/{id}");
+    match webhook_service.deliveries(*id).await {
+        Ok(attempts) => HttpResponse::Ok().json(attempts.into_iter().map(DeliveryResponse::from).collect::<Vec<_>>()),
+        Err(e) => {
+            eprintln!("Error getting deliveries: {:?}", e);
+            HttpResponse::from(e)
+        }
+    }
+}
+
+// Проверяем управление подписками через API
+#[cfg(test)]
+mod webhooks_api_tests {
+    use std::sync::Arc;
+
+    use actix_web::{App, http::StatusCode, test, web};
+    use chrono::Utc;
+    use serde_json::{Value, json};
+    use sqlx::SqlitePool;
+    use tokio::sync::Mutex;
+
+    use crate::application::services::TaskService;
+    use crate::application::webhooks::WebhookService;
+    use crate::domain::events::ChangeKind;
+    use crate::domain::webhooks::{DeliveryAttempt, WebhookRepository};
+    use crate::infrastructure::in_memory::InMemoryTaskRepository;
+    use crate::infrastructure::schema::MIGRATOR;
+    use crate::infrastructure::sqlite_webhooks::SqliteWebhookRepository;
+    use crate::presentation::web_controller;
+
+    #[tokio::test]
+    async fn webhooks_are_managed_without_exposing_secret() {
+        // Проверяем создание, чтение, журнал доставки и удаление подписки
+        let pool = SqlitePool::connect("sqlite::memory:").await.unwrap();
+        MIGRATOR.run(&pool).await.unwrap();
+        let repository = Arc::new(SqliteWebhookRepository::new(pool));
+        let task_service = web::Data::new(Mutex::new(TaskService::new(Box::new(InMemoryTaskRepository::new()))));
+        let app = test::init_service(
+            App::new()
+                .app_data(task_service)
+                .app_data(web::Data::new(WebhookService::new(repository.clone())))
+                .configure(web_controller::configure),
+        )
+        .await;
+
+        let body = json!({ "url": "https://ci.example/hook", "events": ["created", "toggled"], "secret": "s3cr3t" });
+        let response = test::call_service(&app, test::TestRequest::post().uri("/api/v1/webhooks").set_json(body).to_request()).await;
+        assert_eq!(response.status(), StatusCode::CREATED);
+        assert_eq!(response.headers().get("location").unwrap(), "/api/v1/webhooks/1");
+        let created: Value = test::read_body_json(response).await;
+        assert_eq!((created["url"].as_str(), created["events"].clone()), (Some("https://ci.example/hook"), json!(["created", "toggled"])));
+        assert!(created.get("secret").is_none());
+
+        let body = json!({ "url": "mailto:ops@example.com", "secret": "s3cr3t" });
+        let response = test::call_service(&app, test::TestRequest::post().uri("/api/v1/webhooks").set_json(body).to_request()).await;
+        assert_eq!(response.status(), StatusCode::BAD_REQUEST);
+        let problem: Value = test::read_body_json(response).await;
+        assert_eq!((problem["code"].as_str(), problem["errors"][0]["field"].as_str()), (Some("validation_failed"), Some("url")));
+        let body = json!({ "url": "http://169.254.169.254/latest/meta-data", "secret": "s3cr3t" });
+        let response = test::call_service(&app, test::TestRequest::post().uri("/api/v1/webhooks").set_json(body).to_request()).await;
+        let problem: Value = test::read_body_json(response).await;
+        assert_eq!(problem["errors"][0]["code"], "private_host");
+
+        let attempt = DeliveryAttempt {
+            webhook_id: 1,
+            delivery: "d1".to_string(),
+            event_id: 1,
+            event: Some(ChangeKind::Created),
+            attempt: 1,
+            attempted_at: Utc::now(),
+            status_code: Some(503),
+            error: Some("Receiver responded with status 503".to_string()),
+            succeeded: false,
+        };
+        repository.record(&attempt).await.unwrap();
+        let request = test::TestRequest::get().uri("/api/v1/webhooks/1/deliveries").to_request();
+        let deliveries: Value = test::call_and_read_body_json(&app, request).await;
+        assert_eq!((deliveries[0]["status_code"].as_u64(), deliveries[0]["succeeded"].as_bool()), (Some(503), Some(false)));
+
+        let listed: Value = test::call_and_read_body_json(&app, test::TestRequest::get().uri("/api/v1/webhooks").to_request()).await;
+        assert_eq!(listed, json!([created]));
+        let response = test::call_service(&app, test::TestRequest::delete().uri("/api/v1/webhooks/1").to_request()).await;
+        assert_eq!(response.status(), StatusCode::NO_CONTENT);
+        for uri in ["/api/v1/webhooks/1", "/api/v1/webhooks/1/deliveries"] {
+            let response = test::call_service(&app, test::TestRequest::get().uri(uri).to_request()).await;
+            assert_eq!(response.status(), StatusCode::NOT_FOUND);
+        }
+    }
+}